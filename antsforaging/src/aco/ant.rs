@@ -0,0 +1,56 @@
+use core::fmt;
+use krabmaga::engine::agent::Agent;
+use krabmaga::engine::state::State;
+use std::hash::{Hash, Hasher};
+
+use crate::aco::state::AcoState;
+use examples_common::rng;
+
+// An ant of the colony optimization, which walks the whole graph at each step and hands its walk
+// over to the state, where the pheromones are updated once every ant is done.
+#[derive(Copy, Clone)]
+pub struct TourAnt {
+    // An unique id.
+    pub id: u32,
+}
+
+impl TourAnt {
+    pub fn new(id: u32) -> TourAnt {
+        TourAnt { id }
+    }
+}
+
+impl Agent for TourAnt {
+    /// Each ant walks the graph following the pheromones of the previous steps.
+    fn step(&mut self, state: &mut dyn State) {
+        let state = state.as_any().downcast_ref::<AcoState>().unwrap();
+        let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
+        match state.walk(&mut rng) {
+            Some(walk) => state.walks.write().unwrap().push((self.id, walk)),
+            None => *state.failed.write().unwrap() += 1,
+        }
+    }
+}
+
+impl Eq for TourAnt {}
+
+impl PartialEq for TourAnt {
+    fn eq(&self, other: &TourAnt) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Hash for TourAnt {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.id.hash(state);
+    }
+}
+
+impl fmt::Display for TourAnt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
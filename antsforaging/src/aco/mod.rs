@@ -0,0 +1,6 @@
+// Ant colony optimization on a weighted graph: the foraging of the grid model turned into a
+// solver for the TSP and shortest path instances of TSPLIB files.
+pub mod ant;
+pub mod params;
+pub mod state;
+pub mod tsplib;
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::aco::tsplib::Instance;
+
+// Settings of an ant colony optimization run on a TSPLIB instance: an Ant System, where every
+// ant builds a tour at each iteration and leaves pheromone along it, more the shorter it is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AcoParams {
+    // TSPLIB file of the graph
+    pub instance: Option<PathBuf>,
+    pub iterations: u64,
+    pub reps: u64,
+    // Seed of the random number generators, repetition `r` runs with `seed + r`
+    pub seed: u64,
+    // Ants building a tour at each iteration, 0 for one per node
+    pub ants: u32,
+    // Weight of the pheromone and of the inverse distance when an ant picks its next node
+    pub alpha: f64,
+    pub beta: f64,
+    // Share of its pheromone an arc keeps from an iteration to the next, as on the grid
+    pub evaporation: f64,
+    // Pheromone an ant leaves along its tour, divided by the tour length
+    pub deposit: f64,
+    // Extra ants retracing the best tour so far at each iteration, 0 for a plain Ant System
+    pub elitist: f64,
+    // Pheromone of every arc at the start, 0 to take the ants over the length of a nearest
+    // neighbour tour
+    pub initial_pheromone: f64,
+    // Nodes, numbered as in the file, of a shortest path problem: the ants walk from `source`
+    // to `target` instead of touring every node
+    pub source: Option<usize>,
+    pub target: Option<usize>,
+}
+
+impl Default for AcoParams {
+    fn default() -> Self {
+        AcoParams {
+            instance: None,
+            iterations: 100,
+            reps: 1,
+            seed: 0,
+            ants: 0,
+            alpha: 1.,
+            beta: 2.,
+            evaporation: 0.5,
+            deposit: 1.,
+            elitist: 0.,
+            initial_pheromone: 0.,
+            source: None,
+            target: None,
+        }
+    }
+}
+
+impl AcoParams {
+    // Load the parameters from a TOML or JSON scenario file, as `Params::from_file`.
+    pub fn from_file(path: &Path) -> Result<AcoParams, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: AcoParams| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    // Override a parameter by name, case-insensitively.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "instance" => self.instance = Some(PathBuf::from(value.trim())),
+            "iterations" => self.iterations = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "ants" => self.ants = parse(name, value)?,
+            "alpha" => self.alpha = parse(name, value)?,
+            "beta" => self.beta = parse(name, value)?,
+            "evaporation" => self.evaporation = parse(name, value)?,
+            "deposit" => self.deposit = parse(name, value)?,
+            "elitist" => self.elitist = parse(name, value)?,
+            "initial_pheromone" => self.initial_pheromone = parse(name, value)?,
+            "source" => self.source = Some(parse(name, value)?),
+            "target" => self.target = Some(parse(name, value)?),
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    // Check that every parameter is in its valid range. The instance is only read by
+    // `AcoState::new`, which checks the source and the target against its nodes.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        if self.evaporation <= 0. || self.evaporation > 1. {
+            return Err(format!(
+                "`evaporation` must be in (0, 1], found {}",
+                self.evaporation
+            ));
+        }
+        for (name, value) in [
+            ("alpha", self.alpha),
+            ("beta", self.beta),
+            ("deposit", self.deposit),
+            ("elitist", self.elitist),
+            ("initial_pheromone", self.initial_pheromone),
+        ] {
+            if !value.is_finite() || value < 0. {
+                return Err(format!(
+                    "`{}` must be a non-negative number, found {}",
+                    name, value
+                ));
+            }
+        }
+        match (self.source, self.target) {
+            (None, None) => Ok(()),
+            (Some(source), Some(target)) if source == target => {
+                Err(String::from("`source` and `target` must differ"))
+            }
+            (Some(_), Some(_)) => Ok(()),
+            _ => Err(String::from("`source` and `target` must be given together")),
+        }
+    }
+
+    // Check that the source and the target are nodes of `instance`.
+    pub fn validate_endpoints(&self, instance: &Instance) -> Result<(), String> {
+        for node in self.source.into_iter().chain(self.target) {
+            if !(1..=instance.dimension).contains(&node) {
+                return Err(format!(
+                    "node {} is not in 1..{} of the instance",
+                    node, instance.dimension
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // The graph the ants walk on.
+    pub fn instance(&self) -> Result<Instance, String> {
+        match &self.instance {
+            Some(path) => Instance::read(path),
+            None => Err(String::from("no `instance` given")),
+        }
+    }
+
+    // Nodes of the shortest path problem, numbered from 0, or None for a tour of every node.
+    pub fn endpoints(&self) -> Option<(usize, usize)> {
+        Some((self.source? - 1, self.target? - 1))
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
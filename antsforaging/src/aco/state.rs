@@ -0,0 +1,340 @@
+use crate::aco::ant::TourAnt;
+use crate::aco::params::AcoParams;
+use crate::aco::tsplib::Instance;
+use crate::model::pheromone;
+use core::fmt;
+use examples_common::observer::Observer;
+use examples_common::rng::ModelRng;
+use krabmaga::engine::fields::field::Field;
+use krabmaga::engine::fields::network::{EdgeOptions, Network};
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use krabmaga::rand::Rng;
+use std::any::Any;
+use std::sync::RwLock;
+
+// A walk of an ant on the graph: the nodes it went through, numbered from 0, and its length. A
+// tour ends on the node it started from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Walk {
+    pub nodes: Vec<usize>,
+    pub length: f64,
+}
+
+// Nodes of the walk as numbered in the TSPLIB file.
+impl fmt::Display for Walk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nodes: Vec<String> = self.nodes.iter().map(|u| (u + 1).to_string()).collect();
+        write!(f, "{}", nodes.join(" "))
+    }
+}
+
+// The state of an ant colony optimization run: the graph of the instance, as a `Network` whose
+// edges are weighted by the distances, and the pheromone of each of its arcs. Each step is an
+// iteration, where every ant walks the graph and the pheromones are updated from the walks.
+pub struct AcoState {
+    pub params: AcoParams,
+    pub instance: Instance,
+    pub network: Network<u32, String>,
+    // Arcs out of each node, with their length, read from the network once it's built
+    neighbours: Vec<Vec<(usize, f64)>>,
+    // Pheromone of the arc from u to v, `pheromone[u * dimension + v]`
+    pub pheromone: Vec<f64>,
+    // Walks completed by the ants during the current step, with the id of their ant
+    pub walks: RwLock<Vec<(u32, Walk)>>,
+    // Ants stuck during the current step, with no node left to go to
+    pub failed: RwLock<u32>,
+    // Shortest walk since the start of the run
+    pub best: Option<Walk>,
+    // Shortest walk of each iteration, None if every ant got stuck
+    pub history: Vec<Option<Walk>>,
+    pub step: u64,
+    pub observer: Observer,
+}
+
+impl State for AcoState {
+    fn reset(&mut self) {
+        self.step = 0;
+        self.network = Network::new(self.instance.directed);
+        self.neighbours = Vec::new();
+        self.pheromone = Vec::new();
+        self.walks = RwLock::new(Vec::new());
+        self.failed = RwLock::new(0);
+        self.best = None;
+        self.history = Vec::new();
+        self.observer.reset();
+    }
+
+    fn init(&mut self, schedule: &mut Schedule) {
+        self.reset();
+        let n = self.instance.dimension;
+
+        // Graph generation, the node of id u being the node u of the instance
+        for u in 0..n {
+            self.network.add_node(u as u32);
+        }
+        for (u, v) in self.instance.edges() {
+            let distance = self.instance.distance(u, v) as f32;
+            self.network
+                .add_edge(u as u32, v as u32, EdgeOptions::Weighted(distance));
+        }
+        self.network.update();
+        // the ants look the arcs up at every move, read them once rather than clone them each time
+        self.neighbours = (0..n)
+            .map(|u| {
+                let mut arcs: Vec<(usize, f64)> = self
+                    .network
+                    .get_edges(u as u32)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|edge| (edge.v as usize, edge.weight.unwrap_or(0.) as f64))
+                    .filter(|&(v, _)| v != u)
+                    .collect();
+                arcs.sort_by_key(|&(v, _)| v);
+                arcs.dedup_by_key(|(v, _)| *v);
+                arcs
+            })
+            .collect();
+
+        let ants = self.num_ants();
+        let initial = if self.params.initial_pheromone > 0. {
+            self.params.initial_pheromone
+        } else {
+            match self.nearest_neighbour_walk() {
+                Some(walk) if walk.length > 0. => ants as f64 / walk.length,
+                _ => 1.,
+            }
+        };
+        self.pheromone = vec![initial; n * n];
+
+        for id in 0..ants {
+            schedule.schedule_repeating(Box::new(TourAnt::new(id)), 0., 0);
+        }
+    }
+
+    fn update(&mut self, step: u64) {
+        self.step = step;
+    }
+
+    fn before_step(&mut self, _schedule: &mut Schedule) {
+        self.walks.write().unwrap().clear();
+        *self.failed.write().unwrap() = 0;
+    }
+
+    fn after_step(&mut self, schedule: &mut Schedule) {
+        // the walks are sorted by ant, so that the pheromones don't depend on the schedule order
+        let walks: Vec<Walk> = {
+            let mut walks = self.walks.write().unwrap();
+            walks.sort_by_key(|(id, _)| *id);
+            walks.drain(..).map(|(_, walk)| walk).collect()
+        };
+        let failed = *self.failed.read().unwrap();
+
+        let iteration_best = walks
+            .iter()
+            .min_by(|a, b| a.length.total_cmp(&b.length))
+            .cloned();
+        if let Some(walk) = &iteration_best {
+            if self
+                .best
+                .as_ref()
+                .is_none_or(|best| walk.length < best.length)
+            {
+                self.best = Some(walk.clone());
+            }
+        }
+
+        // Pheromone update: evaporation, then a deposit on the arcs of every walk and, with
+        // elitist ants, on the best walk so far
+        let (evaporation, deposit) = (self.params.evaporation, self.params.deposit);
+        let mut deposits = vec![0.; self.pheromone.len()];
+        for walk in &walks {
+            if walk.length > 0. {
+                self.deposit(&mut deposits, &walk.nodes, deposit / walk.length);
+            }
+        }
+        if let Some(best) = &self.best {
+            if self.params.elitist > 0. && best.length > 0. {
+                let amount = self.params.elitist * deposit / best.length;
+                self.deposit(&mut deposits, &best.nodes, amount);
+            }
+        }
+        // no arc is ever cleared, the pheromone of an arc off every walk only fades
+        for (value, &deposited) in self.pheromone.iter_mut().zip(&deposits) {
+            *value = pheromone::update(*value, evaporation, deposited, 0.);
+        }
+
+        let length = |walk: Option<&Walk>| walk.map_or(f64::NAN, |walk| walk.length);
+        let mean_length = if walks.is_empty() {
+            f64::NAN
+        } else {
+            walks.iter().map(|walk| walk.length).sum::<f64>() / walks.len() as f64
+        };
+        self.observer.record(
+            schedule.step + 1,
+            &[
+                ("best_length", length(self.best.as_ref())),
+                ("iteration_best", length(iteration_best.as_ref())),
+                ("mean_length", mean_length),
+                ("failed_walks", failed as f64),
+            ],
+        );
+        self.history.push(iteration_best);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn as_state_mut(&mut self) -> &mut dyn State {
+        self
+    }
+
+    fn as_state(&self) -> &dyn State {
+        self
+    }
+}
+
+impl AcoState {
+    // The state of a run with the given parameters, whose instance is read here. Fails when
+    // the instance cannot be read, or does not have the source and the target among its nodes.
+    pub fn new(params: AcoParams) -> Result<AcoState, String> {
+        let instance = params.instance()?;
+        params.validate_endpoints(&instance)?;
+        Ok(AcoState {
+            network: Network::new(instance.directed),
+            neighbours: Vec::new(),
+            pheromone: Vec::new(),
+            walks: RwLock::new(Vec::new()),
+            failed: RwLock::new(0),
+            best: None,
+            history: Vec::new(),
+            step: 0,
+            params,
+            instance,
+            observer: Observer::new(),
+        })
+    }
+
+    // Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    // `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
+
+    pub fn num_ants(&self) -> u32 {
+        if self.params.ants == 0 {
+            self.instance.dimension as u32
+        } else {
+            self.params.ants
+        }
+    }
+
+    // A walk of an ant: from a random node through every other node and back for a tour, from
+    // the source to the target for a shortest path. At each move the ant picks a node it hasn't
+    // been through yet, with a probability proportional to pheromone^alpha / distance^beta. None
+    // if the ant gets stuck.
+    pub fn walk(&self, rng: &mut ModelRng) -> Option<Walk> {
+        let (alpha, beta) = (self.params.alpha, self.params.beta);
+        let start = match self.params.endpoints() {
+            Some((source, _)) => source,
+            None => rng.random_range(0..self.instance.dimension),
+        };
+        self.construct(start, |u, arcs| {
+            let weights: Vec<f64> = arcs
+                .iter()
+                .map(|&(v, distance)| {
+                    self.pheromone(u, v).powf(alpha) * (1. / distance.max(1e-9)).powf(beta)
+                })
+                .collect();
+            let total: f64 = weights.iter().sum();
+            // the pheromones can fade to nothing on long runs, the ant then picks at random
+            if !total.is_finite() || total <= 0. {
+                return rng.random_range(0..arcs.len());
+            }
+            let mut pick = rng.random_range(0.0..total);
+            for (i, weight) in weights.iter().enumerate() {
+                if pick < *weight {
+                    return i;
+                }
+                pick -= weight;
+            }
+            arcs.len() - 1
+        })
+    }
+
+    // The walk of an ant always going to the nearest node it hasn't been through, from the first
+    // node for a tour.
+    pub fn nearest_neighbour_walk(&self) -> Option<Walk> {
+        let start = self.params.endpoints().map_or(0, |(source, _)| source);
+        self.construct(start, |_, arcs| {
+            (0..arcs.len())
+                .min_by(|&a, &b| arcs[a].1.total_cmp(&arcs[b].1))
+                .unwrap()
+        })
+    }
+
+    pub fn pheromone(&self, u: usize, v: usize) -> f64 {
+        self.pheromone[u * self.instance.dimension + v]
+    }
+
+    // Walk from `start`, `pick` choosing the next node among the arcs to the nodes not yet
+    // visited, given as (node, distance).
+    fn construct(
+        &self,
+        start: usize,
+        mut pick: impl FnMut(usize, &[(usize, f64)]) -> usize,
+    ) -> Option<Walk> {
+        let n = self.instance.dimension;
+        let target = self.params.endpoints().map(|(_, target)| target);
+        let mut visited = vec![false; n];
+        let mut nodes = vec![start];
+        let mut length = 0.;
+        let mut u = start;
+        visited[u] = true;
+        while Some(u) != target && nodes.len() < n {
+            let arcs: Vec<(usize, f64)> = self.neighbours[u]
+                .iter()
+                .copied()
+                .filter(|&(v, _)| !visited[v])
+                .collect();
+            if arcs.is_empty() {
+                return None;
+            }
+            let (v, distance) = arcs[pick(u, &arcs)];
+            visited[v] = true;
+            nodes.push(v);
+            length += distance;
+            u = v;
+        }
+        match target {
+            Some(target) if u != target => None,
+            Some(_) => Some(Walk { nodes, length }),
+            // a tour goes back to its first node
+            None => {
+                let &(_, distance) = self.neighbours[u].iter().find(|&&(v, _)| v == start)?;
+                nodes.push(start);
+                Some(Walk {
+                    nodes,
+                    length: length + distance,
+                })
+            }
+        }
+    }
+
+    // Add `amount` to the `deposits` of the arcs of a walk, laid out as `pheromone`, both ways on
+    // a symmetric instance.
+    fn deposit(&self, deposits: &mut [f64], nodes: &[usize], amount: f64) {
+        let n = self.instance.dimension;
+        for arc in nodes.windows(2) {
+            deposits[arc[0] * n + arc[1]] += amount;
+            if !self.instance.directed {
+                deposits[arc[1] * n + arc[0]] += amount;
+            }
+        }
+    }
+}
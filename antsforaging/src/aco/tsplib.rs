@@ -0,0 +1,297 @@
+use std::fs;
+use std::path::Path;
+
+// A graph instance read from a TSPLIB file: its nodes, numbered from 0 (from 1 in the file),
+// the distance of every pair of nodes and, for sparse graphs, the edges.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instance {
+    pub name: String,
+    // Asymmetric instance (ATSP): the distance from u to v may differ from the one from v to u
+    pub directed: bool,
+    pub dimension: usize,
+    // Distances row by row, `distances[u * dimension + v]`
+    distances: Vec<f64>,
+    // Edges of the graph, every pair of nodes when the file lists none
+    edges: Option<Vec<(usize, usize)>>,
+}
+
+impl Instance {
+    // Read a TSPLIB file, see `Instance::parse`.
+    pub fn read(path: &Path) -> Result<Instance, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read instance `{}`: {}", path.display(), e))?;
+        Instance::parse(&content)
+            .map_err(|e| format!("invalid instance `{}`: {}", path.display(), e))
+    }
+
+    // Parse a TSP or ATSP instance in the TSPLIB format. Distances are given by the node
+    // coordinates (EUC_2D, CEIL_2D, MAN_2D, MAX_2D, ATT or GEO weights) or by an explicit matrix
+    // (FULL_MATRIX, UPPER_ROW, LOWER_ROW, UPPER_DIAG_ROW or LOWER_DIAG_ROW). An EDGE_DATA_SECTION,
+    // as an EDGE_LIST or an ADJ_LIST, restricts the graph to the edges it lists.
+    pub fn parse(content: &str) -> Result<Instance, String> {
+        let mut name = String::new();
+        let mut kind = String::from("TSP");
+        let mut dimension = 0;
+        let mut weight_type = String::new();
+        let mut weight_format = String::from("FULL_MATRIX");
+        let mut edge_format = String::from("EDGE_LIST");
+        let mut coords: Option<Vec<(f64, f64)>> = None;
+        let mut weights: Option<Vec<f64>> = None;
+        let mut edges = None;
+
+        let mut lines = content.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (line, ""),
+            };
+            match key.to_ascii_uppercase().as_str() {
+                "NAME" => name = value.to_string(),
+                "TYPE" => kind = value.to_ascii_uppercase(),
+                "DIMENSION" => {
+                    dimension = value
+                        .parse()
+                        .map_err(|_| format!("invalid DIMENSION `{}`", value))?
+                }
+                "EDGE_WEIGHT_TYPE" => weight_type = value.to_ascii_uppercase(),
+                "EDGE_WEIGHT_FORMAT" => weight_format = value.to_ascii_uppercase(),
+                "EDGE_DATA_FORMAT" => edge_format = value.to_ascii_uppercase(),
+                "COMMENT" | "CAPACITY" | "NODE_COORD_TYPE" | "DISPLAY_DATA_TYPE" => {}
+                "NODE_COORD_SECTION" => {
+                    check_dimension(dimension)?;
+                    let mut nodes = vec![None; dimension];
+                    for _ in 0..dimension {
+                        let line = lines.next().ok_or("truncated NODE_COORD_SECTION")?;
+                        let numbers = numbers(line)?;
+                        let node = node_index(numbers.first().copied(), dimension)?;
+                        match numbers[..] {
+                            [_, x, y, ..] => nodes[node] = Some((x, y)),
+                            _ => return Err(format!("invalid node coordinates `{}`", line)),
+                        }
+                    }
+                    let nodes: Option<Vec<_>> = nodes.into_iter().collect();
+                    coords = Some(nodes.ok_or("a node has no coordinates")?);
+                }
+                "EDGE_WEIGHT_SECTION" => {
+                    check_dimension(dimension)?;
+                    let count = matrix_size(&weight_format, dimension)?;
+                    let mut values = Vec::with_capacity(count);
+                    while values.len() < count {
+                        let line = lines.next().ok_or("truncated EDGE_WEIGHT_SECTION")?;
+                        values.extend(numbers(line)?);
+                    }
+                    weights = Some(values);
+                }
+                "EDGE_DATA_SECTION" => {
+                    check_dimension(dimension)?;
+                    // each node of an ADJ_LIST is followed by its neighbours, up to a -1
+                    let adjacency = match edge_format.as_str() {
+                        "EDGE_LIST" => false,
+                        "ADJ_LIST" => true,
+                        format => return Err(format!("unsupported EDGE_DATA_FORMAT `{}`", format)),
+                    };
+                    let mut listed = Vec::new();
+                    let mut node = None;
+                    'section: loop {
+                        let line = lines.next().ok_or("truncated EDGE_DATA_SECTION")?;
+                        for value in numbers(line)? {
+                            match node {
+                                // a -1 in place of a node ends the section
+                                None if value == -1. => break 'section,
+                                None => node = Some(node_index(Some(value), dimension)?),
+                                Some(_) if adjacency && value == -1. => node = None,
+                                Some(u) => {
+                                    listed.push((u, node_index(Some(value), dimension)?));
+                                    if !adjacency {
+                                        node = None;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    edges = Some(listed);
+                }
+                "EOF" => break,
+                _ => return Err(format!("unknown keyword `{}`", key)),
+            }
+        }
+
+        check_dimension(dimension)?;
+        let directed = match kind.as_str() {
+            "TSP" => false,
+            "ATSP" => true,
+            kind => return Err(format!("unsupported TYPE `{}`, expected TSP or ATSP", kind)),
+        };
+        let mut distances = vec![0.; dimension * dimension];
+        match weight_type.as_str() {
+            "EXPLICIT" => {
+                let weights = weights.ok_or("EXPLICIT weights without an EDGE_WEIGHT_SECTION")?;
+                fill_matrix(&mut distances, dimension, &weight_format, &weights)?;
+            }
+            _ => {
+                let coords = coords.ok_or("no NODE_COORD_SECTION")?;
+                let distance = coordinate_distance(&weight_type)?;
+                for u in 0..dimension {
+                    for v in 0..dimension {
+                        if u != v {
+                            distances[u * dimension + v] = distance(coords[u], coords[v]);
+                        }
+                    }
+                }
+            }
+        }
+        if directed && weight_type == "EXPLICIT" && weight_format != "FULL_MATRIX" {
+            return Err(String::from("an ATSP instance needs a FULL_MATRIX"));
+        }
+        Ok(Instance {
+            name,
+            directed,
+            dimension,
+            distances,
+            edges,
+        })
+    }
+
+    pub fn distance(&self, u: usize, v: usize) -> f64 {
+        self.distances[u * self.dimension + v]
+    }
+
+    // Edges of the graph, from a node to another. Each edge of a symmetric instance is listed
+    // once, every pair of distinct nodes is an edge when the file lists none.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        match &self.edges {
+            Some(edges) => edges.iter().copied().filter(|(u, v)| u != v).collect(),
+            None => (0..self.dimension)
+                .flat_map(|u| (0..self.dimension).map(move |v| (u, v)))
+                .filter(|&(u, v)| if self.directed { u != v } else { u < v })
+                .collect(),
+        }
+    }
+}
+
+fn numbers(line: &str) -> Result<Vec<f64>, String> {
+    line.split_whitespace()
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| format!("invalid number `{}`", token))
+        })
+        .collect()
+}
+
+// Index of the node numbered `number` in the file.
+fn node_index(number: Option<f64>, dimension: usize) -> Result<usize, String> {
+    match number {
+        Some(number) if number >= 1. && number <= dimension as f64 && number.fract() == 0. => {
+            Ok(number as usize - 1)
+        }
+        Some(number) => Err(format!("node {} is not in 1..{}", number, dimension)),
+        None => Err(String::from("missing node number")),
+    }
+}
+
+// Number of values of an explicit matrix.
+// The sections are sized by the DIMENSION, which must be read before any of them.
+fn check_dimension(dimension: usize) -> Result<(), String> {
+    if dimension < 2 {
+        return Err(String::from(
+            "DIMENSION must be at least 2, and come before the data sections",
+        ));
+    }
+    Ok(())
+}
+
+fn matrix_size(format: &str, n: usize) -> Result<usize, String> {
+    match format {
+        "FULL_MATRIX" => Ok(n * n),
+        "UPPER_ROW" | "LOWER_ROW" => Ok(n * (n - 1) / 2),
+        "UPPER_DIAG_ROW" | "LOWER_DIAG_ROW" => Ok(n * (n + 1) / 2),
+        format => Err(format!("unsupported EDGE_WEIGHT_FORMAT `{}`", format)),
+    }
+}
+
+fn fill_matrix(
+    distances: &mut [f64],
+    n: usize,
+    format: &str,
+    weights: &[f64],
+) -> Result<(), String> {
+    let mut values = weights.iter().copied();
+    let mut set = |u: usize, v: usize, symmetric: bool| {
+        let value = values.next().unwrap_or(0.);
+        distances[u * n + v] = value;
+        if symmetric {
+            distances[v * n + u] = value;
+        }
+    };
+    match format {
+        "FULL_MATRIX" => (0..n).for_each(|u| (0..n).for_each(|v| set(u, v, false))),
+        "UPPER_ROW" => (0..n).for_each(|u| (u + 1..n).for_each(|v| set(u, v, true))),
+        "LOWER_ROW" => (0..n).for_each(|u| (0..u).for_each(|v| set(u, v, true))),
+        "UPPER_DIAG_ROW" => (0..n).for_each(|u| (u..n).for_each(|v| set(u, v, true))),
+        "LOWER_DIAG_ROW" => (0..n).for_each(|u| (0..=u).for_each(|v| set(u, v, true))),
+        format => return Err(format!("unsupported EDGE_WEIGHT_FORMAT `{}`", format)),
+    }
+    Ok(())
+}
+
+// Distance of two nodes from their coordinates.
+type Distance = fn((f64, f64), (f64, f64)) -> f64;
+
+// Distance function of a weight type, rounded as TSPLIB specifies for each type.
+fn coordinate_distance(weight_type: &str) -> Result<Distance, String> {
+    fn nint(x: f64) -> f64 {
+        (x + 0.5).floor()
+    }
+    fn euc_2d(a: (f64, f64), b: (f64, f64)) -> f64 {
+        nint((a.0 - b.0).hypot(a.1 - b.1))
+    }
+    fn ceil_2d(a: (f64, f64), b: (f64, f64)) -> f64 {
+        (a.0 - b.0).hypot(a.1 - b.1).ceil()
+    }
+    fn man_2d(a: (f64, f64), b: (f64, f64)) -> f64 {
+        nint((a.0 - b.0).abs() + (a.1 - b.1).abs())
+    }
+    fn max_2d(a: (f64, f64), b: (f64, f64)) -> f64 {
+        nint((a.0 - b.0).abs()).max(nint((a.1 - b.1).abs()))
+    }
+    // pseudo-Euclidean distance of the att48 and att532 instances
+    fn att(a: (f64, f64), b: (f64, f64)) -> f64 {
+        let r = (((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)) / 10.).sqrt();
+        let t = nint(r);
+        if t < r {
+            t + 1.
+        } else {
+            t
+        }
+    }
+    // great circle distance in km, coordinates being latitude and longitude as DDD.MM
+    fn geo(a: (f64, f64), b: (f64, f64)) -> f64 {
+        // the truncated value of the TSPLIB reference code, the optimal tours are given for it
+        #[allow(clippy::approx_constant)]
+        const PI: f64 = 3.141592;
+        const RRR: f64 = 6378.388;
+        let radians = |x: f64| {
+            let degrees = x.trunc();
+            PI * (degrees + 5. * (x - degrees) / 3.) / 180.
+        };
+        let (lat_a, lon_a, lat_b, lon_b) = (radians(a.0), radians(a.1), radians(b.0), radians(b.1));
+        let q1 = (lon_a - lon_b).cos();
+        let q2 = (lat_a - lat_b).cos();
+        let q3 = (lat_a + lat_b).cos();
+        (RRR * (0.5 * ((1. + q1) * q2 - (1. - q1) * q3)).acos() + 1.).trunc()
+    }
+    match weight_type {
+        "EUC_2D" => Ok(euc_2d),
+        "CEIL_2D" => Ok(ceil_2d),
+        "MAN_2D" => Ok(man_2d),
+        "MAX_2D" => Ok(max_2d),
+        "ATT" => Ok(att),
+        "GEO" => Ok(geo),
+        "" => Err(String::from("no EDGE_WEIGHT_TYPE")),
+        weight_type => Err(format!("unsupported EDGE_WEIGHT_TYPE `{}`", weight_type)),
+    }
+}
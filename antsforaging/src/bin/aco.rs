@@ -0,0 +1,174 @@
+use clap::{CommandFactory, Parser};
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use antsforaging::aco::params::AcoParams;
+use antsforaging::aco::state::AcoState;
+use examples_common::cli::parse_key_value;
+use examples_common::observer::{Format, Observer, Output};
+
+/// Ant colony optimization on a TSPLIB instance, the graph counterpart of the ants foraging
+/// simulation.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// TOML or JSON scenario file with the solver parameters
+    #[arg(long, value_name = "FILE")]
+    scenario: Option<PathBuf>,
+
+    /// TSPLIB file of the graph
+    #[arg(long, value_name = "FILE")]
+    instance: Option<PathBuf>,
+
+    /// Number of iterations of each repetition
+    #[arg(long)]
+    iterations: Option<u64>,
+
+    /// Number of repetitions
+    #[arg(long)]
+    reps: Option<u64>,
+
+    /// Seed of the first repetition, the next ones use the following seeds
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Ants walking the graph at each iteration, 0 for one per node
+    #[arg(long)]
+    ants: Option<u32>,
+
+    /// Start node of a shortest path problem, numbered as in the instance
+    #[arg(long, requires = "target")]
+    source: Option<usize>,
+
+    /// End node of a shortest path problem, numbered as in the instance
+    #[arg(long, requires = "source")]
+    target: Option<usize>,
+
+    /// Override a solver parameter, e.g. `--param beta=5`
+    #[arg(long = "param", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+    params: Vec<(String, String)>,
+
+    /// Directory where the per-iteration metrics and the best tours are exported; nothing is
+    /// written without it
+    #[arg(long, value_name = "DIR")]
+    output: Option<PathBuf>,
+
+    /// Name of the run, metrics go to `<output>/<run-id>/rep_<rep>_seed_<seed>.csv` and the best
+    /// tour of each iteration to `<output>/<run-id>/tours_rep_<rep>_seed_<seed>.csv`
+    #[arg(long, default_value = "aco")]
+    run_id: String,
+}
+
+impl Cli {
+    /// Build the parameters of the run: the scenario file (or the defaults), then the named
+    /// flags, then every `--param`. The result is validated before being returned.
+    fn params(&self) -> Result<AcoParams, String> {
+        let mut params = match &self.scenario {
+            Some(path) => AcoParams::from_file(path)?,
+            None => AcoParams::default(),
+        };
+        if let Some(instance) = &self.instance {
+            params.instance = Some(instance.clone());
+        }
+        if let Some(iterations) = self.iterations {
+            params.iterations = iterations;
+        }
+        if let Some(reps) = self.reps {
+            params.reps = reps;
+        }
+        if let Some(seed) = self.seed {
+            params.seed = seed;
+        }
+        if let Some(ants) = self.ants {
+            params.ants = ants;
+        }
+        if self.source.is_some() {
+            (params.source, params.target) = (self.source, self.target);
+        }
+        for (name, value) in &self.params {
+            params.set(name, value)?;
+        }
+        params.validate()?;
+        Ok(params)
+    }
+
+    fn output(&self, seed: u64) -> Option<Output> {
+        self.output.as_ref().map(|dir| Output {
+            dir: dir.clone(),
+            run_id: self.run_id.clone(),
+            seed,
+            format: Format::Csv,
+        })
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let params = cli.params().unwrap_or_else(|e| {
+        Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
+
+    // One state runs every repetition, its observer counting them and seeding each one with
+    // `--seed` plus the repetition
+    let reps = params.reps;
+    let iterations = params.iterations;
+    let output = cli.output(params.seed);
+    let mut state = AcoState::new(params).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    });
+    if let Some(output) = &output {
+        state.observer = Observer::with_output(output.clone());
+    }
+    for rep in 0..reps {
+        let mut schedule = Schedule::new();
+        state.init(&mut schedule);
+        println!(
+            "Repetition {}: {} with {} nodes, {} ants, seed {}",
+            rep,
+            state.instance.name,
+            state.instance.dimension,
+            state.num_ants(),
+            state.seed()
+        );
+        while schedule.step < iterations {
+            schedule.step(&mut state);
+            match state.history.last().unwrap() {
+                Some(walk) => println!("{:>6} {:>12} {}", schedule.step, walk.length, walk),
+                None => println!("{:>6} {:>12} every ant got stuck", schedule.step, "-"),
+            }
+        }
+        match &state.best {
+            Some(best) => println!("Best length {}: {}", best.length, best),
+            None => println!("No ant completed a walk"),
+        }
+        if let Some(output) = &output {
+            if let Err(e) = write_tours(output, rep, &state) {
+                eprintln!("error: cannot export the tours: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+// Write the best walk of each iteration of repetition `rep` next to its metrics, one row per
+// iteration.
+fn write_tours(output: &Output, rep: u64, state: &AcoState) -> io::Result<()> {
+    let dir = output.dir.join(&output.run_id);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("tours_{}.csv", output.stem(rep)));
+    let mut csv = BufWriter::new(File::create(path)?);
+    writeln!(csv, "iteration,length,tour")?;
+    for (i, walk) in state.history.iter().enumerate() {
+        match walk {
+            Some(walk) => writeln!(csv, "{},{},{}", i + 1, walk.length, walk)?,
+            None => writeln!(csv, "{},,", i + 1)?,
+        }
+    }
+    csv.flush()
+}
@@ -0,0 +1,174 @@
+use crate::model::state::{ItemType, ModelState};
+use crate::palette;
+use krabmaga::engine::location::Int2D;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+// RGB image of a grid, drawn on the CPU with `scale` pixels per cell. The first row is the top of
+// the field, so a frame looks like the visualization window.
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    scale: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: i32, height: i32, scale: u32, background: [u8; 3]) -> Canvas {
+        let (width, height) = (width as u32 * scale, height as u32 * scale);
+        Canvas {
+            width,
+            height,
+            scale,
+            pixels: background
+                .iter()
+                .copied()
+                .cycle()
+                .take((width * height * 3) as usize)
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Colour of a pixel, counted from the top left corner of the image.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let i = ((y * self.width + x) * 3) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    // Blend `color` over the whole cell at `(x, y)`, by its alpha.
+    pub fn fill_cell(&mut self, x: i32, y: i32, color: [u8; 4]) {
+        self.fill(x, y, 0, color);
+    }
+
+    // Draw an agent as a square dot in the middle of its cell.
+    pub fn draw_agent(&mut self, x: i32, y: i32, color: [u8; 4]) {
+        self.fill(x, y, self.scale / 4, color);
+    }
+
+    fn fill(&mut self, x: i32, y: i32, margin: u32, color: [u8; 4]) {
+        let (x, y) = (x as u32, y as u32);
+        if x >= self.width / self.scale || y >= self.height / self.scale {
+            return;
+        }
+        let left = x * self.scale + margin;
+        let top = self.height - (y + 1) * self.scale + margin;
+        let size = self.scale - 2 * margin;
+        let alpha = color[3] as u32;
+        for py in top..top + size {
+            for px in left..left + size {
+                let i = ((py * self.width + px) * 3) as usize;
+                for (pixel, &value) in self.pixels[i..i + 3].iter_mut().zip(&color) {
+                    *pixel = ((value as u32 * alpha + *pixel as u32 * (255 - alpha)) / 255) as u8;
+                }
+            }
+        }
+    }
+
+    pub fn save_png(&self, path: &Path) -> io::Result<()> {
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(File::create(path)?), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)
+    }
+}
+
+// PNG frames of a run, written every `every` steps to `<dir>/step_<step>.png`. The same seed
+// replays the same run, so each repetition writes the same frames again.
+pub struct Frames {
+    pub dir: PathBuf,
+    pub every: u64,
+    // Pixels per cell.
+    pub scale: u32,
+    failed: bool,
+}
+
+impl Frames {
+    pub fn new(dir: PathBuf, every: u64, scale: u32) -> Frames {
+        Frames {
+            dir,
+            every,
+            scale,
+            failed: false,
+        }
+    }
+
+    pub fn path(&self, step: u64) -> PathBuf {
+        self.dir.join(format!("step_{:06}.png", step))
+    }
+
+    // Whether a frame is due once `step` steps have been run.
+    pub fn due(&self, step: u64) -> bool {
+        !self.failed && step.is_multiple_of(self.every)
+    }
+
+    // The state hooks cannot return errors: report the failure once and stop writing frames.
+    pub fn write(&mut self, step: u64, canvas: &Canvas) {
+        let path = self.path(step);
+        if let Err(e) = fs::create_dir_all(&self.dir).and_then(|_| canvas.save_png(&path)) {
+            eprintln!("cannot write frame `{}`: {}", path.display(), e);
+            self.failed = true;
+        }
+    }
+}
+
+// Frame of the field: the pheromones of every colony coloured as in the visualization, then the
+// nests, the food and the obstacles, with the ants on top.
+pub fn render(state: &ModelState, scale: u32) -> Canvas {
+    let (width, height) = (state.params.width, state.params.height);
+    let mut canvas = Canvas::new(width, height, scale, palette::BACKGROUND);
+    for x in 0..width {
+        for y in 0..height {
+            let loc = Int2D { x, y };
+            for colony in &state.colonies {
+                if let Some(value) = colony.to_home_grid.grid.get_value(&loc) {
+                    canvas.fill_cell(x, y, palette::pheromone(palette::TO_HOME, value));
+                }
+                if let Some(value) = colony.to_food_grid.grid.get_value(&loc) {
+                    canvas.fill_cell(x, y, palette::pheromone(palette::TO_FOOD, value));
+                }
+            }
+            for item in state.obstacles_grid.get_objects(&loc).unwrap_or_default() {
+                let color = match item.value {
+                    ItemType::Home => palette::HOME,
+                    ItemType::Food => palette::FOOD,
+                    ItemType::Obstacle => palette::OBSTACLE,
+                };
+                canvas.fill_cell(x, y, color);
+            }
+            if let Some(ants) = state.ants_grid.get_objects(&loc) {
+                let color = if ants.iter().any(|ant| ant.has_food) {
+                    palette::ANT_WITH_FOOD
+                } else {
+                    palette::ANT
+                };
+                canvas.draw_agent(x, y, color);
+            }
+        }
+    }
+    canvas
+}
+
+// Write the frame of `step` when one is due. Called once the fields are updated.
+pub fn record(state: &mut ModelState, step: u64) {
+    if let Some(mut frames) = state.frames.take() {
+        if frames.due(step) {
+            let canvas = render(state, frames.scale);
+            frames.write(step, &canvas);
+        }
+        state.frames = Some(frames);
+    }
+}
@@ -0,0 +1,34 @@
+pub mod aco;
+#[cfg(feature = "snapshot")]
+pub mod frames;
+pub mod model;
+pub mod palette;
+pub mod trail;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+// Constants
+pub const WIDTH: i32 = 200;
+pub const HEIGHT: i32 = 200;
+pub const NUM_AGENT: u32 = 100;
+pub const EVAPORATION: f32 = 0.999;
+pub const STEP: u64 = 1000;
+// Nest coordinate range
+pub const HOME_XMIN: i32 = 175;
+pub const HOME_XMAX: i32 = 175;
+pub const HOME_YMIN: i32 = 175;
+pub const HOME_YMAX: i32 = 175;
+// Food coordinate range
+pub const FOOD_XMIN: i32 = 25;
+pub const FOOD_XMAX: i32 = 25;
+pub const FOOD_YMIN: i32 = 25;
+pub const FOOD_YMAX: i32 = 25;
+// Pheromone value
+pub const HOME_LOW_PHEROMONE: f32 = 0.00000000000001;
+pub const FOOD_LOW_PHEROMONE: f32 = 0.00000000000001;
+// Ants action parameters
+pub const REWARD: f32 = 1.;
+pub const MOMENTUM_PROBABILITY: f64 = 0.8;
+pub const RANDOM_ACTION_PROBABILITY: f64 = 0.1;
+pub const UPDATE_CUTDOWN: f32 = 0.9;
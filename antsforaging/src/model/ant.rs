@@ -2,24 +2,44 @@ use core::fmt;
 use krabmaga::engine::agent::Agent;
 use krabmaga::engine::location::Int2D;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
 use crate::model::state::*;
-use crate::{
-    HEIGHT, MOMENTUM_PROBABILITY, RANDOM_ACTION_PROBABILITY, REWARD, UPDATE_CUTDOWN, WIDTH,
-};
+use crate::trail;
+use examples_common::checkpoint::{option_int2d, Int2DDef};
+use examples_common::rng;
+
+// Task of an ant in its colony. Foragers are the ants of the original model; the other roles
+// only appear with the caste parameters, see `Params::castes`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    // Explores the field, taking random steps with `scout_random_action_probability`
+    Scout,
+    #[default]
+    Forager,
+    // Found food and lays stronger food trails, with `recruiter_reward`, rousing the idle ants of
+    // its nest each time it brings food back
+    Recruiter,
+    // Waits in the nest until the recruiters bring enough food back
+    Idle,
+}
 
 // A struct representing an ant, with an id, a location, whether it's holding food or not and the
 // current reward, used to increase the pheromone on the location of the ant if a site is reached.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Ant {
     // An unique id.
     pub id: u32,
+    // Index of the colony of the ant, whose trails it follows.
+    pub colony: usize,
     // The location of the agent.
+    #[serde(with = "Int2DDef")]
     pub loc: Int2D,
     // Last location of the agent, starts as None
+    #[serde(with = "option_int2d")]
     pub last: Option<Int2D>,
     // False means the agent will try to find food by following food pheromones if possible, or by
     // flooding the grid until it is found. True means the agent will try to return home by using the
@@ -29,32 +49,51 @@ pub struct Ant {
     // This will let the agents spread pheromones in the surrounding areas from point of interests
     // so that other agents will know which path to take to do their job.
     pub reward: f32,
+    // Step at which the ant last left its nest, to time its round trips to the food.
+    #[serde(default)]
+    pub left_nest: u64,
+    // Task of the ant, and the food it brought back since it became a recruiter.
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub trips: u32,
+    // A scout goes back to scouting once done recruiting, instead of foraging.
+    #[serde(default)]
+    pub scout: bool,
 }
 
 impl Ant {
-    pub fn new(id: u32, loc: Int2D, has_food: bool, reward: f32) -> Ant {
+    pub fn new(id: u32, colony: usize, loc: Int2D, has_food: bool, reward: f32) -> Ant {
         Ant {
             id,
+            colony,
             loc,
             last: None,
             has_food,
             reward,
+            left_nest: 0,
+            role: Role::Forager,
+            trips: 0,
+            scout: false,
         }
     }
 
     // Deposit a home pheromone if self is not holding food, else deposit a food pheromone,
-    // so that other agents will take in account the pheromone value when choosing the next step's
-    // direction.
+    // so that other agents of the colony will take in account the pheromone value when choosing
+    // the next step's direction.
     pub fn deposit_pheromone(&mut self, state: &ModelState) {
+        let colony = &state.colonies[self.colony];
         let x = self.loc.x;
         let y = self.loc.y;
+        let (width, height) = (state.params.width, state.params.height);
+        let update_cutdown = state.params.update_cutdown;
 
         // Fetch the value of the correct pheromone on our location, depending whether we're holding
         // food or not.
         let mut max = if self.has_food {
-            state.to_food_grid.grid.get_value(&self.loc)
+            colony.to_food_grid.grid.get_value(&self.loc)
         } else {
-            state.to_home_grid.grid.get_value(&self.loc)
+            colony.to_home_grid.grid.get_value(&self.loc)
         }
         .unwrap_or(0.);
 
@@ -65,23 +104,23 @@ impl Ant {
             for dy in -1..2 {
                 let _x = dx + x;
                 let _y = dy + y;
-                if _x < 0 || _y < 0 || _x >= WIDTH || _y >= HEIGHT {
+                if _x < 0 || _y < 0 || _x >= width || _y >= height {
                     // Do not try to take into account out of bounds grid cells
                     continue;
                 }
                 // Fetch the pheromone in the cell we're analyzing
                 let pheromone = if self.has_food {
-                    state.to_food_grid.grid.get_value(&Int2D { x: _x, y: _y })
+                    colony.to_food_grid.grid.get_value(&Int2D { x: _x, y: _y })
                 } else {
-                    state.to_home_grid.grid.get_value(&Int2D { x: _x, y: _y })
+                    colony.to_home_grid.grid.get_value(&Int2D { x: _x, y: _y })
                 }
                 .unwrap_or(0.);
                 // Decrease the value a bit, with diagonal cells of our 3x3 grid considered farther
                 let m = (pheromone * {
                     if dx * dy != 0 {
-                        Ant::diagonal_cutdown()
+                        Ant::diagonal_cutdown(update_cutdown)
                     } else {
-                        UPDATE_CUTDOWN
+                        update_cutdown
                     }
                 }) + self.reward;
                 if m > max {
@@ -91,12 +130,38 @@ impl Ant {
         }
         // Set the new value of the pheromone we're considering
         if self.has_food {
-            state.to_food_grid.grid.set_value_location(max, &self.loc);
+            colony.to_food_grid.grid.set_value_location(max, &self.loc);
         } else {
-            state.to_home_grid.grid.set_value_location(max, &self.loc);
+            colony.to_home_grid.grid.set_value_location(max, &self.loc);
         }
         // We have used our reward, reset it
         self.reward = 0.;
+        self.mask_trails(state);
+    }
+
+    // Cover part of the pheromones of the other colonies on our cell with our own scent, so that
+    // their trails fade where we walk.
+    fn mask_trails(&self, state: &ModelState) {
+        let masking = state.params.trail_masking;
+        if masking == 0. {
+            return;
+        }
+        let rivals = state
+            .colonies
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != self.colony);
+        for (_, rival) in rivals {
+            for grid in [&rival.to_food_grid.grid, &rival.to_home_grid.grid] {
+                // Pheromones already deposited during this step are masked too
+                let pheromone = grid
+                    .get_value_unbuffered(&self.loc)
+                    .or_else(|| grid.get_value(&self.loc));
+                if let Some(pheromone) = pheromone {
+                    grid.set_value_location(pheromone * (1. - masking), &self.loc);
+                }
+            }
+        }
     }
 
     // Step to the next cell by taking into account pheromones. If no pheromones of the right type
@@ -104,8 +169,10 @@ impl Ant {
     // with a probability of MOMENTUM_PROBABILITY. Otherwise, step in a random direction with a
     // probability of RANDOM_ACTION_PROBABILITY.
     pub fn act(&mut self, state: &ModelState) {
-        let mut rng = rand::thread_rng();
+        let colony = &state.colonies[self.colony];
+        let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
         let mut max = -1.; // An initial, impossible pheromone.
+        let (width, height) = (state.params.width, state.params.height);
 
         let x = self.loc.x;
         let y = self.loc.y;
@@ -125,17 +192,17 @@ impl Ant {
                 if (dx == 0 && dy == 0)
                     || new_x < 0
                     || new_y < 0
-                    || new_x >= WIDTH
-                    || new_y >= HEIGHT
+                    || new_x >= width
+                    || new_y >= height
                     || state.get_obstacle(&new_int2d).is_some()
                 {
                     continue;
                 }
 
                 let m = if self.has_food {
-                    state.to_home_grid.grid.get_value(&new_int2d)
+                    colony.to_home_grid.grid.get_value(&new_int2d)
                 } else {
-                    state.to_food_grid.grid.get_value(&new_int2d)
+                    colony.to_food_grid.grid.get_value(&new_int2d)
                 }
                 .unwrap_or(0.);
                 if m > max {
@@ -145,7 +212,7 @@ impl Ant {
                 // A new maximum is found, or the maximux hasn't changed. In the latter case, we
                 // randomly choose whether to consider the new cell for the next step or not with an
                 // equal chance.
-                if m > max || (m == max && rng.gen_bool(1. / count as f64)) {
+                if m > max || (m == max && rng.random_bool(1. / count as f64)) {
                     // Latter expression is to take a random step towards paths with a good pheromone
                     max = m;
                     max_x = new_x;
@@ -158,12 +225,12 @@ impl Ant {
         if max == 0. && self.last.is_some() {
             // No tips from pheromones, consider stepping in the same direction
             if let Some(last_loc) = self.last {
-                if rng.gen_bool(MOMENTUM_PROBABILITY) {
+                if rng.random_bool(state.params.momentum_probability) {
                     let xm = x + (x - last_loc.x);
                     let ym = y + (y - last_loc.y);
                     // Don't go outside the field or in an obstacle
-                    if (0..WIDTH).contains(&xm)
-                        && (0..HEIGHT).contains(&ym)
+                    if (0..width).contains(&xm)
+                        && (0..height).contains(&ym)
                         && state.get_obstacle(&Int2D { x: xm, y: ym }).is_none()
                     {
                         max_x = xm;
@@ -171,16 +238,19 @@ impl Ant {
                     }
                 }
             }
-        } else if rng.gen_bool(RANDOM_ACTION_PROBABILITY) {
+        } else if rng.random_bool(match self.role {
+            Role::Scout => state.params.scout_random_action_probability,
+            _ => state.params.random_action_probability,
+        }) {
             // All other ideas have failed, just choose a random direction
-            let xd: i32 = rng.gen_range(-1..2);
-            let yd: i32 = rng.gen_range(-1..2);
+            let xd: i32 = rng.random_range(-1..2);
+            let yd: i32 = rng.random_range(-1..2);
             let xm = x + xd;
             let ym = y + yd;
             // Don't go outside the field, in an obstacle and do not stay still
             if !(xd == 0 && yd == 0)
-                && (0..WIDTH).contains(&xm)
-                && (0..HEIGHT).contains(&ym)
+                && (0..width).contains(&xm)
+                && (0..height).contains(&ym)
                 && state.get_obstacle(&Int2D { x: xm, y: ym }).is_none()
             {
                 max_x = xm;
@@ -194,26 +264,32 @@ impl Ant {
 
         // Get rewarded if we've reached a site and update our food status
         if let Some(obs) = state.obstacles_grid.get_objects(&self.loc) {
-            match obs.first().unwrap().value {
+            let item = *obs.first().unwrap();
+            match item.value {
                 ItemType::Home => {
-                    if self.has_food {
-                        {
-                            let mut x = state.food_returned_home.write().unwrap();
-                            *x = true;
-                        }
-                        self.reward = REWARD;
+                    // Only our own nest takes the food we carry
+                    if self.has_food && colony.is_nest(&self.loc) {
+                        *colony.food_delivered.write().unwrap() += 1;
+                        let round_trip = state.step - self.left_nest;
+                        state.round_trips.write().unwrap().push(round_trip);
+                        self.left_nest = state.step;
+                        self.reward = state.params.reward;
                         self.has_food = !self.has_food;
+                        self.switch_role_at_nest(state);
                     }
                 }
                 ItemType::Food => {
-                    if !self.has_food {
-                        {
-                            let mut x = state.food_source_found.write().unwrap();
-                            *x = true;
-                            //println!("Found food!");
-                        }
-                        self.reward = REWARD;
+                    // A source that ran out earlier in the step is still on the field until the
+                    // next update, but there's nothing left to take
+                    let index = (item.id - FOOD_ID) as usize;
+                    if !self.has_food
+                        && !self.loses_fight(state, &mut rng)
+                        && state.food.write().unwrap()[index].take()
+                    {
+                        *state.food_picked.write().unwrap() += 1;
+                        self.reward = state.params.reward;
                         self.has_food = !self.has_food;
+                        self.switch_role_at_food(state);
                     }
                 }
                 ItemType::Obstacle => {}
@@ -221,8 +297,109 @@ impl Ant {
         }
     }
 
-    fn diagonal_cutdown() -> f32 {
-        UPDATE_CUTDOWN.powf((2_f32).sqrt())
+    // Role switching on picking food up: a scout, or a forager finding a source whose food trail
+    // is weaker than `recruit_threshold`, becomes a recruiter, and marks the way back with
+    // `recruiter_reward`.
+    fn switch_role_at_food(&mut self, state: &ModelState) {
+        let trail = state.colonies[self.colony]
+            .to_food_grid
+            .grid
+            .get_value(&self.loc)
+            .unwrap_or(0.);
+        let recruits = match self.role {
+            Role::Scout => true,
+            Role::Forager => trail < state.params.recruit_threshold,
+            Role::Recruiter | Role::Idle => false,
+        };
+        if recruits {
+            self.role = Role::Recruiter;
+            self.trips = 0;
+        }
+        if self.role == Role::Recruiter {
+            self.reward = state.params.recruiter_reward;
+        }
+    }
+
+    // Role switching on bringing food back: the delivery rouses the idle ants of the nest, only
+    // when made by a recruiter with `recruiters_only`. A recruiter goes back to scouting or
+    // foraging after `recruiter_trips` deliveries, while a forager rests in the nest when the
+    // recruitment of its colony is below `rest_threshold`.
+    fn switch_role_at_nest(&mut self, state: &ModelState) {
+        let colony = &state.colonies[self.colony];
+        if self.role == Role::Recruiter || !state.params.recruiters_only {
+            *colony.recruited.write().unwrap() += 1;
+        }
+        match self.role {
+            Role::Recruiter => {
+                self.trips += 1;
+                if self.trips >= state.params.recruiter_trips {
+                    self.role = if self.scout {
+                        Role::Scout
+                    } else {
+                        Role::Forager
+                    };
+                }
+            }
+            Role::Forager if colony.recruitment < state.params.rest_threshold => {
+                self.role = Role::Idle;
+            }
+            _ => {}
+        }
+    }
+
+    // An idle ant stays in the nest, and starts foraging with a probability that grows with the
+    // recruitment of its colony, s^2 / (s^2 + activation_threshold^2).
+    fn wait(&mut self, state: &ModelState) {
+        state.ants_grid.set_object_location(*self, &self.loc);
+        let stimulus = state.colonies[self.colony].recruitment;
+        if stimulus <= 0. {
+            return;
+        }
+        let threshold = state.params.activation_threshold;
+        let probability = stimulus * stimulus / (stimulus * stimulus + threshold * threshold);
+        let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
+        if rng.random_bool(probability as f64) {
+            self.role = Role::Forager;
+            self.left_nest = state.step;
+        }
+    }
+
+    // At a food source where ants of other colonies stand, fight them for the food, and lose with
+    // a probability of `fight_probability`.
+    fn loses_fight(&self, state: &ModelState, rng: &mut impl Rng) -> bool {
+        let probability = state.params.fight_probability;
+        if probability == 0. {
+            return false;
+        }
+        let rivals = state
+            .ants_grid
+            .get_objects(&self.loc)
+            .is_some_and(|ants| ants.iter().any(|ant| ant.colony != self.colony));
+        let lost = rivals && rng.random_bool(probability);
+        if lost {
+            *state.colonies[self.colony].fights_lost.write().unwrap() += 1;
+        }
+        lost
+    }
+
+    // Walk out of obstacles put on our cell during the run, to the nearest open cell.
+    fn escape(&mut self, state: &ModelState) {
+        let (width, height) = (state.params.width, state.params.height);
+        let open = trail::shortest_path(
+            self.loc,
+            width,
+            height,
+            |_| false,
+            |loc| state.get_obstacle(loc).is_none(),
+        );
+        if let Some(&loc) = open.as_ref().and_then(|path| path.last()) {
+            self.loc = loc;
+            self.last = None;
+        }
+    }
+
+    fn diagonal_cutdown(update_cutdown: f32) -> f32 {
+        update_cutdown.powf((2_f32).sqrt())
     }
 }
 
@@ -230,6 +407,13 @@ impl Agent for Ant {
     /// Each ant deposits a pheromone in its current location, then it steps in the next grid cell.
     fn step(&mut self, state: &mut dyn State) {
         let state = state.as_any().downcast_ref::<ModelState>().unwrap();
+        if state.get_obstacle(&self.loc).is_some() {
+            self.escape(state);
+        }
+        if self.role == Role::Idle {
+            self.wait(state);
+            return;
+        }
         self.deposit_pheromone(state);
         self.act(state);
     }
@@ -0,0 +1,79 @@
+use crate::model::params::Params;
+use crate::model::to_food_grid::ToFoodGrid;
+use crate::model::to_home_grid::ToHomeGrid;
+use krabmaga::engine::location::Int2D;
+use std::sync::RwLock;
+
+// A colony of ants: its nest, and the two pheromone trails that only its own ants follow.
+pub struct Colony {
+    // Cells of the nest, the ants of the colony start from the first one
+    pub nest: Vec<Int2D>,
+    pub num_agent: u32,
+    pub to_food_grid: ToFoodGrid,
+    pub to_home_grid: ToHomeGrid,
+    // Units of food brought back to the nest during the current step
+    pub food_delivered: RwLock<u32>,
+    // Fights lost at shared food sources during the current step
+    pub fights_lost: RwLock<u32>,
+    // Units of food brought back to the nest since the start of the run
+    pub total_delivered: u64,
+    // Deliveries raising the recruitment during the current step: every one, or only those of
+    // recruiters with `recruiters_only`
+    pub recruited: RwLock<u32>,
+    // Stimulus the deliveries give the idle ants, fading by `recruitment_decay` at each step
+    pub recruitment: f32,
+}
+
+impl Colony {
+    pub fn new(params: &Params, nest: Vec<Int2D>, num_agent: u32) -> Colony {
+        let (width, height) = (params.width, params.height);
+        Colony {
+            nest,
+            num_agent,
+            to_food_grid: ToFoodGrid::new(
+                width,
+                height,
+                params.evaporation,
+                params.food_low_pheromone,
+            ),
+            to_home_grid: ToHomeGrid::new(
+                width,
+                height,
+                params.evaporation,
+                params.home_low_pheromone,
+            ),
+            food_delivered: RwLock::new(0),
+            fights_lost: RwLock::new(0),
+            total_delivered: 0,
+            recruited: RwLock::new(0),
+            recruitment: 0.,
+        }
+    }
+
+    // The colonies of a run: the first one has its nest placed by `ModelState::init`, the others
+    // are those listed in the parameters.
+    pub fn all(params: &Params) -> Vec<Colony> {
+        let mut colonies = vec![Colony::new(params, Vec::new(), params.num_agent)];
+        colonies.extend(params.colonies.iter().map(|colony| {
+            let nest = Int2D {
+                x: colony.x,
+                y: colony.y,
+            };
+            Colony::new(params, vec![nest], colony.num_agent)
+        }));
+        colonies
+    }
+
+    pub fn is_nest(&self, loc: &Int2D) -> bool {
+        self.nest.contains(loc)
+    }
+
+    // Foraging efficiency of the colony: units of food brought back so far, per ant.
+    pub fn food_per_ant(&self) -> f64 {
+        if self.num_agent == 0 {
+            0.
+        } else {
+            self.total_delivered as f64 / self.num_agent as f64
+        }
+    }
+}
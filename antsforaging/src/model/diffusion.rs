@@ -0,0 +1,138 @@
+use krabmaga::engine::fields::field::Field;
+use krabmaga::engine::fields::sparse_number_grid_2d::SparseNumberGrid2D;
+use krabmaga::engine::location::Int2D;
+use std::collections::HashMap;
+
+// Share of the cells of the field holding a pheromone above which the diffusion is computed
+// over every cell of the field rather than over the marked ones only.
+pub const DENSE_COVERAGE: f64 = 0.25;
+
+// Diffusion of the pheromones: at each step a cell shares `rate` of its pheromone equally with
+// its 8 neighbours, as NetLogo's `diffuse`. The shares of neighbours that are obstacles or outside
+// the field stay in the cell, so no pheromone is lost, nor leaks into the obstacles.
+pub struct Diffusion {
+    pub rate: f32,
+    // Coverage above which the dense computation is used, see `DENSE_COVERAGE`
+    pub dense_coverage: f64,
+    width: i32,
+    height: i32,
+    // Whether pheromone can spread into each cell, column by column
+    open: Vec<bool>,
+}
+
+impl Diffusion {
+    pub fn new(rate: f32, width: i32, height: i32, blocked: impl Fn(&Int2D) -> bool) -> Diffusion {
+        let mut open = Vec::with_capacity((width * height) as usize);
+        for x in 0..width {
+            for y in 0..height {
+                open.push(!blocked(&Int2D { x, y }));
+            }
+        }
+        Diffusion {
+            rate,
+            dense_coverage: DENSE_COVERAGE,
+            width,
+            height,
+            open,
+        }
+    }
+
+    // Spread the pheromones of the read buffer of `grid`, which holds the result once the
+    // grid is updated.
+    pub fn spread(&self, grid: &mut SparseNumberGrid2D<f32>) {
+        let mut sources = pheromones(grid);
+        if sources.is_empty() {
+            return;
+        }
+        // Shares are added up in the same order whatever the computation, so that the sparse and
+        // the dense ones give the same values
+        sources.sort_by_key(|&(cell, _)| cell);
+        let cells = (self.width * self.height) as usize;
+        if sources.len() as f64 > self.dense_coverage * cells as f64 {
+            let mut values = vec![0.; cells];
+            self.shares(&sources, |cell, share| values[cell] += share);
+            // sources that gave all their pheromone away are cleared too
+            let cleared = sources.iter().filter(|&&(cell, _)| values[cell] == 0.);
+            for &(cell, _) in cleared {
+                grid.set_value_location(0., &self.loc(cell));
+            }
+            for (cell, &value) in values.iter().enumerate() {
+                if value > 0. {
+                    grid.set_value_location(value, &self.loc(cell));
+                }
+            }
+        } else {
+            let mut values: HashMap<usize, f32> = HashMap::with_capacity(sources.len() * 3);
+            self.shares(&sources, |cell, share| {
+                *values.entry(cell).or_insert(0.) += share
+            });
+            for (cell, value) in values {
+                grid.set_value_location(value, &self.loc(cell));
+            }
+        }
+        grid.update();
+    }
+
+    // Hand the shares of every source to `add`, cell by cell.
+    fn shares(&self, sources: &[(usize, f32)], mut add: impl FnMut(usize, f32)) {
+        for &(cell, value) in sources {
+            let loc = self.loc(cell);
+            let share = value * self.rate / 8.;
+            let mut kept = value * (1. - self.rate);
+            for dx in -1..2 {
+                for dy in -1..2 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+                    let (x, y) = (loc.x + dx, loc.y + dy);
+                    let neighbour = (x * self.height + y) as usize;
+                    if (0..self.width).contains(&x)
+                        && (0..self.height).contains(&y)
+                        && self.open[neighbour]
+                    {
+                        add(neighbour, share);
+                    } else {
+                        kept += share;
+                    }
+                }
+            }
+            add(cell, kept);
+        }
+    }
+
+    fn loc(&self, cell: usize) -> Int2D {
+        Int2D {
+            x: cell as i32 / self.height,
+            y: cell as i32 % self.height,
+        }
+    }
+}
+
+// Marked cells of the read buffer of `grid`, with their pheromone.
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+fn pheromones(grid: &SparseNumberGrid2D<f32>) -> Vec<(usize, f32)> {
+    let height = grid.height;
+    let values = std::cell::RefCell::new(Vec::new());
+    grid.iter_values(|loc, &value| {
+        if value > 0. {
+            values
+                .borrow_mut()
+                .push(((loc.x * height + loc.y) as usize, value));
+        }
+    });
+    values.into_inner()
+}
+
+// The grids of the visualization can't be iterated, every cell is looked up instead.
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+fn pheromones(grid: &SparseNumberGrid2D<f32>) -> Vec<(usize, f32)> {
+    let mut values = Vec::new();
+    for x in 0..grid.width {
+        for y in 0..grid.height {
+            if let Some(value) = grid.get_value(&Int2D { x, y }).filter(|&value| value > 0.) {
+                values.push(((x * grid.height + y) as usize, value));
+            }
+        }
+    }
+    values
+}
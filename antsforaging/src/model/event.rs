@@ -0,0 +1,179 @@
+use crate::model::state::ModelState;
+use crate::trail;
+use krabmaga::engine::location::Int2D;
+use serde::{Deserialize, Serialize};
+
+// A change of the field during a run, made before the ants move on step `step`. Events are
+// listed in the parameters as `[[events]]` entries, the kind of event named by `action`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Event {
+    // Put obstacles on the `width` x `height` rectangle whose bottom left cell is (`x`, `y`),
+    // except on the nest and the food
+    AddObstacles {
+        step: u64,
+        x: i32,
+        y: i32,
+        #[serde(default = "one")]
+        width: i32,
+        #[serde(default = "one")]
+        height: i32,
+    },
+    // Clear the obstacles of a rectangle, as in `AddObstacles`
+    RemoveObstacles {
+        step: u64,
+        x: i32,
+        y: i32,
+        #[serde(default = "one")]
+        width: i32,
+        #[serde(default = "one")]
+        height: i32,
+    },
+    // Move the food source `source` (in the order of `ModelState::food`) to (`x`, `y`), with the
+    // food it has left
+    MoveFood {
+        step: u64,
+        #[serde(default)]
+        source: usize,
+        x: i32,
+        y: i32,
+    },
+    // Put a `size` x `size` square of obstacles across the middle of the trail of the colony
+    // `colony` from its nest to the food, or of the shortest path when there's no trail yet
+    BlockTrail {
+        step: u64,
+        #[serde(default)]
+        colony: usize,
+        #[serde(default = "three")]
+        size: i32,
+    },
+}
+
+fn one() -> i32 {
+    1
+}
+
+fn three() -> i32 {
+    3
+}
+
+impl Event {
+    pub fn step(&self) -> u64 {
+        match *self {
+            Event::AddObstacles { step, .. }
+            | Event::RemoveObstacles { step, .. }
+            | Event::MoveFood { step, .. }
+            | Event::BlockTrail { step, .. } => step,
+        }
+    }
+
+    // Check that the event stays inside a `width` x `height` field with `colonies` colonies.
+    pub fn validate(&self, width: i32, height: i32, colonies: usize) -> Result<(), String> {
+        let inside = |x: i32, y: i32| (0..width).contains(&x) && (0..height).contains(&y);
+        match *self {
+            Event::AddObstacles {
+                step,
+                x,
+                y,
+                width: w,
+                height: h,
+            }
+            | Event::RemoveObstacles {
+                step,
+                x,
+                y,
+                width: w,
+                height: h,
+            } => {
+                if w < 1 || h < 1 || !inside(x, y) || !inside(x + w - 1, y + h - 1) {
+                    return Err(format!(
+                        "event of step {}: the {}x{} rectangle at ({}, {}) is not inside the field",
+                        step, w, h, x, y
+                    ));
+                }
+            }
+            Event::MoveFood { step, x, y, .. } => {
+                if !inside(x, y) {
+                    return Err(format!(
+                        "event of step {}: food moved to ({}, {}), outside the field",
+                        step, x, y
+                    ));
+                }
+            }
+            Event::BlockTrail { step, colony, size } => {
+                if colony >= colonies {
+                    return Err(format!(
+                        "event of step {}: there is no colony {}",
+                        step, colony
+                    ));
+                }
+                if size < 1 {
+                    return Err(format!(
+                        "event of step {}: `size` must be at least 1, found {}",
+                        step, size
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Make the change on the field of `state`.
+    pub fn apply(&self, state: &mut ModelState) {
+        match *self {
+            Event::AddObstacles {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => state.set_obstacles(&rectangle(x, y, width, height), true),
+            Event::RemoveObstacles {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => state.set_obstacles(&rectangle(x, y, width, height), false),
+            Event::MoveFood { source, x, y, .. } => state.move_food(source, Int2D { x, y }),
+            Event::BlockTrail { colony, size, .. } => {
+                if let Some(middle) = trail_middle(state, colony) {
+                    // The square is cut at the edges of the field near which the trail passes
+                    let (width, height) = (state.params.width, state.params.height);
+                    let corner = -(size - 1) / 2;
+                    let mut square = rectangle(middle.x + corner, middle.y + corner, size, size);
+                    square
+                        .retain(|loc| (0..width).contains(&loc.x) && (0..height).contains(&loc.y));
+                    state.set_obstacles(&square, true);
+                }
+            }
+        }
+    }
+}
+
+// Cells of a rectangle, from its bottom left cell.
+fn rectangle(x: i32, y: i32, width: i32, height: i32) -> Vec<Int2D> {
+    (x..x + width)
+        .flat_map(|x| (y..y + height).map(move |y| Int2D { x, y }))
+        .collect()
+}
+
+// Middle cell of the trail of `colony` from its nest to the food, or of the shortest path when
+// the pheromones don't lead there. None if the food is out of reach.
+fn trail_middle(state: &ModelState, colony: usize) -> Option<Int2D> {
+    let (width, height) = (state.params.width, state.params.height);
+    let colony = &state.colonies[colony];
+    let nest = *colony.nest.first()?;
+    let food: Vec<Int2D> = {
+        let food = state.food.read().unwrap();
+        food.iter()
+            .filter(|source| !source.is_depleted())
+            .map(|source| source.loc)
+            .collect()
+    };
+    let blocked = |loc: &Int2D| state.get_obstacle(loc).is_some();
+    let is_food = |loc: &Int2D| food.contains(loc);
+    let path = trail::follow(&colony.to_food_grid.grid, nest, blocked, is_food)
+        .or_else(|| trail::shortest_path(nest, width, height, blocked, is_food))?;
+    Some(path[path.len() / 2])
+}
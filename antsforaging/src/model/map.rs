@@ -0,0 +1,244 @@
+use crate::palette;
+use krabmaga::engine::location::Int2D;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+// What a cell of a map holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Obstacle,
+    Nest,
+    Food,
+}
+
+impl Tile {
+    // Tile of an ASCII map character: `#` is an obstacle, `N` (or `H`) the nest, `F` food, and
+    // `.` or a space an empty cell.
+    pub fn of_char(c: char) -> Option<Tile> {
+        match c.to_ascii_uppercase() {
+            '.' | ' ' => Some(Tile::Empty),
+            '#' => Some(Tile::Obstacle),
+            'N' | 'H' => Some(Tile::Nest),
+            'F' => Some(Tile::Food),
+            _ => None,
+        }
+    }
+
+    // Tile of a pixel of a map image, by the nearest colour among those of the frames: white
+    // is empty, black and grey are obstacles, brown is the nest and orange is food.
+    pub fn of_color(color: [u8; 3]) -> Tile {
+        let rgb = |c: [u8; 4]| [c[0], c[1], c[2]];
+        let tiles = [
+            (palette::BACKGROUND, Tile::Empty),
+            ([0, 0, 0], Tile::Obstacle),
+            (rgb(palette::OBSTACLE), Tile::Obstacle),
+            (rgb(palette::HOME), Tile::Nest),
+            (rgb(palette::FOOD), Tile::Food),
+        ];
+        let distance = |other: [u8; 3]| -> i32 {
+            (0..3)
+                .map(|i| (color[i] as i32 - other[i] as i32).pow(2))
+                .sum()
+        };
+        tiles
+            .iter()
+            .min_by_key(|(other, _)| distance(*other))
+            .unwrap()
+            .1
+    }
+}
+
+// A field read from a file: its dimensions, and where the obstacles, the nest and the food are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Map {
+    pub width: i32,
+    pub height: i32,
+    // Cells column by column, as the grids are saved
+    tiles: Vec<Tile>,
+}
+
+impl Map {
+    // Read a map from a PNG image or a PGM (P2 or P5) greymap, one pixel per cell, or otherwise
+    // from an ASCII grid, one character per cell (see `Tile::of_char`). The top row of the file
+    // is the top of the field, i.e. the highest y. A greymap only marks obstacles, its dark
+    // pixels; in an ASCII grid, lines shorter than the longest one are padded with empty cells.
+    pub fn read(path: &Path) -> Result<Map, String> {
+        let rows = match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("png") => read_png(path),
+            Some("pgm") => read_pgm(path),
+            _ => read_ascii(path),
+        }
+        .map_err(|e| format!("invalid map `{}`: {}", path.display(), e))?;
+
+        let height = rows.len();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        if width == 0 || height == 0 {
+            return Err(format!("invalid map `{}`: it is empty", path.display()));
+        }
+        let mut tiles = vec![Tile::Empty; width * height];
+        for (row, line) in rows.iter().enumerate() {
+            let y = height - 1 - row;
+            for (x, &tile) in line.iter().enumerate() {
+                tiles[x * height + y] = tile;
+            }
+        }
+        Ok(Map {
+            width: width as i32,
+            height: height as i32,
+            tiles,
+        })
+    }
+
+    pub fn tile(&self, loc: &Int2D) -> Tile {
+        self.tiles[(loc.x * self.height + loc.y) as usize]
+    }
+
+    // Cells holding `tile`, column by column.
+    pub fn cells(&self, tile: Tile) -> Vec<Int2D> {
+        let height = self.height;
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, &t)| t == tile)
+            .map(|(i, _)| Int2D {
+                x: i as i32 / height,
+                y: i as i32 % height,
+            })
+            .collect()
+    }
+}
+
+// Rows of tiles, from the top of the field down.
+type Rows = Vec<Vec<Tile>>;
+
+fn read_ascii(path: &Path) -> Result<Rows, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let lines: Vec<&str> = content.trim_end_matches(['\n', '\r']).lines().collect();
+    let mut rows = Vec::with_capacity(lines.len());
+    for (n, line) in lines.iter().enumerate() {
+        let row = line
+            .chars()
+            .map(|c| {
+                Tile::of_char(c).ok_or_else(|| format!("unknown cell `{}` on line {}", c, n + 1))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn read_png(path: &Path) -> Result<Rows, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).map_err(|e| e.to_string())?;
+    // the alpha channel is left out
+    let samples = info.color_type.samples();
+    let rows = pixels
+        .chunks(info.line_size)
+        .take(info.height as usize)
+        .map(|line| {
+            (0..info.width as usize)
+                .map(|x| {
+                    let i = x * samples;
+                    let color = if samples >= 3 {
+                        [line[i], line[i + 1], line[i + 2]]
+                    } else {
+                        [line[i]; 3]
+                    };
+                    Tile::of_color(color)
+                })
+                .collect()
+        })
+        .collect();
+    Ok(rows)
+}
+
+// Plain (P2) or raw (P5) greymap, pixels darker than half the maximum value are obstacles.
+fn read_pgm(path: &Path) -> Result<Rows, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    // The header is made of whitespace-separated tokens, with comments from `#` to the end of
+    // the line: the magic number, the width, the height and the maximum value.
+    let mut pos = 0;
+    let mut token = || -> Result<String, String> {
+        loop {
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if pos < bytes.len() && bytes[pos] == b'#' {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+        let start = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(String::from("truncated greymap"));
+        }
+        Ok(String::from_utf8_lossy(&bytes[start..pos]).into_owned())
+    };
+    let number = |token: String| -> Result<usize, String> {
+        token
+            .parse()
+            .map_err(|_| format!("invalid greymap header value `{}`", token))
+    };
+    let magic = token()?;
+    let width = number(token()?)?;
+    let height = number(token()?)?;
+    let max = number(token()?)?;
+    if max == 0 || max > 65535 {
+        return Err(format!("invalid greymap maximum value {}", max));
+    }
+    let values: Vec<usize> = match magic.as_str() {
+        "P2" => (0..width * height)
+            .map(|_| token().and_then(number))
+            .collect::<Result<_, _>>()?,
+        "P5" => {
+            // a single whitespace character separates the header from the pixels
+            let raster = bytes.get(pos + 1..).unwrap_or_default();
+            let size = if max < 256 { 1 } else { 2 };
+            if raster.len() < width * height * size {
+                return Err(String::from("truncated greymap"));
+            }
+            raster
+                .chunks(size)
+                .take(width * height)
+                .map(|v| v.iter().fold(0, |value, &b| value * 256 + b as usize))
+                .collect()
+        }
+        _ => {
+            return Err(format!(
+                "unsupported greymap `{}`, expected P2 or P5",
+                magic
+            ))
+        }
+    };
+    Ok(values
+        .chunks(width.max(1))
+        .map(|row| {
+            row.iter()
+                .map(|&v| {
+                    if v * 2 < max {
+                        Tile::Obstacle
+                    } else {
+                        Tile::Empty
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
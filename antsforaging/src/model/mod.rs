@@ -1,4 +1,10 @@
 pub mod ant;
+pub mod colony;
+pub mod diffusion;
+pub mod event;
+pub mod map;
+pub mod params;
+pub mod pheromone;
 pub mod state;
 pub mod to_food_grid;
 pub mod to_home_grid;
@@ -0,0 +1,375 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::model::event::Event;
+use crate::model::map::{Map, Tile};
+use crate::{
+    EVAPORATION, FOOD_LOW_PHEROMONE, FOOD_XMAX, FOOD_XMIN, FOOD_YMAX, FOOD_YMIN, HEIGHT,
+    HOME_LOW_PHEROMONE, HOME_XMAX, HOME_XMIN, HOME_YMAX, HOME_YMIN, MOMENTUM_PROBABILITY,
+    NUM_AGENT, RANDOM_ACTION_PROBABILITY, REWARD, STEP, UPDATE_CUTDOWN, WIDTH,
+};
+
+// A food source at a fixed cell, holding `amount` units of food (0 for a source that never runs
+// out). Ants carry one unit at a time back to the nest.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FoodSource {
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub amount: u32,
+}
+
+// The nest of a colony competing with the first one, and its number of ants.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Nest {
+    pub x: i32,
+    pub y: i32,
+    #[serde(default = "default_num_agent")]
+    pub num_agent: u32,
+}
+
+fn default_num_agent() -> u32 {
+    NUM_AGENT
+}
+
+// Run settings and model coefficients of an ants foraging simulation. Defaults are the
+// crate-level constants, so a run without overrides behaves as the original example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    // Seed of the random number generators, the same seed replays the same run
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    pub num_agent: u32,
+    pub evaporation: f32,
+    // Share of its pheromone a cell spreads to its neighbours at each step, 0 for none
+    pub diffusion: f32,
+    // Map of the field (see `Map::read`), in place of the default obstacles. The field takes
+    // its dimensions, and the nest and the food cells it marks replace the ranges below.
+    pub map: Option<PathBuf>,
+    // Nest coordinate range
+    pub home_xmin: i32,
+    pub home_xmax: i32,
+    pub home_ymin: i32,
+    pub home_ymax: i32,
+    // Food coordinate range
+    pub food_xmin: i32,
+    pub food_xmax: i32,
+    pub food_ymin: i32,
+    pub food_ymax: i32,
+    // Units of food of that source, 0 for a source that never runs out
+    pub food_amount: u32,
+    // Food sources added to the one above, at fixed cells
+    pub food_sources: Vec<FoodSource>,
+    // Further colonies, each with its own nest, ants and trails
+    pub colonies: Vec<Nest>,
+    // Share of the pheromones of the other colonies an ant masks on its cell at each step
+    pub trail_masking: f32,
+    // Probability that an ant reaching a food source where ants of other colonies stand loses
+    // the fight for it, and leaves without food
+    pub fight_probability: f64,
+    // Steps between two analyses of the trails against the shortest paths, 0 for none
+    pub trail_every: u64,
+    // Changes of the field during the run, see `Event`
+    pub events: Vec<Event>,
+    // Castes: shares of the ants of each colony that start as scouts and idle in the nest, the
+    // others being foragers, see `Role`
+    pub scouts: f64,
+    pub idle: f64,
+    pub scout_random_action_probability: f64,
+    // Reward of a recruiter picking food up, for a stronger trail to the food
+    pub recruiter_reward: f32,
+    // Role switching thresholds: a forager finding a source whose food pheromone is below
+    // `recruit_threshold` becomes a recruiter, a recruiter forages again after
+    // `recruiter_trips` deliveries, an idle ant starts foraging with a probability of
+    // s^2 / (s^2 + activation_threshold^2) for a recruitment s of its colony, and a forager
+    // bringing food back rests when the recruitment is below `rest_threshold`
+    pub recruit_threshold: f32,
+    pub recruiter_trips: u32,
+    pub activation_threshold: f32,
+    pub rest_threshold: f32,
+    // Share of the recruitment of a colony left after a step
+    pub recruitment_decay: f32,
+    // Whether only the deliveries of recruiters raise the recruitment, rather than every food
+    // brought back to the nest. With no recruiter left, the idle ants would then never wake up
+    pub recruiters_only: bool,
+    // Pheromone value
+    pub home_low_pheromone: f32,
+    pub food_low_pheromone: f32,
+    // Ants action parameters
+    pub reward: f32,
+    pub momentum_probability: f64,
+    pub random_action_probability: f64,
+    pub update_cutdown: f32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: STEP,
+            reps: 10,
+            seed: 0,
+            width: WIDTH,
+            height: HEIGHT,
+            num_agent: NUM_AGENT,
+            evaporation: EVAPORATION,
+            diffusion: 0.,
+            map: None,
+            home_xmin: HOME_XMIN,
+            home_xmax: HOME_XMAX,
+            home_ymin: HOME_YMIN,
+            home_ymax: HOME_YMAX,
+            food_xmin: FOOD_XMIN,
+            food_xmax: FOOD_XMAX,
+            food_ymin: FOOD_YMIN,
+            food_ymax: FOOD_YMAX,
+            food_amount: 0,
+            food_sources: Vec::new(),
+            colonies: Vec::new(),
+            trail_masking: 0.,
+            fight_probability: 0.,
+            trail_every: 0,
+            events: Vec::new(),
+            scouts: 0.,
+            idle: 0.,
+            scout_random_action_probability: 0.5,
+            recruiter_reward: 2. * REWARD,
+            recruit_threshold: 0.,
+            recruiter_trips: 3,
+            activation_threshold: 1.,
+            rest_threshold: 0.,
+            recruitment_decay: 0.9,
+            recruiters_only: false,
+            home_low_pheromone: HOME_LOW_PHEROMONE,
+            food_low_pheromone: FOOD_LOW_PHEROMONE,
+            reward: REWARD,
+            momentum_probability: MOMENTUM_PROBABILITY,
+            random_action_probability: RANDOM_ACTION_PROBABILITY,
+            update_cutdown: UPDATE_CUTDOWN,
+        }
+    }
+}
+
+impl Params {
+    // Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    // keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    // Override a parameter by name. Names are matched case-insensitively, so both
+    // `evaporation` and `EVAPORATION` are accepted.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "steps" | "step" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "num_agent" | "agents" => self.num_agent = parse(name, value)?,
+            "evaporation" => self.evaporation = parse(name, value)?,
+            "diffusion" => self.diffusion = parse(name, value)?,
+            "map" => self.map = Some(PathBuf::from(value.trim())),
+            "home_xmin" => self.home_xmin = parse(name, value)?,
+            "home_xmax" => self.home_xmax = parse(name, value)?,
+            "home_ymin" => self.home_ymin = parse(name, value)?,
+            "home_ymax" => self.home_ymax = parse(name, value)?,
+            "food_xmin" => self.food_xmin = parse(name, value)?,
+            "food_xmax" => self.food_xmax = parse(name, value)?,
+            "food_ymin" => self.food_ymin = parse(name, value)?,
+            "food_ymax" => self.food_ymax = parse(name, value)?,
+            "food_amount" => self.food_amount = parse(name, value)?,
+            "trail_masking" => self.trail_masking = parse(name, value)?,
+            "fight_probability" => self.fight_probability = parse(name, value)?,
+            "trail_every" => self.trail_every = parse(name, value)?,
+            "scouts" => self.scouts = parse(name, value)?,
+            "idle" => self.idle = parse(name, value)?,
+            "scout_random_action_probability" => {
+                self.scout_random_action_probability = parse(name, value)?
+            }
+            "recruiter_reward" => self.recruiter_reward = parse(name, value)?,
+            "recruit_threshold" => self.recruit_threshold = parse(name, value)?,
+            "recruiter_trips" => self.recruiter_trips = parse(name, value)?,
+            "activation_threshold" => self.activation_threshold = parse(name, value)?,
+            "rest_threshold" => self.rest_threshold = parse(name, value)?,
+            "recruitment_decay" => self.recruitment_decay = parse(name, value)?,
+            "recruiters_only" => self.recruiters_only = parse(name, value)?,
+            "home_low_pheromone" => self.home_low_pheromone = parse(name, value)?,
+            "food_low_pheromone" => self.food_low_pheromone = parse(name, value)?,
+            "reward" => self.reward = parse(name, value)?,
+            "momentum_probability" => self.momentum_probability = parse(name, value)?,
+            "random_action_probability" => self.random_action_probability = parse(name, value)?,
+            "update_cutdown" => self.update_cutdown = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    // Check that every parameter is in its valid range, including that the nest and the food
+    // ranges lie inside the field, the one of the map when there is one.
+    pub fn validate(&self) -> Result<(), String> {
+        let map = self.map.as_deref().map(Map::read).transpose()?;
+        let (width, height) = map
+            .as_ref()
+            .map_or((self.width, self.height), |map| (map.width, map.height));
+        if width <= 0 || height <= 0 {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                width, height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        let marks = |tile| map.as_ref().is_some_and(|map| !map.cells(tile).is_empty());
+        let mut ranges = Vec::new();
+        if !marks(Tile::Nest) {
+            ranges.push(("home_x", self.home_xmin, self.home_xmax, width));
+            ranges.push(("home_y", self.home_ymin, self.home_ymax, height));
+        }
+        if !marks(Tile::Food) {
+            ranges.push(("food_x", self.food_xmin, self.food_xmax, width));
+            ranges.push(("food_y", self.food_ymin, self.food_ymax, height));
+        }
+        for (name, min, max, bound) in ranges {
+            if min > max || min < 0 || max >= bound {
+                return Err(format!(
+                    "`{0}min..{0}max` must be a range inside 0..{1}, found {2}..{3}",
+                    name, bound, min, max
+                ));
+            }
+        }
+        for source in &self.food_sources {
+            if !(0..width).contains(&source.x) || !(0..height).contains(&source.y) {
+                return Err(format!(
+                    "food source ({}, {}) is outside the field",
+                    source.x, source.y
+                ));
+            }
+        }
+        for nest in &self.colonies {
+            if !(0..width).contains(&nest.x) || !(0..height).contains(&nest.y) {
+                return Err(format!(
+                    "nest ({}, {}) is outside the field",
+                    nest.x, nest.y
+                ));
+            }
+        }
+        // the food sources are those of the map, or the one of the food range, then the listed ones
+        let sources = map
+            .as_ref()
+            .map_or(0, |map| map.cells(Tile::Food).len())
+            .max(1)
+            + self.food_sources.len();
+        for event in &self.events {
+            event.validate(width, height, self.colonies.len() + 1)?;
+            if let Event::MoveFood { step, source, .. } = *event {
+                if source >= sources {
+                    return Err(format!(
+                        "event of step {}: there is no food source {}",
+                        step, source
+                    ));
+                }
+            }
+        }
+        if !(0.0..=1.0).contains(&self.trail_masking) {
+            return Err(format!(
+                "`trail_masking` must be in [0, 1], found {}",
+                self.trail_masking
+            ));
+        }
+        if self.evaporation <= 0. || self.evaporation > 1. {
+            return Err(format!(
+                "`evaporation` must be in (0, 1], found {}",
+                self.evaporation
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.diffusion) {
+            return Err(format!(
+                "`diffusion` must be in [0, 1], found {}",
+                self.diffusion
+            ));
+        }
+        if self.update_cutdown <= 0. || self.update_cutdown > 1. {
+            return Err(format!(
+                "`update_cutdown` must be in (0, 1], found {}",
+                self.update_cutdown
+            ));
+        }
+        if self.scouts + self.idle > 1. {
+            return Err(format!(
+                "`scouts` and `idle` must add up to at most 1, found {} and {}",
+                self.scouts, self.idle
+            ));
+        }
+        if self.recruiter_reward < 0.
+            || self.recruit_threshold < 0.
+            || self.activation_threshold < 0.
+            || self.rest_threshold < 0.
+        {
+            return Err(String::from(
+                "`recruiter_reward` and the role switching thresholds must not be negative",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.recruitment_decay) {
+            return Err(format!(
+                "`recruitment_decay` must be in [0, 1], found {}",
+                self.recruitment_decay
+            ));
+        }
+        if self.home_low_pheromone < 0. || self.food_low_pheromone < 0. || self.reward < 0. {
+            return Err(String::from(
+                "`home_low_pheromone`, `food_low_pheromone` and `reward` must not be negative",
+            ));
+        }
+        probability("momentum_probability", self.momentum_probability)?;
+        probability("fight_probability", self.fight_probability)?;
+        probability("scouts", self.scouts)?;
+        probability("idle", self.idle)?;
+        probability(
+            "scout_random_action_probability",
+            self.scout_random_action_probability,
+        )?;
+        probability("random_action_probability", self.random_action_probability)
+    }
+
+    // Whether ants can take other roles than foraging, which adds their counts to the metrics.
+    pub fn castes(&self) -> bool {
+        self.scouts > 0.
+            || self.idle > 0.
+            || self.recruit_threshold > 0.
+            || self.rest_threshold > 0.
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
+
+fn probability(name: &str, value: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{}` must be in [0, 1], found {}", name, value))
+    }
+}
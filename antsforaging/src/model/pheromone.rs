@@ -0,0 +1,40 @@
+use crate::model::diffusion::Diffusion;
+use core::ops::{Add, Mul};
+use krabmaga::engine::fields::field::Field;
+use krabmaga::engine::fields::grid_option::GridOption;
+use krabmaga::engine::fields::sparse_number_grid_2d::SparseNumberGrid2D;
+
+// Pheromone left after a step: what was there evaporates by `evaporation`, the share it keeps,
+// then `deposit` is added. Pheromones below `low_pheromone` are cleared. The trails of the grid
+// and the arcs of the colony optimization both go through here.
+pub fn update<T>(pheromone: T, evaporation: T, deposit: T, low_pheromone: T) -> T
+where
+    T: Copy + Default + PartialOrd + Add<Output = T> + Mul<Output = T>,
+{
+    let new_val = pheromone * evaporation + deposit;
+    if new_val < low_pheromone {
+        T::default()
+    } else {
+        new_val
+    }
+}
+
+// Keep the pheromones of the last step where none was deposited again, diffuse them if
+// `diffusion` is given, then let them evaporate. The ants deposit during the step, so nothing
+// is deposited here.
+pub fn update_grid(
+    grid: &mut SparseNumberGrid2D<f32>,
+    diffusion: Option<&Diffusion>,
+    evaporation: f32,
+    low_pheromone: f32,
+) {
+    grid.apply_to_all_values(|&val| val, GridOption::READWRITE);
+    grid.update();
+    if let Some(diffusion) = diffusion {
+        diffusion.spread(grid);
+    }
+    grid.apply_to_all_values(
+        |&val| update(val, evaporation, 0., low_pheromone),
+        GridOption::READ,
+    )
+}
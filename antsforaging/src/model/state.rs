@@ -1,23 +1,32 @@
-use crate::model::ant::Ant;
-use crate::model::to_food_grid::ToFoodGrid;
-use crate::model::to_home_grid::ToHomeGrid;
-use crate::{
-    FOOD_XMAX, FOOD_XMIN, FOOD_YMAX, FOOD_YMIN, HEIGHT, HOME_XMAX, HOME_XMIN, HOME_YMAX, HOME_YMIN,
-    NUM_AGENT, WIDTH,
-};
+#[cfg(feature = "snapshot")]
+use crate::frames::{self, Frames};
+use crate::model::ant::{Ant, Role};
+use crate::model::colony::Colony;
+use crate::model::diffusion::Diffusion;
+use crate::model::event::Event;
+use crate::model::map::{Map, Tile};
+use crate::model::params::Params;
+use crate::trail::{self, TrailReport};
 use core::fmt;
 use core::hash::{Hash, Hasher};
+use examples_common::checkpoint::{self, Cell, Int2DDef, Resumable};
+use examples_common::observer::Observer;
+use examples_common::rng;
+use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::field::Field;
+use krabmaga::engine::fields::sparse_number_grid_2d::SparseNumberGrid2D;
 use krabmaga::engine::fields::sparse_object_grid_2d::SparseGrid2D;
 use krabmaga::engine::location::Int2D;
 use krabmaga::engine::schedule::Schedule;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
 // Objects within the field
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ItemType {
     Food,
     Home,
@@ -34,7 +43,7 @@ impl fmt::Display for ItemType {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Item {
     pub id: u32,
     pub value: ItemType,
@@ -63,82 +72,174 @@ impl fmt::Display for Item {
     }
 }
 
+// Item ids of the nest and of the first food source, the other sources follow it
+pub const HOME_ID: u32 = 99999999;
+pub const FOOD_ID: u32 = 888888888;
+
+// A food source of the field, and how much food is left in it. Ants take one unit at a time.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Food {
+    #[serde(with = "Int2DDef")]
+    pub loc: Int2D,
+    // Units of food left, None for a source that never runs out
+    pub left: Option<u32>,
+}
+
+impl Food {
+    fn new(loc: Int2D, amount: u32) -> Food {
+        Food {
+            loc,
+            left: (amount > 0).then_some(amount),
+        }
+    }
+
+    // Take a unit of food, if there is any left.
+    pub fn take(&mut self) -> bool {
+        match &mut self.left {
+            None => true,
+            Some(0) => false,
+            Some(left) => {
+                *left -= 1;
+                true
+            }
+        }
+    }
+
+    pub fn is_depleted(&self) -> bool {
+        self.left == Some(0)
+    }
+}
+
 // The global simulation state. This holds the various grids used for movement, exposing setter methods
 // so that the state itself will worry about ownership rules by mutating its own fields.
 pub struct ModelState {
     pub ants_grid: SparseGrid2D<Ant>,
     pub obstacles_grid: SparseGrid2D<Item>,
-    pub to_food_grid: ToFoodGrid,
-    pub to_home_grid: ToHomeGrid,
-    pub food_source_found: RwLock<bool>,
-    pub food_returned_home: RwLock<bool>,
+    // Colonies of ants, each with its nest and its trails
+    pub colonies: Vec<Colony>,
+    // Map of the field, if the parameters name one
+    pub map: Option<Map>,
+    // Diffusion of the pheromones around the obstacles, if enabled
+    pub diffusion: Option<Diffusion>,
+    // Units of food taken from the sources during the current step
+    pub food_picked: RwLock<u32>,
+    // Durations of the round trips between the nest and the food completed during the current step
+    pub round_trips: RwLock<Vec<u64>>,
+    // Round trips completed since the start of the run, and the steps they took altogether
+    pub total_trips: u64,
+    pub total_trip_steps: u64,
+    // Last report on the trails of each colony, see `trail_every`
+    pub trails: Vec<TrailReport>,
+    // Food sources, indexed by their item id minus FOOD_ID
+    pub food: RwLock<Vec<Food>>,
     pub step: u64,
+    pub params: Params,
+    pub observer: Observer,
+    #[cfg(feature = "snapshot")]
+    pub frames: Option<Frames>,
 }
 
 impl State for ModelState {
     fn reset(&mut self) {
+        let (width, height) = (self.params.width, self.params.height);
         self.step = 0;
-        self.ants_grid = SparseGrid2D::new(WIDTH, HEIGHT);
-        self.obstacles_grid = SparseGrid2D::new(WIDTH, HEIGHT);
-        self.to_food_grid = ToFoodGrid::new(WIDTH, HEIGHT);
-        self.to_home_grid = ToHomeGrid::new(WIDTH, HEIGHT);
-        self.food_source_found = RwLock::new(false);
-        self.food_returned_home = RwLock::new(false);
+        self.ants_grid = SparseGrid2D::new(width, height);
+        self.obstacles_grid = SparseGrid2D::new(width, height);
+        self.colonies = Colony::all(&self.params);
+        self.diffusion = None;
+        self.food_picked = RwLock::new(0);
+        self.round_trips = RwLock::new(Vec::new());
+        self.total_trips = 0;
+        self.total_trip_steps = 0;
+        self.trails = vec![TrailReport::default(); self.colonies.len()];
+        self.food = RwLock::new(Vec::new());
+        self.observer.reset();
     }
 
     fn init(&mut self, schedule: &mut Schedule) {
-        self.step = 0;
-        self.ants_grid = SparseGrid2D::new(WIDTH, HEIGHT);
-        self.obstacles_grid = SparseGrid2D::new(WIDTH, HEIGHT);
-        self.to_food_grid = ToFoodGrid::new(WIDTH, HEIGHT);
-        self.to_home_grid = ToHomeGrid::new(WIDTH, HEIGHT);
-        self.food_source_found = RwLock::new(false);
-        self.food_returned_home = RwLock::new(false);
-
-        let mut rng = rand::thread_rng();
-
-        // Food generation
-        let x: i32 = if FOOD_XMIN == FOOD_XMAX {
-            FOOD_XMIN
-        } else {
-            rng.gen_range(FOOD_XMIN..FOOD_XMAX)
-        };
-        let y: i32 = if FOOD_YMIN == FOOD_YMAX {
-            FOOD_YMIN
-        } else {
-            rng.gen_range(FOOD_YMIN..FOOD_YMAX)
-        };
-        let food_location = Int2D { x, y };
-
-        self.obstacles_grid.set_object_location(
-            Item {
-                id: 888888888,
-                value: ItemType::Food,
-            },
-            &food_location,
-        );
-
-        // Nest generation
-        let x: i32 = if HOME_XMIN == HOME_XMAX {
-            HOME_XMIN
-        } else {
-            rng.gen_range(HOME_XMIN..HOME_XMAX)
+        self.reset();
+        let p = self.params.clone();
+
+        let mut rng = rng::state_rng(self.seed());
+
+        // Food generation, on the food cells of the map if it marks any
+        let mut food: Vec<Food> = match self.map.as_ref().map(|map| map.cells(Tile::Food)) {
+            Some(cells) if !cells.is_empty() => cells
+                .into_iter()
+                .map(|loc| Food::new(loc, p.food_amount))
+                .collect(),
+            _ => {
+                let x: i32 = if p.food_xmin == p.food_xmax {
+                    p.food_xmin
+                } else {
+                    rng.random_range(p.food_xmin..p.food_xmax)
+                };
+                let y: i32 = if p.food_ymin == p.food_ymax {
+                    p.food_ymin
+                } else {
+                    rng.random_range(p.food_ymin..p.food_ymax)
+                };
+                vec![Food::new(Int2D { x, y }, p.food_amount)]
+            }
         };
-        let y: i32 = if HOME_YMIN == HOME_YMAX {
-            HOME_YMIN
-        } else {
-            rng.gen_range(HOME_YMIN..HOME_YMAX)
+        food.extend(p.food_sources.iter().map(|source| {
+            Food::new(
+                Int2D {
+                    x: source.x,
+                    y: source.y,
+                },
+                source.amount,
+            )
+        }));
+        for (i, source) in food.iter().enumerate() {
+            self.obstacles_grid.set_object_location(
+                Item {
+                    id: FOOD_ID + i as u32,
+                    value: ItemType::Food,
+                },
+                &source.loc,
+            );
+        }
+        self.food = RwLock::new(food);
+
+        // Nest generation, on the nest cells of the map if it marks any. Ants start from the
+        // first of them, otherwise from the middle of the nest range.
+        let (nest, ant_loc) = match self.map.as_ref().map(|map| map.cells(Tile::Nest)) {
+            Some(cells) if !cells.is_empty() => {
+                let first = cells[0];
+                (cells, first)
+            }
+            _ => {
+                let x: i32 = if p.home_xmin == p.home_xmax {
+                    p.home_xmin
+                } else {
+                    rng.random_range(p.home_xmin..p.home_xmax)
+                };
+                let y: i32 = if p.home_ymin == p.home_ymax {
+                    p.home_ymin
+                } else {
+                    rng.random_range(p.home_ymin..p.home_ymax)
+                };
+                let middle = Int2D {
+                    x: (p.home_xmax + p.home_xmin) / 2,
+                    y: (p.home_ymax + p.home_ymin) / 2,
+                };
+                (vec![Int2D { x, y }], middle)
+            }
         };
-        let nest_location = Int2D { x, y };
-        self.obstacles_grid.set_object_location(
-            Item {
-                id: 99999999,
-                value: ItemType::Home,
-            },
-            &nest_location,
-        );
-
-        // Obastacles generation
+        self.colonies[0].nest = nest;
+        let nests = self.colonies.iter().flat_map(|colony| &colony.nest);
+        for (i, nest_location) in nests.enumerate() {
+            self.obstacles_grid.set_object_location(
+                Item {
+                    id: HOME_ID + i as u32,
+                    value: ItemType::Home,
+                },
+                nest_location,
+            );
+        }
+
+        // Obastacles generation, from the map if there is one
         /* General formula to calculate an ellipsis, used to draw obstacles.
            x and y define a specific cell
            horizontal and vertical define the ellipsis location (bottom left: 0,0)
@@ -155,15 +256,20 @@ impl State for ModelState {
         };
 
         let mut obstacle_id = 0;
-        for i in 0..WIDTH {
-            for j in 0..HEIGHT {
-                // Good obstacle placement for 500x500 simulations
-                // if ellipsis(i as f32, j as f32, 300., 345., 0.407)
-                //    || ellipsis(i as f32, j as f32, 190., 155., 0.407)
-                if ellipsis(i as f32, j as f32, 100., 145., 0.407)
-                    || ellipsis(i as f32, j as f32, 90., 55., 0.407)
-                {
-                    let obstacle_location = Int2D { x: i, y: j };
+        for i in 0..p.width {
+            for j in 0..p.height {
+                let obstacle_location = Int2D { x: i, y: j };
+                let obstacle = match &self.map {
+                    Some(map) => map.tile(&obstacle_location) == Tile::Obstacle,
+                    // Good obstacle placement for 500x500 simulations
+                    // ellipsis(i as f32, j as f32, 300., 345., 0.407)
+                    //    || ellipsis(i as f32, j as f32, 190., 155., 0.407)
+                    None => {
+                        ellipsis(i as f32, j as f32, 100., 145., 0.407)
+                            || ellipsis(i as f32, j as f32, 90., 55., 0.407)
+                    }
+                };
+                if obstacle {
                     self.obstacles_grid.set_object_location(
                         Item {
                             id: obstacle_id,
@@ -176,26 +282,176 @@ impl State for ModelState {
             }
         }
 
-        // Ants generation
-        for ant_id in 0..NUM_AGENT {
-            let x = (HOME_XMAX + HOME_XMIN) / 2;
-            let y = (HOME_YMAX + HOME_YMIN) / 2;
-            let ant_loc = Int2D { x, y };
-            // Generate the ant with an initial reward of 1, so that it starts spreading home pheromones
-            // around the nest, the initial spawn point.
-            let ant = Ant::new(ant_id, ant_loc, false, 1.);
-            self.ants_grid.set_object_location(ant, &ant_loc);
-            schedule.schedule_repeating(Box::new(ant), 0., 0);
+        // Ants generation, colony by colony: the first ones are the scouts, then the idle ants
+        let mut ant_id = 0;
+        for (i, colony) in self.colonies.iter().enumerate() {
+            let ant_loc = if i == 0 { ant_loc } else { colony.nest[0] };
+            let scouts = (p.scouts * colony.num_agent as f64).round() as u32;
+            let idle = (p.idle * colony.num_agent as f64).round() as u32;
+            for k in 0..colony.num_agent {
+                // Generate the ant with an initial reward of 1, so that it starts spreading home pheromones
+                // around the nest, the initial spawn point.
+                let mut ant = Ant::new(ant_id, i, ant_loc, false, 1.);
+                if k < scouts {
+                    ant.role = Role::Scout;
+                    ant.scout = true;
+                } else if k < scouts + idle {
+                    ant.role = Role::Idle;
+                }
+                self.ants_grid.set_object_location(ant, &ant_loc);
+                schedule.schedule_repeating(Box::new(ant), 0., 0);
+                ant_id += 1;
+            }
         }
 
         self.obstacles_grid.update();
+        self.diffusion = self.diffusion();
     }
 
     fn update(&mut self, step: u64) {
         self.ants_grid.lazy_update();
-        self.to_food_grid.update();
-        self.to_home_grid.update();
+        for colony in &mut self.colonies {
+            colony.to_food_grid.update(self.diffusion.as_ref());
+            colony.to_home_grid.update(self.diffusion.as_ref());
+        }
+        self.remove_depleted_food();
+        let events: Vec<Event> = self
+            .params
+            .events
+            .iter()
+            .filter(|event| event.step() == step)
+            .cloned()
+            .collect();
+        for event in events {
+            event.apply(self);
+        }
         self.step = step;
+        #[cfg(feature = "snapshot")]
+        frames::record(self, step);
+    }
+
+    fn before_step(&mut self, _schedule: &mut Schedule) {
+        for colony in &self.colonies {
+            *colony.food_delivered.write().unwrap() = 0;
+            *colony.fights_lost.write().unwrap() = 0;
+            *colony.recruited.write().unwrap() = 0;
+        }
+        *self.food_picked.write().unwrap() = 0;
+        self.round_trips.write().unwrap().clear();
+    }
+
+    fn after_step(&mut self, schedule: &mut Schedule) {
+        let mut with_food = vec![0; self.colonies.len()];
+        // ants of each colony in each role, in the order of `Role`
+        let mut roles = vec![[0; 4]; self.colonies.len()];
+        for agent in schedule.get_all_events() {
+            let ant = agent.downcast_ref::<Ant>().unwrap();
+            if ant.has_food {
+                with_food[ant.colony] += 1;
+            }
+            roles[ant.colony][ant.role as usize] += 1;
+        }
+        let decay = self.params.recruitment_decay;
+        for colony in &mut self.colonies {
+            colony.total_delivered += *colony.food_delivered.read().unwrap() as u64;
+            colony.recruitment =
+                colony.recruitment * decay + *colony.recruited.read().unwrap() as f32;
+        }
+        let round_trips = {
+            let trips = self.round_trips.read().unwrap();
+            self.total_trips += trips.len() as u64;
+            self.total_trip_steps += trips.iter().sum::<u64>();
+            trips.len()
+        };
+        let mean_round_trip = if self.total_trips == 0 {
+            0.
+        } else {
+            self.total_trip_steps as f64 / self.total_trips as f64
+        };
+        let (sources, left) = self.food_left();
+        let mut metrics = vec![
+            (
+                String::from("ants_with_food"),
+                with_food.iter().sum::<u32>() as f64,
+            ),
+            (
+                String::from("food_picked"),
+                *self.food_picked.read().unwrap() as f64,
+            ),
+            (String::from("food_delivered"), self.food_delivered() as f64),
+            (String::from("round_trips"), round_trips as f64),
+            (String::from("mean_round_trip"), mean_round_trip),
+            (String::from("food_sources"), sources as f64),
+            (String::from("food_left"), left as f64),
+        ];
+        // Task allocation, when ants take other roles than foraging
+        if self.params.castes() {
+            for (i, name) in ["scouts", "foragers", "recruiters", "idle_ants"]
+                .iter()
+                .enumerate()
+            {
+                let count: u32 = roles.iter().map(|colony| colony[i]).sum();
+                metrics.push((name.to_string(), count as f64));
+            }
+            let recruitment: f32 = self.colonies.iter().map(|colony| colony.recruitment).sum();
+            metrics.push((String::from("recruitment"), recruitment as f64));
+        }
+        // Foraging of each colony, when they compete
+        if self.colonies.len() > 1 {
+            for (i, colony) in self.colonies.iter().enumerate() {
+                let delivered = *colony.food_delivered.read().unwrap();
+                let fights_lost = *colony.fights_lost.read().unwrap();
+                metrics.extend([
+                    (format!("colony_{}_ants_with_food", i), with_food[i] as f64),
+                    (format!("colony_{}_food_delivered", i), delivered as f64),
+                    (format!("colony_{}_food_per_ant", i), colony.food_per_ant()),
+                    (format!("colony_{}_fights_lost", i), fights_lost as f64),
+                ]);
+                if self.params.castes() {
+                    metrics.extend([
+                        (
+                            format!("colony_{}_idle_ants", i),
+                            roles[i][Role::Idle as usize] as f64,
+                        ),
+                        (
+                            format!("colony_{}_recruitment", i),
+                            colony.recruitment as f64,
+                        ),
+                    ]);
+                }
+            }
+        }
+        // Trails against the shortest paths, reported again between two analyses
+        let every = self.params.trail_every;
+        if every > 0 {
+            if schedule.step.is_multiple_of(every) {
+                self.trails = (0..self.colonies.len())
+                    .map(|colony| trail::report(self, colony))
+                    .collect();
+            }
+            let length = |length: Option<usize>| length.map_or(f64::NAN, |l| l as f64);
+            for (i, report) in self.trails.iter().enumerate() {
+                let prefix = if self.colonies.len() > 1 {
+                    format!("colony_{}_", i)
+                } else {
+                    String::new()
+                };
+                metrics.extend([
+                    (format!("{}trail_to_food", prefix), length(report.to_food)),
+                    (format!("{}trail_to_nest", prefix), length(report.to_nest)),
+                    (format!("{}shortest_path", prefix), length(report.shortest)),
+                    (
+                        format!("{}trail_stretch", prefix),
+                        report.stretch().unwrap_or(f64::NAN),
+                    ),
+                ]);
+            }
+        }
+        let metrics: Vec<(&str, f64)> = metrics
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+        self.observer.record(schedule.step + 1, &metrics);
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -216,16 +472,38 @@ impl State for ModelState {
 }
 
 impl ModelState {
-    pub(crate) fn new() -> ModelState {
-        ModelState {
-            ants_grid: SparseGrid2D::new(WIDTH, HEIGHT),
-            obstacles_grid: SparseGrid2D::new(WIDTH, HEIGHT),
-            to_food_grid: ToFoodGrid::new(WIDTH, HEIGHT),
-            to_home_grid: ToHomeGrid::new(WIDTH, HEIGHT),
-            food_source_found: RwLock::new(false),
-            food_returned_home: RwLock::new(false),
-            step: 0,
+    // The state of a run with the given parameters. A map, if any, is read here and sets the
+    // dimensions of the field; a map that cannot be read is an error.
+    pub fn new(mut params: Params) -> Result<ModelState, String> {
+        let map = params.map.as_deref().map(Map::read).transpose()?;
+        if let Some(map) = &map {
+            (params.width, params.height) = (map.width, map.height);
         }
+        let (width, height) = (params.width, params.height);
+        Ok(ModelState {
+            ants_grid: SparseGrid2D::new(width, height),
+            obstacles_grid: SparseGrid2D::new(width, height),
+            colonies: Colony::all(&params),
+            map,
+            diffusion: None,
+            food_picked: RwLock::new(0),
+            round_trips: RwLock::new(Vec::new()),
+            total_trips: 0,
+            total_trip_steps: 0,
+            trails: vec![TrailReport::default(); params.colonies.len() + 1],
+            food: RwLock::new(Vec::new()),
+            step: 0,
+            params,
+            observer: Observer::new(),
+            #[cfg(feature = "snapshot")]
+            frames: None,
+        })
+    }
+
+    // Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    // `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
     }
 
     // Check if a particular grid cell has an obstacle or not. Will return None if the grid cell holds no obstacle.
@@ -234,4 +512,251 @@ impl ModelState {
             .get_objects(loc)
             .filter(|vec| vec.first().unwrap().value == ItemType::Obstacle)
     }
+
+    // Diffusion of the pheromones around the obstacles of the field, None when disabled.
+    fn diffusion(&self) -> Option<Diffusion> {
+        let rate = self.params.diffusion;
+        (rate > 0.).then(|| {
+            Diffusion::new(rate, self.params.width, self.params.height, |loc| {
+                self.get_obstacle(loc).is_some()
+            })
+        })
+    }
+
+    // Units of food brought back to the nests during the current step.
+    pub fn food_delivered(&self) -> u32 {
+        self.colonies
+            .iter()
+            .map(|colony| *colony.food_delivered.read().unwrap())
+            .sum()
+    }
+
+    // Number of food sources that have not run out, and units of food left in the finite ones.
+    pub fn food_left(&self) -> (usize, u32) {
+        let food = self.food.read().unwrap();
+        let sources = food.iter().filter(|source| !source.is_depleted()).count();
+        let left = food.iter().filter_map(|source| source.left).sum();
+        (sources, left)
+    }
+
+    // Take the food sources that ran out during the step off the field. The grid is rebuilt
+    // without them, which only happens on the step a source runs out.
+    fn remove_depleted_food(&mut self) {
+        let depleted: Vec<Item> = self
+            .food
+            .read()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|(_, source)| source.is_depleted())
+            .map(|(i, source)| {
+                let item = Item {
+                    id: FOOD_ID + i as u32,
+                    value: ItemType::Food,
+                };
+                (source.loc, item)
+            })
+            .filter(|(loc, item)| {
+                self.obstacles_grid
+                    .get_objects(loc)
+                    .is_some_and(|items| items.contains(item))
+            })
+            .map(|(_, item)| item)
+            .collect();
+        if depleted.is_empty() {
+            return;
+        }
+        self.edit_field(|field| {
+            for items in field.values_mut() {
+                items.retain(|item| !depleted.contains(item));
+            }
+        });
+    }
+
+    // Put obstacles on `cells`, except those of the nests and the food, or clear the obstacles
+    // of `cells`, which must be inside the field. Pheromones left under new obstacles are
+    // cleared, and the diffusion goes around the obstacles as they now are.
+    pub fn set_obstacles(&mut self, cells: &[Int2D], obstacle: bool) {
+        let height = self.params.height;
+        let mut walled = HashSet::new();
+        self.edit_field(|field| {
+            for loc in cells {
+                let items = field.entry(*loc).or_default();
+                if obstacle {
+                    if items.is_empty() {
+                        items.push(Item {
+                            id: (loc.x * height + loc.y) as u32,
+                            value: ItemType::Obstacle,
+                        });
+                        walled.insert(*loc);
+                    }
+                } else {
+                    items.retain(|item| item.value != ItemType::Obstacle);
+                }
+            }
+        });
+        if !walled.is_empty() {
+            for colony in &mut self.colonies {
+                clear_pheromones(&mut colony.to_food_grid.grid, &walled);
+                clear_pheromones(&mut colony.to_home_grid.grid, &walled);
+            }
+        }
+        self.diffusion = self.diffusion();
+    }
+
+    // Move the food source `source` to `loc`, clearing any obstacle there. A source that ran out
+    // stays off the field.
+    pub fn move_food(&mut self, source: usize, loc: Int2D) {
+        let item = Item {
+            id: FOOD_ID + source as u32,
+            value: ItemType::Food,
+        };
+        let depleted = {
+            let mut food = self.food.write().unwrap();
+            food[source].loc = loc;
+            food[source].is_depleted()
+        };
+        self.edit_field(|field| {
+            for items in field.values_mut() {
+                items.retain(|other| *other != item);
+            }
+            if !depleted {
+                let items = field.entry(loc).or_default();
+                items.retain(|other| other.value != ItemType::Obstacle);
+                items.push(item);
+            }
+        });
+        self.diffusion = self.diffusion();
+    }
+
+    // Rebuild the field with the changes `edit` makes to the items of its cells, by location, as
+    // items can't be taken off an updated grid. All the changes of an event go in one edit.
+    fn edit_field(&mut self, edit: impl FnOnce(&mut HashMap<Int2D, Vec<Item>>)) {
+        let field = RefCell::new(HashMap::<Int2D, Vec<Item>>::new());
+        self.obstacles_grid.iter_objects(|loc, item| {
+            field.borrow_mut().entry(*loc).or_default().push(*item);
+        });
+        let mut field = field.into_inner();
+        edit(&mut field);
+        self.obstacles_grid = SparseGrid2D::new(self.params.width, self.params.height);
+        for (loc, items) in field {
+            for item in items {
+                self.obstacles_grid.set_object_location(item, &loc);
+            }
+        }
+        self.obstacles_grid.update();
+    }
+}
+
+// Clear the pheromones of `cells`, rebuilding the grid without them as in `edit_field`.
+fn clear_pheromones(grid: &mut SparseNumberGrid2D<f32>, cells: &HashSet<Int2D>) {
+    let mut values = checkpoint::save_sparse_values(grid);
+    values.retain(|&(x, y, _)| !cells.contains(&Int2D { x, y }));
+    *grid = SparseNumberGrid2D::new(grid.width, grid.height);
+    checkpoint::restore_sparse_values(grid, values);
+    grid.update();
+}
+
+// The nest, the trails, the food brought back so far and the recruitment of a colony, see
+// `ModelStateSnapshot`.
+#[derive(Serialize, Deserialize)]
+pub struct ColonySnapshot {
+    pub nest: Vec<(i32, i32)>,
+    pub to_food: Vec<(i32, i32, f32)>,
+    pub to_home: Vec<(i32, i32, f32)>,
+    pub total_delivered: u64,
+    #[serde(default)]
+    pub recruitment: f32,
+}
+
+// Everything a `ModelState` needs to go on with a run, see `Resumable`.
+#[derive(Serialize, Deserialize)]
+pub struct ModelStateSnapshot {
+    pub params: Params,
+    pub step: u64,
+    pub ants: Vec<Cell<Ant>>,
+    pub obstacles: Vec<Cell<Item>>,
+    pub colonies: Vec<ColonySnapshot>,
+    pub total_trips: u64,
+    pub total_trip_steps: u64,
+    pub trails: Vec<TrailReport>,
+    pub food: Vec<Food>,
+}
+
+impl Resumable for ModelState {
+    type Snapshot = ModelStateSnapshot;
+    type Agent = Ant;
+
+    fn snapshot(&self) -> ModelStateSnapshot {
+        ModelStateSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            ants: checkpoint::save_sparse_grid(&self.ants_grid),
+            obstacles: checkpoint::save_sparse_grid(&self.obstacles_grid),
+            colonies: self
+                .colonies
+                .iter()
+                .map(|colony| ColonySnapshot {
+                    nest: colony.nest.iter().map(|loc| (loc.x, loc.y)).collect(),
+                    to_food: checkpoint::save_sparse_values(&colony.to_food_grid.grid),
+                    to_home: checkpoint::save_sparse_values(&colony.to_home_grid.grid),
+                    total_delivered: colony.total_delivered,
+                    recruitment: colony.recruitment,
+                })
+                .collect(),
+            total_trips: self.total_trips,
+            total_trip_steps: self.total_trip_steps,
+            trails: self.trails.clone(),
+            food: self.food.read().unwrap().clone(),
+        }
+    }
+
+    fn restore(snapshot: ModelStateSnapshot) -> Result<ModelState, String> {
+        let mut state = ModelState::new(snapshot.params)?;
+        state.step = snapshot.step;
+        checkpoint::restore_sparse_grid(&state.ants_grid, snapshot.ants);
+        checkpoint::restore_sparse_grid(&state.obstacles_grid, snapshot.obstacles);
+        for (colony, saved) in state.colonies.iter_mut().zip(snapshot.colonies) {
+            colony.nest = saved
+                .nest
+                .into_iter()
+                .map(|(x, y)| Int2D { x, y })
+                .collect();
+            checkpoint::restore_sparse_values(&colony.to_food_grid.grid, saved.to_food);
+            checkpoint::restore_sparse_values(&colony.to_home_grid.grid, saved.to_home);
+            colony.total_delivered = saved.total_delivered;
+            colony.recruitment = saved.recruitment;
+        }
+        // Same updates as `init` and `update`, without evaporating the pheromones again
+        state.ants_grid.lazy_update();
+        state.obstacles_grid.update();
+        state.diffusion = state.diffusion();
+        for colony in &mut state.colonies {
+            colony.to_food_grid.grid.update();
+            colony.to_home_grid.grid.update();
+        }
+        state.total_trips = snapshot.total_trips;
+        state.total_trip_steps = snapshot.total_trip_steps;
+        state.trails = snapshot.trails;
+        state.food = RwLock::new(snapshot.food);
+        Ok(state)
+    }
+
+    fn save_agent(agent: &dyn Agent) -> Ant {
+        *agent.downcast_ref::<Ant>().unwrap()
+    }
+
+    fn restore_agent(ant: Ant) -> Box<dyn Agent> {
+        Box::new(ant)
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
+    }
+
+    #[cfg(feature = "snapshot")]
+    fn take_outputs(&mut self, state: &mut ModelState) {
+        std::mem::swap(&mut self.observer, &mut state.observer);
+        self.frames = state.frames.take();
+    }
 }
@@ -1,32 +1,32 @@
-use crate::{EVAPORATION, FOOD_LOW_PHEROMONE};
-use krabmaga::engine::fields::field::Field;
-use krabmaga::engine::fields::grid_option::GridOption;
+use crate::model::diffusion::Diffusion;
+use crate::model::pheromone;
 use krabmaga::engine::fields::sparse_number_grid_2d::SparseNumberGrid2D;
 
 // Represents food pheromones. Higher f32 value means more concentrated pheromone.
 pub struct ToFoodGrid {
     pub grid: SparseNumberGrid2D<f32>,
+    // Multiplier applied to every pheromone at each step.
+    pub evaporation: f32,
+    // Pheromones below this value are cleared.
+    pub low_pheromone: f32,
 }
 
 impl ToFoodGrid {
-    pub fn new(width: i32, height: i32) -> ToFoodGrid {
+    pub fn new(width: i32, height: i32, evaporation: f32, low_pheromone: f32) -> ToFoodGrid {
         ToFoodGrid {
             grid: SparseNumberGrid2D::new(width, height),
+            evaporation,
+            low_pheromone,
         }
     }
 
-    pub fn update(&mut self) {
-        self.grid.update();
-        self.grid.apply_to_all_values(
-            |val| {
-                let new_val = val * EVAPORATION;
-                if new_val < FOOD_LOW_PHEROMONE {
-                    0.
-                } else {
-                    new_val
-                }
-            },
-            GridOption::READ,
-        )
+    // Evaporate, and diffuse, the pheromones of the last step, see `pheromone::update_grid`.
+    pub fn update(&mut self, diffusion: Option<&Diffusion>) {
+        pheromone::update_grid(
+            &mut self.grid,
+            diffusion,
+            self.evaporation,
+            self.low_pheromone,
+        );
     }
 }
@@ -0,0 +1,23 @@
+// Colours of the field, shared by the Bevy visualization and the PNG frames of the `snapshot`
+// feature.
+
+pub const BACKGROUND: [u8; 3] = [255, 255, 255];
+pub const TO_HOME: [u8; 3] = [0, 255, 0];
+pub const TO_FOOD: [u8; 3] = [0, 0, 255];
+pub const HOME: [u8; 4] = [139, 69, 19, 255];
+pub const FOOD: [u8; 4] = [255, 165, 0, 255];
+pub const OBSTACLE: [u8; 4] = [128, 128, 128, 255];
+pub const ANT: [u8; 4] = [0, 0, 0, 255];
+pub const ANT_WITH_FOOD: [u8; 4] = [220, 20, 60, 255];
+
+// Colour of a cell marked with a pheromone, more opaque as the pheromone gets stronger.
+pub fn pheromone(color: [u8; 3], value: f32) -> [u8; 4] {
+    let alpha = if value < 0.01 {
+        50u8
+    } else if value < 0.1 {
+        80u8
+    } else {
+        255u8
+    };
+    [color[0], color[1], color[2], alpha]
+}
@@ -0,0 +1,147 @@
+// Trail analytics: the path the pheromones of a colony lead its ants along between the nest and
+// the food, against the shortest path around the obstacles.
+use crate::model::state::ModelState;
+use krabmaga::engine::fields::sparse_number_grid_2d::SparseNumberGrid2D;
+use krabmaga::engine::location::Int2D;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+// Lengths, in ant steps, of the trails of a colony and of the shortest path from its nest to the
+// nearest food source. A trail is None when the pheromones don't lead all the way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrailReport {
+    // Following the food pheromones from the nest
+    pub to_food: Option<usize>,
+    // Following the home pheromones from the nearest food source
+    pub to_nest: Option<usize>,
+    // Shortest path from the nest to the nearest food source, None if the food is out of reach
+    pub shortest: Option<usize>,
+}
+
+impl TrailReport {
+    // How much longer than the shortest path the trail to the food is: 1 for an optimal trail.
+    pub fn stretch(&self) -> Option<f64> {
+        match (self.to_food, self.shortest) {
+            (Some(trail), Some(shortest)) if shortest > 0 => Some(trail as f64 / shortest as f64),
+            _ => None,
+        }
+    }
+}
+
+// Report on the trails of a colony, with the field as it is at the start of the step.
+pub fn report(state: &ModelState, colony: usize) -> TrailReport {
+    let (width, height) = (state.params.width, state.params.height);
+    let colony = &state.colonies[colony];
+    let Some(&nest) = colony.nest.first() else {
+        return TrailReport::default();
+    };
+    let food: Vec<Int2D> = {
+        let food = state.food.read().unwrap();
+        food.iter()
+            .filter(|source| !source.is_depleted())
+            .map(|source| source.loc)
+            .collect()
+    };
+    // the obstacles are looked up once, the searches go over each cell several times
+    let mut obstacles = vec![false; (width * height) as usize];
+    for x in 0..width {
+        for y in 0..height {
+            obstacles[(x * height + y) as usize] = state.get_obstacle(&Int2D { x, y }).is_some();
+        }
+    }
+    let blocked = |loc: &Int2D| obstacles[(loc.x * height + loc.y) as usize];
+    let is_food = |loc: &Int2D| food.contains(loc);
+
+    let Some(path) = shortest_path(nest, width, height, blocked, is_food) else {
+        return TrailReport::default();
+    };
+    let nearest = *path.last().unwrap();
+    let to_food = follow(&colony.to_food_grid.grid, nest, blocked, is_food);
+    let to_nest = follow(&colony.to_home_grid.grid, nearest, blocked, |loc| {
+        colony.is_nest(loc)
+    });
+    TrailReport {
+        to_food: to_food.map(|trail| trail.len() - 1),
+        to_nest: to_nest.map(|trail| trail.len() - 1),
+        shortest: Some(path.len() - 1),
+    }
+}
+
+// The dominant trail of `grid` from `start`: the cells an ant reaches by always stepping to the
+// neighbour with the strongest pheromone, never going back over its own path. None if the trail
+// fades or loops before reaching a `target`.
+pub fn follow(
+    grid: &SparseNumberGrid2D<f32>,
+    start: Int2D,
+    blocked: impl Fn(&Int2D) -> bool,
+    target: impl Fn(&Int2D) -> bool,
+) -> Option<Vec<Int2D>> {
+    let (width, height) = (grid.width, grid.height);
+    let mut visited = vec![false; (width * height) as usize];
+    let mut trail = vec![start];
+    let mut loc = start;
+    while !target(&loc) {
+        visited[(loc.x * height + loc.y) as usize] = true;
+        let mut next = None;
+        let mut max = 0.;
+        for (x, y) in neighbours(loc, width, height) {
+            let cell = Int2D { x, y };
+            if visited[(x * height + y) as usize] || blocked(&cell) {
+                continue;
+            }
+            let pheromone = grid.get_value(&cell).unwrap_or(0.);
+            if pheromone > max {
+                max = pheromone;
+                next = Some(cell);
+            }
+        }
+        loc = next?;
+        trail.push(loc);
+    }
+    Some(trail)
+}
+
+// Shortest path of an ant from `start` to the nearest `target`, moving to any of the 8
+// neighbours of a cell but through no obstacle, found with a breadth-first search. None if no
+// target can be reached.
+pub fn shortest_path(
+    start: Int2D,
+    width: i32,
+    height: i32,
+    blocked: impl Fn(&Int2D) -> bool,
+    target: impl Fn(&Int2D) -> bool,
+) -> Option<Vec<Int2D>> {
+    let index = |x: i32, y: i32| (x * height + y) as usize;
+    let mut previous: Vec<Option<Int2D>> = vec![None; (width * height) as usize];
+    previous[index(start.x, start.y)] = Some(start);
+    let mut queue = VecDeque::from([start]);
+    while let Some(loc) = queue.pop_front() {
+        if target(&loc) {
+            let mut path = vec![loc];
+            let mut cell = loc;
+            while cell != start {
+                cell = previous[index(cell.x, cell.y)].unwrap();
+                path.push(cell);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for (x, y) in neighbours(loc, width, height) {
+            let cell = Int2D { x, y };
+            if previous[index(x, y)].is_none() && !blocked(&cell) {
+                previous[index(x, y)] = Some(loc);
+                queue.push_back(cell);
+            }
+        }
+    }
+    None
+}
+
+// The 8 neighbours of `loc` inside the field.
+fn neighbours(loc: Int2D, width: i32, height: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-1..2)
+        .flat_map(|dx| (-1..2).map(move |dy| (dx, dy)))
+        .filter(|&d| d != (0, 0))
+        .map(move |(dx, dy)| (loc.x + dx, loc.y + dy))
+        .filter(move |&(x, y)| (0..width).contains(&x) && (0..height).contains(&y))
+}
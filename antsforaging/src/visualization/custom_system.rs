@@ -2,6 +2,7 @@ use crate::model::state::ModelState;
 use crate::model::state::*;
 use crate::model::to_food_grid::ToFoodGrid;
 use crate::model::to_home_grid::ToHomeGrid;
+use crate::palette;
 use krabmaga::bevy::prelude::Image;
 use krabmaga::engine::location::Int2D;
 use krabmaga::visualization::fields::number_grid_2d::BatchRender;
@@ -13,18 +14,7 @@ use krabmaga::visualization::fields::object_grid_2d::RenderObjectGrid2D;
 impl BatchRender<ModelState> for ToHomeGrid {
     fn get_pixel(&self, loc: &Int2D) -> [u8; 4] {
         match self.grid.get_value(loc) {
-            Some(val) => {
-                let cell = val;
-
-                let alpha = if cell < 0.01 {
-                    50u8
-                } else if cell < 0.1 {
-                    80u8
-                } else {
-                    255u8
-                };
-                [0u8, 255u8, 0u8, alpha]
-            }
+            Some(val) => palette::pheromone(palette::TO_HOME, val),
             None => [0u8, 255u8, 0u8, 0u8],
         }
     }
@@ -37,26 +27,16 @@ impl BatchRender<ModelState> for ToHomeGrid {
         0.
     }
 
+    // Trails of the first colony
     fn get_texture_from_state(state: &ModelState) -> Image {
-        state.to_home_grid.texture()
+        state.colonies[0].to_home_grid.texture()
     }
 }
 
 impl BatchRender<ModelState> for ToFoodGrid {
     fn get_pixel(&self, loc: &Int2D) -> [u8; 4] {
         match self.grid.get_value(loc) {
-            Some(val) => {
-                let cell = val;
-
-                let alpha = if cell < 0.01 {
-                    50u8
-                } else if cell < 0.1 {
-                    80u8
-                } else {
-                    255u8
-                };
-                [0u8, 0u8, 255u8, alpha]
-            }
+            Some(val) => palette::pheromone(palette::TO_FOOD, val),
             None => [0u8, 0u8, 255u8, 0u8],
         }
     }
@@ -70,7 +50,7 @@ impl BatchRender<ModelState> for ToFoodGrid {
     }
 
     fn get_texture_from_state(state: &ModelState) -> Image {
-        state.to_food_grid.texture()
+        state.colonies[0].to_food_grid.texture()
     }
 }
 
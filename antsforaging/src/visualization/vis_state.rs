@@ -29,8 +29,12 @@ impl VisualizationState<ModelState> for VisState {
         _schedule: &mut Schedule,
         sim: &mut SimulationDescriptor,
     ) {
-        state.to_home_grid.render(sprite_factory, commands, sim);
-        state.to_food_grid.render(sprite_factory, commands, sim);
+        state.colonies[0]
+            .to_home_grid
+            .render(sprite_factory, commands, sim);
+        state.colonies[0]
+            .to_food_grid
+            .render(sprite_factory, commands, sim);
         SparseGrid2D::<Item>::init_graphics_grid(sprite_factory, commands, state);
     }
 
@@ -55,6 +59,7 @@ impl VisualizationState<ModelState> for VisState {
         if let Some(_ant_vis) = agent_render.downcast_ref::<AntVis>() {
             match state.ants_grid.get(&Ant::new(
                 agent_render.get_id(),
+                0,
                 Int2D { x: 0, y: 0 },
                 false,
                 0.,
@@ -0,0 +1,1230 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use antsforaging::aco::params::AcoParams;
+use antsforaging::aco::state::AcoState;
+use antsforaging::aco::tsplib::Instance;
+use antsforaging::model::ant::{Ant, Role};
+use antsforaging::model::diffusion::Diffusion;
+use antsforaging::model::event::Event;
+use antsforaging::model::map::{Map, Tile};
+use antsforaging::model::params::{FoodSource, Nest, Params};
+use antsforaging::model::pheromone;
+use antsforaging::model::state::{ItemType, ModelState, ModelStateSnapshot};
+use antsforaging::model::to_food_grid::ToFoodGrid;
+use antsforaging::trail;
+use examples_common::checkpoint::{self, Resumable, ScheduleSnapshot};
+use krabmaga::engine::fields::field::Field;
+use krabmaga::engine::fields::sparse_number_grid_2d::SparseNumberGrid2D;
+use krabmaga::engine::location::Int2D;
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+
+fn init(params: Params) -> (ModelState, Schedule) {
+    let mut state = ModelState::new(params).unwrap();
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    (state, schedule)
+}
+
+// (id, x, y, has food) of every scheduled ant, sorted by id
+fn ants(schedule: &Schedule) -> Vec<(u32, i32, i32, bool)> {
+    let mut ants: Vec<_> = schedule
+        .get_all_events()
+        .iter()
+        .map(|agent| {
+            let ant = agent.downcast_ref::<Ant>().unwrap();
+            (ant.id, ant.loc.x, ant.loc.y, ant.has_food)
+        })
+        .collect();
+    ants.sort();
+    ants
+}
+
+#[test]
+fn ants_are_conserved_and_stay_inside_the_field() {
+    let params = Params::default();
+    let (width, height, num_agent) = (params.width, params.height, params.num_agent);
+    let (mut state, mut schedule) = init(params);
+
+    for _ in 0..200 {
+        schedule.step(&mut state);
+        let ants = ants(&schedule);
+        assert_eq!(ants.len(), num_agent as usize);
+        for (id, x, y, _) in ants {
+            assert!(
+                x >= 0 && x < width && y >= 0 && y < height,
+                "ant {} left",
+                id
+            );
+        }
+    }
+}
+
+#[test]
+fn pheromones_are_never_negative() {
+    let (mut state, mut schedule) = init(Params::default());
+    for _ in 0..200 {
+        schedule.step(&mut state);
+    }
+
+    for x in 0..state.params.width {
+        for y in 0..state.params.height {
+            let loc = Int2D { x, y };
+            for grid in [
+                &state.colonies[0].to_food_grid.grid,
+                &state.colonies[0].to_home_grid.grid,
+            ] {
+                if let Some(pheromone) = grid.get_value(&loc) {
+                    assert!(pheromone.is_finite() && pheromone >= 0.);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut state, mut schedule) = init(Params {
+            seed,
+            ..Default::default()
+        });
+        for _ in 0..100 {
+            schedule.step(&mut state);
+        }
+        ants(&schedule)
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+// Pheromone of every marked cell of a grid, sorted by location
+fn pheromones(grid: &SparseNumberGrid2D<f32>) -> Vec<(i32, i32, u32)> {
+    let mut values: Vec<_> = checkpoint::save_sparse_values(grid)
+        .into_iter()
+        .map(|(x, y, value)| (x, y, value.to_bits()))
+        .collect();
+    values.sort();
+    values
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let resume = |params: Params| {
+        let (mut state, mut schedule) = init(params);
+        for _ in 0..100 {
+            schedule.step(&mut state);
+        }
+        let saved = serde_json::to_string(&(
+            state.snapshot(),
+            ScheduleSnapshot::take(&mut schedule, ModelState::save_agent),
+        ))
+        .unwrap();
+        let (snapshot, events): (ModelStateSnapshot, ScheduleSnapshot<Ant>) =
+            serde_json::from_str(&saved).unwrap();
+        let mut resumed = ModelState::restore(snapshot).unwrap();
+        let mut resumed_schedule = events.restore(ModelState::restore_agent);
+
+        for _ in 0..100 {
+            schedule.step(&mut state);
+            resumed_schedule.step(&mut resumed);
+        }
+        assert_eq!(ants(&resumed_schedule), ants(&schedule));
+        assert_eq!(resumed.colonies.len(), state.colonies.len());
+        for (resumed, colony) in resumed.colonies.iter().zip(&state.colonies) {
+            assert_eq!(
+                pheromones(&resumed.to_food_grid.grid),
+                pheromones(&colony.to_food_grid.grid)
+            );
+            assert_eq!(
+                pheromones(&resumed.to_home_grid.grid),
+                pheromones(&colony.to_home_grid.grid)
+            );
+            assert_eq!(resumed.total_delivered, colony.total_delivered);
+        }
+        assert_eq!(resumed.trails, state.trails);
+        for (resumed, colony) in resumed.colonies.iter().zip(&state.colonies) {
+            assert_eq!(resumed.recruitment, colony.recruitment);
+        }
+        assert_eq!(resumed.total_trips, state.total_trips);
+    };
+    resume(Params {
+        seed: 3,
+        ..Default::default()
+    });
+    resume(Params {
+        seed: 3,
+        fight_probability: 0.5,
+        trail_masking: 0.2,
+        trail_every: 25,
+        scouts: 0.2,
+        idle: 0.3,
+        recruit_threshold: 0.5,
+        rest_threshold: 0.5,
+        events: vec![
+            Event::AddObstacles {
+                step: 50,
+                x: 30,
+                y: 40,
+                width: 10,
+                height: 2,
+            },
+            Event::BlockTrail {
+                step: 150,
+                colony: 1,
+                size: 3,
+            },
+        ],
+        ..colonies()
+    });
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.toml");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}
+
+// A small field with the nest in a corner, a finite source in the opposite one and a second one
+// next to the nest
+fn food_sources() -> Params {
+    Params {
+        width: 30,
+        height: 30,
+        num_agent: 50,
+        home_xmin: 25,
+        home_xmax: 25,
+        home_ymin: 25,
+        home_ymax: 25,
+        food_xmin: 3,
+        food_xmax: 3,
+        food_ymin: 3,
+        food_ymax: 3,
+        food_amount: 5,
+        food_sources: vec![FoodSource {
+            x: 20,
+            y: 22,
+            amount: 10,
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn food_taken_is_carried_or_delivered_until_sources_run_out() {
+    let (mut state, mut schedule) = init(food_sources());
+    assert_eq!(state.food_left(), (2, 15));
+
+    let mut delivered = 0;
+    for _ in 0..3000 {
+        schedule.step(&mut state);
+        delivered += state.food_delivered();
+        let carried = ants(&schedule).iter().filter(|ant| ant.3).count() as u32;
+        let (_, left) = state.food_left();
+        assert_eq!(15 - left, delivered + carried);
+
+        // a source that ran out is taken off the field
+        let food = state.food.read().unwrap().clone();
+        for source in &food {
+            let on_field = state
+                .obstacles_grid
+                .get_objects(&source.loc)
+                .is_some_and(|items| items.iter().any(|item| item.value == ItemType::Food));
+            assert_eq!(on_field, !source.is_depleted());
+        }
+    }
+    assert_eq!(state.food_left(), (0, 0), "the food did not run out");
+    assert!(delivered > 0);
+}
+
+#[test]
+fn unlimited_food_never_runs_out() {
+    let (mut state, mut schedule) = init(Params {
+        food_amount: 0,
+        ..food_sources()
+    });
+    for _ in 0..1000 {
+        schedule.step(&mut state);
+    }
+    let food = state.food.read().unwrap();
+    assert_eq!(food[0].left, None);
+    assert!(!food[0].is_depleted());
+}
+
+#[test]
+fn maps_are_read_from_ascii_greymap_and_image_files() {
+    use Tile::*;
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let tiles = |map: &Map| {
+        [(0, 1), (1, 1), (2, 1), (0, 0), (1, 0), (2, 0)].map(|(x, y)| map.tile(&Int2D { x, y }))
+    };
+
+    // the first line is the top of the field, and short lines are padded with empty cells
+    let path = dir.join("map.txt");
+    fs::write(&path, "#NF\n.#\n").unwrap();
+    let map = Map::read(&path).unwrap();
+    assert_eq!((map.width, map.height), (3, 2));
+    assert_eq!(tiles(&map), [Obstacle, Nest, Food, Empty, Obstacle, Empty]);
+    let cells: Vec<_> = map.cells(Obstacle).iter().map(|c| (c.x, c.y)).collect();
+    assert_eq!(cells, [(0, 1), (1, 0)]);
+    fs::write(&path, "#N\n.x\n").unwrap();
+    let error = Map::read(&path).unwrap_err();
+    assert!(error.contains("unknown cell `x` on line 2"), "{}", error);
+    let state = ModelState::new(Params {
+        map: Some(path.clone()),
+        ..Default::default()
+    });
+    assert_eq!(state.err(), Some(error));
+
+    // greymaps only mark obstacles, the dark pixels
+    let path = dir.join("map.pgm");
+    fs::write(&path, "P2\n# a comment\n3 2\n255\n0 255 200\n255 100 255\n").unwrap();
+    let map = Map::read(&path).unwrap();
+    assert_eq!(
+        tiles(&map),
+        [Obstacle, Empty, Empty, Empty, Obstacle, Empty]
+    );
+    fs::write(
+        &path,
+        [&b"P5 3 2 255\n"[..], &[0, 255, 200, 255, 100, 255]].concat(),
+    )
+    .unwrap();
+    assert_eq!(Map::read(&path).unwrap(), map);
+
+    // images have the colours of the frames
+    let path = dir.join("map.png");
+    let mut encoder = png::Encoder::new(fs::File::create(&path).unwrap(), 3, 2);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    let top = [10, 10, 10, 140, 70, 20, 250, 160, 10];
+    let bottom = [250, 250, 250, 120, 130, 128, 255, 255, 255];
+    writer.write_image_data(&[top, bottom].concat()).unwrap();
+    writer.finish().unwrap();
+    let map = Map::read(&path).unwrap();
+    assert_eq!(tiles(&map), [Obstacle, Nest, Food, Empty, Obstacle, Empty]);
+}
+
+#[test]
+fn map_sets_the_field_obstacles_nest_and_food() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("corridor.txt");
+    fs::write(
+        &path,
+        "##########\n#N......F#\n#.######.#\n#........#\n##########\n",
+    )
+    .unwrap();
+    let params = Params {
+        map: Some(path),
+        num_agent: 20,
+        food_amount: 3,
+        ..Default::default()
+    };
+    params.validate().unwrap();
+    let (mut state, mut schedule) = init(params);
+    assert_eq!((state.params.width, state.params.height), (10, 5));
+    let food = state.food.read().unwrap()[0].loc;
+    assert_eq!((food.x, food.y), (8, 3));
+    let item = |x, y| {
+        let items = state.obstacles_grid.get_objects(&Int2D { x, y });
+        items.map(|items| items[0].value)
+    };
+    assert!(item(1, 3) == Some(ItemType::Home) && item(0, 0) == Some(ItemType::Obstacle));
+    assert!(ants(&schedule).iter().all(|ant| (ant.1, ant.2) == (1, 3)));
+
+    for _ in 0..300 {
+        schedule.step(&mut state);
+        for (id, x, y, _) in ants(&schedule) {
+            let loc = Int2D { x, y };
+            assert!(state.get_obstacle(&loc).is_none(), "ant {} in a wall", id);
+        }
+    }
+
+    // the nest and food ranges are checked against the map when it doesn't mark them
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("empty.txt");
+    fs::write(&path, "....\n....\n").unwrap();
+    let error = Params {
+        map: Some(path),
+        ..Default::default()
+    }
+    .validate()
+    .unwrap_err();
+    assert!(error.contains("inside 0..4"), "{}", error);
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/maze.toml");
+    let (state, _) = init(Params::from_file(&path).unwrap());
+    assert_eq!((state.params.width, state.params.height), (40, 20));
+}
+
+// Two colonies at the top corners of a small field, and a food source between them
+fn colonies() -> Params {
+    Params {
+        width: 30,
+        height: 30,
+        num_agent: 40,
+        home_xmin: 25,
+        home_xmax: 25,
+        home_ymin: 25,
+        home_ymax: 25,
+        food_xmin: 15,
+        food_xmax: 15,
+        food_ymin: 12,
+        food_ymax: 12,
+        food_amount: 400,
+        colonies: vec![Nest {
+            x: 4,
+            y: 25,
+            num_agent: 30,
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn colonies_forage_for_their_own_nest() {
+    let (mut state, mut schedule) = init(Params {
+        fight_probability: 0.5,
+        trail_masking: 0.05,
+        ..colonies()
+    });
+    let start = ants(&schedule);
+    assert_eq!(start.len(), 70);
+    let colony = |id: u32| if id < 40 { 0 } else { 1 };
+    for (id, x, y, _) in start {
+        assert_eq!((x, y), [(25, 25), (4, 25)][colony(id)]);
+    }
+
+    let (mut delivered, mut fights) = ([0; 2], [0; 2]);
+    for _ in 0..2000 {
+        schedule.step(&mut state);
+        for (i, colony) in state.colonies.iter().enumerate() {
+            delivered[i] += *colony.food_delivered.read().unwrap() as u64;
+            fights[i] += *colony.fights_lost.read().unwrap();
+            assert_eq!(colony.total_delivered, delivered[i]);
+        }
+        let carried = ants(&schedule).iter().filter(|ant| ant.3).count() as u64;
+        let (_, left) = state.food_left();
+        assert_eq!(400 - left as u64, delivered.iter().sum::<u64>() + carried);
+    }
+    assert!(delivered.iter().all(|&d| d > 0), "{:?}", delivered);
+    assert!(fights.iter().sum::<u32>() > 0);
+    let food_per_ant = state.colonies[1].food_per_ant();
+    assert_eq!(food_per_ant, delivered[1] as f64 / 30.);
+}
+
+#[test]
+fn ants_mask_the_trails_of_other_colonies() {
+    let (mut state, _) = init(Params {
+        trail_masking: 0.25,
+        ..colonies()
+    });
+    let loc = Int2D { x: 10, y: 10 };
+    state.colonies[0]
+        .to_food_grid
+        .grid
+        .set_value_location(0.8, &loc);
+    state.colonies[0].to_food_grid.grid.update();
+    let rival = &state.colonies[0].to_food_grid.grid;
+
+    // an ant of the second colony walks by, and leaves the first one's trail fainter
+    let mut ant = Ant::new(1000, 1, loc, false, 0.);
+    ant.deposit_pheromone(&state);
+    assert_eq!(rival.get_value_unbuffered(&loc), Some(0.6));
+    // but its own colony's trails are left alone
+    let mut ant = Ant::new(1001, 0, loc, false, 0.);
+    ant.deposit_pheromone(&state);
+    assert_eq!(rival.get_value_unbuffered(&loc), Some(0.6));
+}
+
+#[test]
+fn pheromones_evaporate_until_deposited_again() {
+    let mut trail = ToFoodGrid::new(5, 5, 0.5, 0.1);
+    let loc = Int2D { x: 2, y: 3 };
+    trail.grid.set_value_location(1., &loc);
+    let mut values = Vec::new();
+    for _ in 0..4 {
+        trail.update(None);
+        values.push(trail.grid.get_value(&loc));
+    }
+    assert_eq!(values, [Some(0.5), Some(0.25), Some(0.125), Some(0.)]);
+}
+
+#[test]
+fn trails_and_arcs_share_the_pheromone_update() {
+    // evaporation first, then the deposit, and what is left below the floor is cleared
+    assert_eq!(pheromone::update(1_f32, 0.5, 0., 0.1), 0.5);
+    assert_eq!(pheromone::update(0.15_f32, 0.5, 0., 0.1), 0.);
+    assert_eq!(pheromone::update(0.15_f32, 0.5, 0.05, 0.1), 0.125);
+    assert_eq!(pheromone::update(0.5_f64, 0.5, 0.25, 0.), 0.5);
+}
+
+// Sum of the pheromones of a grid
+fn total(grid: &SparseNumberGrid2D<f32>) -> f32 {
+    checkpoint::save_sparse_values(grid)
+        .iter()
+        .map(|v| v.2)
+        .sum()
+}
+
+#[test]
+fn diffusion_spreads_pheromone_around_obstacles_without_loss() {
+    // a wall on the column x = 2, below the top row
+    let wall = |loc: &Int2D| loc.x == 2 && loc.y < 4;
+    let diffusion = Diffusion::new(0.5, 5, 5, wall);
+    let mut grid = SparseNumberGrid2D::new(5, 5);
+    grid.set_value_location(0.8, &Int2D { x: 1, y: 2 });
+    grid.set_value_location(0.4, &Int2D { x: 0, y: 0 });
+    grid.update();
+    diffusion.spread(&mut grid);
+
+    let value =
+        |grid: &SparseNumberGrid2D<f32>, x, y| grid.get_value(&Int2D { x, y }).unwrap_or(0.);
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+    // next to the wall, 3 of the 8 shares stay in the cell
+    assert!(close(value(&grid, 1, 2), 0.8 * 0.5 + 0.05 * 3.));
+    assert!(close(value(&grid, 1, 3), 0.05) && close(value(&grid, 0, 3), 0.05));
+    // in the corner, 5 shares stay
+    assert!(close(value(&grid, 0, 0), 0.4 * 0.5 + 0.025 * 5.));
+    for y in 0..4 {
+        assert_eq!(grid.get_value(&Int2D { x: 2, y }), None);
+    }
+    assert!(close(total(&grid), 1.2));
+
+    // once spread, pheromone goes around the wall through the top row
+    for _ in 0..20 {
+        diffusion.spread(&mut grid);
+    }
+    assert!(value(&grid, 4, 0) > 0.);
+    assert!(close(total(&grid), 1.2));
+}
+
+#[test]
+fn dense_and_sparse_diffusion_agree() {
+    let blocked = |loc: &Int2D| (loc.x + 2 * loc.y) % 7 == 0;
+    let spread = |dense_coverage| {
+        let mut diffusion = Diffusion::new(0.3, 20, 15, blocked);
+        diffusion.dense_coverage = dense_coverage;
+        let mut grid = SparseNumberGrid2D::new(20, 15);
+        for i in 0..12 {
+            let loc = Int2D {
+                x: (i * 7) % 20,
+                y: (i * 5) % 15,
+            };
+            if !blocked(&loc) {
+                grid.set_value_location(1. + i as f32, &loc);
+            }
+        }
+        grid.update();
+        for _ in 0..10 {
+            diffusion.spread(&mut grid);
+        }
+        pheromones(&grid)
+    };
+    // always dense, and sparse as long as less than the whole field is covered
+    assert_eq!(spread(0.), spread(1.));
+}
+
+#[test]
+fn diffused_trails_stay_out_of_the_walls() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("diffusion.txt");
+    fs::write(
+        &path,
+        "##########\n#N......F#\n#.######.#\n#........#\n##########\n",
+    )
+    .unwrap();
+    let (mut state, mut schedule) = init(Params {
+        map: Some(path),
+        num_agent: 20,
+        diffusion: 0.2,
+        ..Default::default()
+    });
+    for _ in 0..200 {
+        schedule.step(&mut state);
+    }
+    let colony = &state.colonies[0];
+    for grid in [&colony.to_food_grid.grid, &colony.to_home_grid.grid] {
+        assert!(total(grid) > 0.);
+        for (x, y, value) in checkpoint::save_sparse_values(grid) {
+            assert!(value.is_finite() && value >= 0.);
+            if value > 0. {
+                assert!(state.get_obstacle(&Int2D { x, y }).is_none());
+            }
+        }
+    }
+}
+
+#[test]
+fn food_picked_and_round_trips_are_counted() {
+    let (mut state, mut schedule) = init(Params {
+        food_amount: 0,
+        ..food_sources()
+    });
+    let (mut picked, mut delivered) = (0, 0);
+    for _ in 0..1000 {
+        schedule.step(&mut state);
+        picked += *state.food_picked.read().unwrap();
+        delivered += state.food_delivered();
+        let carried = ants(&schedule).iter().filter(|ant| ant.3).count() as u32;
+        assert_eq!(picked, delivered + carried);
+
+        // one round trip per unit delivered, at least as long as there and back from the nearest
+        // source, 5 cells away
+        let round_trips = state.round_trips.read().unwrap();
+        assert_eq!(round_trips.len() as u32, state.food_delivered());
+        assert!(round_trips.iter().all(|&steps| steps >= 10));
+    }
+    assert!(delivered > 0);
+    assert_eq!(state.total_trips, delivered as u64);
+    let mean = state.observer.series("mean_round_trip").unwrap();
+    assert_eq!(
+        *mean.last().unwrap(),
+        state.total_trip_steps as f64 / state.total_trips as f64
+    );
+}
+
+#[test]
+fn shortest_path_goes_around_obstacles() {
+    let wall = |loc: &Int2D| loc.x == 2 && loc.y < 4;
+    let start = Int2D { x: 0, y: 0 };
+    let target = |loc: &Int2D| *loc == Int2D { x: 4, y: 0 };
+
+    let open = trail::shortest_path(start, 5, 5, |_| false, target).unwrap();
+    assert_eq!(open.len() - 1, 4);
+    let path = trail::shortest_path(start, 5, 5, wall, target).unwrap();
+    assert_eq!(path.len() - 1, 8);
+    assert!(path.iter().all(|loc| !wall(loc)));
+    for step in path.windows(2) {
+        assert!((step[0].x - step[1].x).abs() <= 1 && (step[0].y - step[1].y).abs() <= 1);
+    }
+
+    let closed = |loc: &Int2D| loc.x == 2;
+    assert!(trail::shortest_path(start, 5, 5, closed, target).is_none());
+}
+
+#[test]
+fn trails_are_followed_up_the_pheromones() {
+    let mut grid = SparseNumberGrid2D::new(10, 10);
+    // a trail along the bottom row, stronger towards the target, and a fainter branch upwards
+    for x in 0..10 {
+        grid.set_value_location(1. + x as f32, &Int2D { x, y: 0 });
+    }
+    for y in 1..5 {
+        grid.set_value_location(0.5, &Int2D { x: 1, y });
+    }
+    grid.update();
+    let start = Int2D { x: 0, y: 0 };
+    let target = |loc: &Int2D| *loc == Int2D { x: 9, y: 0 };
+
+    let path = trail::follow(&grid, start, |_| false, target).unwrap();
+    let cells: Vec<(i32, i32)> = path.iter().map(|loc| (loc.x, loc.y)).collect();
+    assert_eq!(cells, (0..10).map(|x| (x, 0)).collect::<Vec<_>>());
+
+    // the trail fades before the target
+    let wall = |loc: &Int2D| loc.x == 5;
+    assert!(trail::follow(&grid, start, wall, target).is_none());
+}
+
+#[test]
+fn trails_are_compared_with_the_shortest_path() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("trails.txt");
+    fs::write(
+        &path,
+        "##########\n#N......F#\n#.######.#\n#........#\n##########\n",
+    )
+    .unwrap();
+    let (mut state, mut schedule) = init(Params {
+        map: Some(path),
+        num_agent: 20,
+        trail_every: 50,
+        ..Default::default()
+    });
+    for _ in 0..500 {
+        schedule.step(&mut state);
+    }
+    let report = state.trails[0];
+    assert_eq!(report.shortest, Some(7));
+    let to_food = report.to_food.expect("no trail to the food");
+    assert!(to_food >= 7);
+    assert!(report.stretch().unwrap() >= 1.);
+    if let Some(to_nest) = report.to_nest {
+        assert!(to_nest >= 7);
+    }
+    let shortest = state.observer.series("shortest_path").unwrap();
+    assert!(shortest.iter().all(|&length| length == 7.));
+}
+
+// Cells of the field holding an obstacle
+fn obstacles(state: &ModelState) -> Vec<(i32, i32)> {
+    checkpoint::save_sparse_grid(&state.obstacles_grid)
+        .iter()
+        .filter(|cell| {
+            cell.objects
+                .iter()
+                .any(|item| item.value == ItemType::Obstacle)
+        })
+        .map(|cell| (cell.x, cell.y))
+        .collect()
+}
+
+#[test]
+fn events_are_read_from_scenarios_and_checked() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/shortcut.toml");
+    let params = Params::from_file(&path).unwrap();
+    assert_eq!(
+        params.events,
+        [
+            Event::RemoveObstacles {
+                step: 1500,
+                x: 20,
+                y: 2,
+                width: 1,
+                height: 3,
+            },
+            Event::BlockTrail {
+                step: 3000,
+                colony: 0,
+                size: 3,
+            },
+        ]
+    );
+
+    let invalid = |event: Event| {
+        Params {
+            events: vec![event],
+            ..food_sources()
+        }
+        .validate()
+        .unwrap_err()
+    };
+    let error = invalid(Event::AddObstacles {
+        step: 1,
+        x: 25,
+        y: 0,
+        width: 10,
+        height: 1,
+    });
+    assert!(error.contains("not inside the field"), "{}", error);
+    let error = invalid(Event::MoveFood {
+        step: 1,
+        source: 2,
+        x: 0,
+        y: 0,
+    });
+    assert!(error.contains("no food source 2"), "{}", error);
+    let error = invalid(Event::BlockTrail {
+        step: 1,
+        colony: 1,
+        size: 3,
+    });
+    assert!(error.contains("no colony 1"), "{}", error);
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("events.toml");
+    fs::write(&path, "[[events]]\nstep = 1\naction = \"flood\"\n").unwrap();
+    assert!(Params::from_file(&path).is_err());
+}
+
+#[test]
+fn obstacles_are_added_and_removed_during_a_run() {
+    let (mut state, mut schedule) = init(Params {
+        diffusion: 0.2,
+        events: vec![
+            // the wall goes across the food source next to the nest, which stays
+            Event::AddObstacles {
+                step: 100,
+                x: 18,
+                y: 22,
+                width: 5,
+                height: 1,
+            },
+            Event::RemoveObstacles {
+                step: 200,
+                x: 19,
+                y: 22,
+                width: 2,
+                height: 1,
+            },
+        ],
+        food_sources: vec![FoodSource {
+            x: 20,
+            y: 22,
+            amount: 0,
+        }],
+        ..food_sources()
+    });
+    let before = obstacles(&state);
+    let wall: Vec<(i32, i32)> = vec![(18, 22), (19, 22), (21, 22), (22, 22)];
+    assert!(wall.iter().all(|cell| !before.contains(cell)));
+
+    for _ in 0..300 {
+        // the events of a step are applied once the previous one is over
+        schedule.step(&mut state);
+        let step = schedule.step;
+        let field = obstacles(&state);
+        if (100..200).contains(&step) {
+            assert_eq!(field.len(), before.len() + 4);
+            assert!(wall.iter().all(|cell| field.contains(cell)));
+        } else if step >= 200 {
+            // the food source was never walled in
+            assert_eq!(field.len(), before.len() + 3);
+            assert!(field.contains(&(18, 22)) && !field.contains(&(19, 22)));
+        }
+        let food = state.food.read().unwrap()[1].loc;
+        assert!(state.get_obstacle(&food).is_none());
+        for (id, x, y, _) in ants(&schedule) {
+            // ants walled in by the event walk out of it
+            if step > 101 {
+                let loc = Int2D { x, y };
+                assert!(state.get_obstacle(&loc).is_none(), "ant {} in a wall", id);
+            }
+        }
+        // no pheromone is left under the new walls, nor spreads into them
+        let colony = &state.colonies[0];
+        for grid in [&colony.to_food_grid.grid, &colony.to_home_grid.grid] {
+            for (x, y, value) in checkpoint::save_sparse_values(grid) {
+                if value > 0. {
+                    assert!(state.get_obstacle(&Int2D { x, y }).is_none());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn food_moves_with_what_it_has_left() {
+    let (mut state, mut schedule) = init(Params {
+        food_amount: 100,
+        events: vec![Event::MoveFood {
+            step: 500,
+            source: 0,
+            x: 10,
+            y: 10,
+        }],
+        ..food_sources()
+    });
+    let has_food = |state: &ModelState, x, y| {
+        state
+            .obstacles_grid
+            .get_objects(&Int2D { x, y })
+            .is_some_and(|items| items.iter().any(|item| item.value == ItemType::Food))
+    };
+    for _ in 0..499 {
+        schedule.step(&mut state);
+    }
+    let left = state.food.read().unwrap()[0].left;
+    assert!(has_food(&state, 3, 3) && !has_food(&state, 10, 10));
+
+    schedule.step(&mut state);
+    let source = state.food.read().unwrap()[0];
+    assert_eq!((source.loc.x, source.loc.y), (10, 10));
+    // the food taken during the step is not given back
+    assert!(source.left <= left);
+    assert_eq!(has_food(&state, 10, 10), !source.is_depleted());
+    assert!(!has_food(&state, 3, 3));
+}
+
+#[test]
+fn blocked_trail_is_walled_off() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("blocked.txt");
+    fs::write(
+        &path,
+        "############\n#N........F#\n#..........#\n#..........#\n#..........#\n#..........#\n############\n",
+    )
+    .unwrap();
+    let (mut state, mut schedule) = init(Params {
+        map: Some(path),
+        num_agent: 20,
+        events: vec![Event::BlockTrail {
+            step: 300,
+            colony: 0,
+            size: 3,
+        }],
+        ..Default::default()
+    });
+    let before = obstacles(&state);
+    for _ in 0..300 {
+        schedule.step(&mut state);
+    }
+    // a 3x3 square across the middle of the trail, the walls around the field left aside
+    let added: Vec<(i32, i32)> = obstacles(&state)
+        .into_iter()
+        .filter(|cell| !before.contains(cell))
+        .collect();
+    assert!((6..=9).contains(&added.len()), "{:?}", added);
+    let xs: Vec<i32> = added.iter().map(|cell| cell.0).collect();
+    assert!(xs.iter().max().unwrap() - xs.iter().min().unwrap() == 2);
+    assert!(xs.iter().all(|&x| (3..=8).contains(&x)));
+
+    // the food can still be reached around the block
+    let report = antsforaging::trail::report(&state, 0);
+    assert!(report.shortest.is_some());
+}
+
+#[test]
+fn block_is_cut_at_the_edges_of_the_field() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("corridor.txt");
+    fs::write(&path, "N....F\n").unwrap();
+    let (mut state, mut schedule) = init(Params {
+        map: Some(path),
+        num_agent: 5,
+        events: vec![Event::BlockTrail {
+            step: 1,
+            colony: 0,
+            size: 9,
+        }],
+        ..Default::default()
+    });
+    for _ in 0..3 {
+        schedule.step(&mut state);
+    }
+    // the whole corridor but the nest and the food, and nothing off the field
+    assert_eq!(obstacles(&state), [(1, 0), (2, 0), (3, 0), (4, 0)]);
+    let items = RefCell::new(Vec::new());
+    state
+        .obstacles_grid
+        .iter_objects(|loc, item| items.borrow_mut().push((loc.x, loc.y, item.id)));
+    let items = items.into_inner();
+    assert_eq!(items.len(), 6);
+    assert!(items.iter().all(|&(x, y, _)| (0..6).contains(&x) && y == 0));
+}
+
+// A small field with the nest in a corner and a source that never runs out in the opposite one,
+// the ants split into castes
+fn castes() -> Params {
+    Params {
+        width: 30,
+        height: 30,
+        num_agent: 50,
+        home_xmin: 25,
+        home_xmax: 25,
+        home_ymin: 25,
+        home_ymax: 25,
+        food_xmin: 3,
+        food_xmax: 3,
+        food_ymin: 3,
+        food_ymax: 3,
+        scouts: 0.2,
+        idle: 0.4,
+        ..Default::default()
+    }
+}
+
+// Number of ants in each role, in the order of `Role`
+fn roles(schedule: &Schedule) -> [u32; 4] {
+    let mut roles = [0; 4];
+    for agent in schedule.get_all_events() {
+        roles[agent.downcast_ref::<Ant>().unwrap().role as usize] += 1;
+    }
+    roles
+}
+
+#[test]
+fn castes_are_assigned_and_idle_ants_wait_in_the_nest() {
+    let (mut state, mut schedule) = init(castes());
+    assert_eq!(roles(&schedule), [10, 20, 0, 20]);
+    for _ in 0..20 {
+        schedule.step(&mut state);
+    }
+    // nothing is recruited yet, the idle ants are still in the nest
+    for agent in schedule.get_all_events() {
+        let ant = agent.downcast_ref::<Ant>().unwrap();
+        if ant.role == Role::Idle {
+            assert_eq!((ant.loc.x, ant.loc.y), (25, 25));
+        }
+    }
+    assert_eq!(state.observer.series("idle_ants").unwrap()[19], 20.);
+    assert_eq!(state.observer.series("recruitment").unwrap()[19], 0.);
+
+    // without castes there are only foragers, and no role columns
+    let (mut state, mut schedule) = init(Params::default());
+    schedule.step(&mut state);
+    assert_eq!(roles(&schedule), [0, 100, 0, 0]);
+    assert!(state.observer.series("scouts").is_none());
+
+    let error = Params {
+        scouts: 0.6,
+        idle: 0.6,
+        ..castes()
+    }
+    .validate()
+    .unwrap_err();
+    assert!(error.contains("at most 1"), "{}", error);
+}
+
+#[test]
+fn recruiters_rouse_the_idle_ants() {
+    let (mut state, mut schedule) = init(Params {
+        recruiter_trips: 2,
+        ..castes()
+    });
+    for _ in 0..1000 {
+        schedule.step(&mut state);
+    }
+    let recruiters = state.observer.series("recruiters").unwrap();
+    let recruitment = state.observer.series("recruitment").unwrap();
+    let idle = state.observer.series("idle_ants").unwrap();
+    assert!(recruiters.iter().any(|&count| count > 0.));
+    assert!(recruitment.iter().any(|&value| value > 0.));
+    assert!(idle[999] < idle[0]);
+    // the recruiters are scouts that found food, they go back to scouting after two deliveries
+    assert!(state.total_trips > 0);
+    for agent in schedule.get_all_events() {
+        let ant = agent.downcast_ref::<Ant>().unwrap();
+        assert_eq!(ant.scout, ant.id < 10);
+        if ant.scout {
+            assert!(matches!(ant.role, Role::Scout | Role::Recruiter));
+            assert!(ant.role == Role::Scout || ant.trips < 2);
+        } else {
+            assert_ne!(ant.role, Role::Recruiter);
+        }
+    }
+}
+
+#[test]
+fn forager_deliveries_rouse_the_idle_ants() {
+    // no scouts, so no recruiters: only the foragers bring food back
+    let run = |recruiters_only: bool| {
+        let (mut state, mut schedule) = init(Params {
+            scouts: 0.,
+            recruiters_only,
+            ..castes()
+        });
+        for _ in 0..1000 {
+            schedule.step(&mut state);
+        }
+        assert!(state.total_trips > 0);
+        let recruitment = state.observer.series("recruitment").unwrap().to_vec();
+        let idle = state.observer.series("idle_ants").unwrap().to_vec();
+        (recruitment, idle)
+    };
+
+    let (recruitment, idle) = run(false);
+    assert!(recruitment.iter().any(|&value| value > 0.));
+    assert!(idle[999] < idle[0]);
+
+    // counting only the recruiters, the idle ants are never roused
+    let (recruitment, idle) = run(true);
+    assert!(recruitment.iter().all(|&value| value == 0.));
+    assert_eq!(idle[999], 20.);
+}
+
+#[test]
+fn tsplib_distances_follow_the_edge_weight_types() {
+    let euclidean = Instance::parse(
+        "NAME: square\nTYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 3 4\n3 1.2 1.2\nEOF\n",
+    )
+    .unwrap();
+    assert_eq!(euclidean.name, "square");
+    assert_eq!(euclidean.distance(0, 1), 5.);
+    // 1.697 and 3.329, rounded to the nearest integer
+    assert_eq!(euclidean.distance(0, 2), 2.);
+    assert_eq!(euclidean.distance(2, 1), 3.);
+    assert_eq!(euclidean.edges(), vec![(0, 1), (0, 2), (1, 2)]);
+
+    // the same matrix in every explicit format
+    let explicit = |format: &str, weights: &str| {
+        let content = format!(
+            "TYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n{}\nEOF\n",
+            format, weights
+        );
+        let instance = Instance::parse(&content).unwrap();
+        let mut matrix = Vec::new();
+        for u in 0..4 {
+            for v in 0..4 {
+                matrix.push(instance.distance(u, v));
+            }
+        }
+        matrix
+    };
+    let full = explicit("FULL_MATRIX", "0 1 2 3\n1 0 4 5\n2 4 0 6\n3 5 6 0");
+    assert_eq!(explicit("UPPER_ROW", "1 2 3\n4 5\n6"), full);
+    assert_eq!(explicit("LOWER_ROW", "1\n2 4\n3 5 6"), full);
+    assert_eq!(explicit("UPPER_DIAG_ROW", "0 1 2 3 0 4 5 0 6 0"), full);
+    assert_eq!(explicit("LOWER_DIAG_ROW", "0\n1 0\n2 4 0\n3 5 6 0"), full);
+
+    // great circle distances, as in the explicit matrix of burma14
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/burma14.tsp");
+    let burma14 = Instance::read(&path).unwrap();
+    assert_eq!(burma14.dimension, 14);
+    assert_eq!(burma14.distance(0, 1), 153.);
+    assert_eq!(burma14.distance(0, 2), 510.);
+    assert_eq!(burma14.distance(13, 4), 636.);
+
+    // an adjacency list gives the edges of the matching edge list
+    let sparse = |format: &str, section: &str| {
+        let content = format!(
+            "TYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: MAN_2D\nNODE_COORD_SECTION\n1 0 0\n2 1 0\n3 1 1\n4 0 1\nEDGE_DATA_FORMAT: {}\nEDGE_DATA_SECTION\n{}\nEOF\n",
+            format, section
+        );
+        Instance::parse(&content).unwrap()
+    };
+    let list = sparse("EDGE_LIST", "1 2\n2 3\n1 4\n-1");
+    assert_eq!(list.edges(), vec![(0, 1), (1, 2), (0, 3)]);
+    assert_eq!(
+        sparse("ADJ_LIST", "1 2 4 -1\n2 3 -1\n-1").edges(),
+        vec![(0, 1), (0, 3), (1, 2)]
+    );
+    assert_eq!(list.distance(0, 2), 2.);
+
+    assert!(Instance::parse("TYPE: HCP\nDIMENSION: 3\nEOF\n").is_err());
+    assert!(Instance::parse("DIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_2D\nEOF\n").is_err());
+}
+
+#[test]
+fn tsplib_sections_need_a_dimension_first() {
+    let explicit = |header: &str, format: &str| {
+        let content = format!(
+            "TYPE: TSP\n{}EDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n1 2 3\nDIMENSION: 3\nEOF\n",
+            header, format
+        );
+        Instance::parse(&content)
+    };
+    for format in ["UPPER_ROW", "LOWER_ROW", "UPPER_DIAG_ROW", "FULL_MATRIX"] {
+        // a section before the DIMENSION, or after a DIMENSION of 0 or 1, cannot be sized
+        for header in ["", "DIMENSION: 0\n", "DIMENSION: 1\n"] {
+            let error = explicit(header, format).unwrap_err();
+            assert!(error.contains("DIMENSION"), "{}", error);
+        }
+    }
+    assert!(Instance::parse(
+        "TYPE: TSP\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 3 4\nDIMENSION: 2\nEOF\n"
+    )
+    .is_err());
+    assert!(Instance::parse("TYPE: TSP\nDIMENSION: 1\nEOF\n").is_err());
+}
+
+fn solve(params: AcoParams) -> AcoState {
+    let iterations = params.iterations;
+    let mut state = AcoState::new(params).unwrap();
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    for _ in 0..iterations {
+        schedule.step(&mut state);
+    }
+    state
+}
+
+fn burma14() -> AcoParams {
+    AcoParams {
+        instance: Some(Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/burma14.tsp")),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn colony_finds_the_optimal_burma14_tour() {
+    let state = solve(burma14());
+    let best = state.best.clone().unwrap();
+    assert_eq!(best.length, 3323.);
+    // a tour through every city, back to the first one
+    assert_eq!(best.nodes.len(), 15);
+    assert_eq!(best.nodes.first(), best.nodes.last());
+    let mut cities = best.nodes[1..].to_vec();
+    cities.sort();
+    assert_eq!(cities, (0..14).collect::<Vec<_>>());
+    let length: f64 = best
+        .nodes
+        .windows(2)
+        .map(|arc| state.instance.distance(arc[0], arc[1]))
+        .sum();
+    assert_eq!(length, best.length);
+
+    // a best tour per iteration, and the best so far never gets longer
+    assert_eq!(state.history.len(), 100);
+    let series = state.observer.series("best_length").unwrap();
+    assert_eq!(series.len(), 100);
+    assert!(series.windows(2).all(|pair| pair[1] <= pair[0]));
+    let iteration_best = state.observer.series("iteration_best").unwrap();
+    for (walk, length) in state.history.iter().zip(iteration_best) {
+        assert_eq!(walk.as_ref().unwrap().length, *length);
+    }
+}
+
+#[test]
+fn same_seed_replays_same_tours() {
+    let run = |seed| {
+        let state = solve(AcoParams {
+            seed,
+            iterations: 20,
+            ..burma14()
+        });
+        state.history
+    };
+    assert_eq!(run(5), run(5));
+    assert_ne!(run(5), run(6));
+}
+
+#[test]
+fn colony_finds_the_shortest_path_on_a_sparse_graph() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/detour.tsp");
+    let instance = Instance::read(&path).unwrap();
+    // Dijkstra from node 1 on the listed edges
+    let n = instance.dimension;
+    let mut distance = vec![f64::INFINITY; n];
+    let mut done = vec![false; n];
+    distance[0] = 0.;
+    while let Some(u) = (0..n)
+        .filter(|&u| !done[u] && distance[u].is_finite())
+        .min_by(|&a, &b| distance[a].total_cmp(&distance[b]))
+    {
+        done[u] = true;
+        for (a, b) in instance.edges() {
+            for (from, to) in [(a, b), (b, a)] {
+                if from == u {
+                    distance[to] = distance[to].min(distance[u] + instance.distance(u, to));
+                }
+            }
+        }
+    }
+    assert_eq!(distance[7], 44.);
+
+    let state = solve(AcoParams {
+        instance: Some(path),
+        beta: 1.,
+        source: Some(1),
+        target: Some(8),
+        ..Default::default()
+    });
+    let best = state.best.unwrap();
+    assert_eq!(best.length, distance[7]);
+    assert_eq!(best.nodes, vec![0, 4, 5, 6, 7]);
+    // some ants run into the dead end at node 9
+    let failed = state.observer.series("failed_walks").unwrap();
+    assert!(failed.iter().sum::<f64>() > 0.);
+}
+
+#[test]
+fn aco_scenarios_are_read_and_checked() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // the instances are named from the crate directory, where the tests run
+    for scenario in ["scenarios/aco_burma14.toml", "scenarios/aco_detour.toml"] {
+        AcoState::new(AcoParams::from_file(&dir.join(scenario)).unwrap()).unwrap();
+    }
+
+    let mut params = burma14();
+    params.set("BETA", "5").unwrap();
+    params.set("source", "1").unwrap();
+    assert_eq!(params.beta, 5.);
+    assert!(params.set("gamma", "1").is_err());
+    // a source needs a target, inside the instance, which is only read with the state
+    assert!(params.validate().is_err());
+    params.set("target", "15").unwrap();
+    params.validate().unwrap();
+    assert!(AcoState::new(params.clone()).is_err());
+    params.set("target", "14").unwrap();
+    params.validate().unwrap();
+    assert_eq!(params.endpoints(), Some((0, 13)));
+    AcoState::new(params).unwrap();
+
+    assert!(AcoState::new(AcoParams::default()).is_err());
+    let unreadable = AcoParams {
+        instance: Some(dir.join("scenarios/missing.tsp")),
+        ..Default::default()
+    };
+    assert!(AcoState::new(unreadable).is_err());
+    let invalid = AcoParams {
+        evaporation: 0.,
+        ..burma14()
+    };
+    assert!(invalid.validate().is_err());
+}
@@ -0,0 +1,376 @@
+use crate::observer::{Observer, TimeSeries};
+use krabmaga::engine::agent::Agent;
+use krabmaga::engine::agentimpl::AgentImpl;
+use krabmaga::engine::fields::dense_number_grid_2d::DenseNumberGrid2D;
+use krabmaga::engine::fields::dense_object_grid_2d::DenseGrid2D;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use krabmaga::engine::fields::field_2d::{Field2D, Location2D};
+use krabmaga::engine::fields::sparse_number_grid_2d::SparseNumberGrid2D;
+use krabmaga::engine::fields::sparse_object_grid_2d::SparseGrid2D;
+use krabmaga::engine::location::{Int2D, Real2D};
+use krabmaga::engine::priority::Priority;
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use std::cell::RefCell;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{self, BufReader, BufWriter};
+use std::mem;
+use std::path::PathBuf;
+
+/// Model state that can be written to a checkpoint and rebuilt from it.
+pub trait Resumable: State + Sized {
+    /// Everything the state needs to go on with the run, except the scheduled agents.
+    type Snapshot: Serialize + DeserializeOwned;
+    /// Any agent the model puts in the schedule.
+    type Agent: Serialize + DeserializeOwned;
+
+    fn snapshot(&self) -> Self::Snapshot;
+    /// Rebuild the state. Fails when the snapshot refers to files of the run, like a terrain,
+    /// that cannot be read anymore.
+    fn restore(snapshot: Self::Snapshot) -> Result<Self, String>;
+    fn save_agent(agent: &dyn Agent) -> Self::Agent;
+    fn restore_agent(agent: Self::Agent) -> Box<dyn Agent>;
+    fn observer(&mut self) -> &mut Observer;
+
+    /// Move the outputs of the run, like the observer, from `state` to this restored state.
+    fn take_outputs(&mut self, state: &mut Self) {
+        mem::swap(self.observer(), state.observer());
+    }
+}
+
+/// An agent waiting in the schedule queue.
+#[derive(Serialize, Deserialize)]
+pub struct Event<A> {
+    pub id: u32,
+    pub repeating: bool,
+    pub time: f32,
+    pub ordering: i32,
+    pub agent: A,
+}
+
+/// Pending events of a schedule, with its clock and the next id it hands out to an agent.
+#[derive(Serialize, Deserialize)]
+pub struct ScheduleSnapshot<A> {
+    pub step: u64,
+    pub time: f32,
+    pub next_id: u32,
+    pub events: Vec<Event<A>>,
+}
+
+impl<A> ScheduleSnapshot<A> {
+    /// Snapshot the pending events of `schedule`. The order in which agents of the same priority
+    /// are stepped depends on how the queue was filled, so the queue is also rebuilt the way
+    /// `restore` does: from there on, the run goes on exactly as one resumed from the snapshot.
+    pub fn take(schedule: &mut Schedule, save_agent: impl Fn(&dyn Agent) -> A) -> Self {
+        let mut events: Vec<(AgentImpl, Priority)> =
+            mem::take(&mut schedule.events).into_iter().collect();
+        events.sort_by(|(a, p), (b, q)| {
+            p.time
+                .total_cmp(&q.time)
+                .then(p.ordering.cmp(&q.ordering))
+                .then(a.id.cmp(&b.id))
+        });
+        let mut snapshot = ScheduleSnapshot {
+            step: schedule.step,
+            time: schedule.time,
+            next_id: schedule.agent_ids_counting,
+            events: Vec::with_capacity(events.len()),
+        };
+        for (agent, priority) in events {
+            snapshot.events.push(Event {
+                id: agent.id,
+                repeating: agent.repeating,
+                time: priority.time,
+                ordering: priority.ordering,
+                agent: save_agent(agent.agent.as_ref()),
+            });
+            schedule.events.push(agent, priority);
+        }
+        snapshot
+    }
+
+    /// Rebuild the schedule, queueing the events in the order they were saved.
+    pub fn restore(self, restore_agent: impl Fn(A) -> Box<dyn Agent>) -> Schedule {
+        let mut schedule = Schedule::new();
+        schedule.step = self.step;
+        schedule.time = self.time;
+        schedule.agent_ids_counting = self.next_id;
+        for event in self.events {
+            let mut agent = AgentImpl::new(restore_agent(event.agent), event.id);
+            agent.repeating = event.repeating;
+            schedule
+                .events
+                .push(agent, Priority::new(event.time, event.ordering));
+        }
+        schedule
+    }
+}
+
+/// Periodic snapshots of a headless run. Only the latest one is kept: each snapshot replaces
+/// the previous file once it is completely written, so a crash never leaves a partial one.
+pub struct Checkpoints {
+    pub path: PathBuf,
+    /// Steps between two snapshots.
+    pub every: u64,
+    /// Start from the snapshot at `path`, when there is one, instead of from scratch.
+    pub resume: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint<S, A> {
+    rep: u64,
+    state: S,
+    schedule: ScheduleSnapshot<A>,
+    metrics: TimeSeries,
+}
+
+impl Checkpoints {
+    /// Run `reps` repetitions of `steps` steps, as `simulate!` does, taking a snapshot every
+    /// `every` steps. A resumed run keeps the outputs of `state` and the parameters saved in
+    /// the snapshot.
+    pub fn run<S: Resumable>(&self, mut state: S, steps: u64, reps: u64) -> Result<(), String> {
+        let mut first_rep = 0;
+        let mut resumed = None;
+        if self.resume && self.path.exists() {
+            let checkpoint: Checkpoint<S::Snapshot, S::Agent> = self.load()?;
+            let mut restored = S::restore(checkpoint.state)
+                .map_err(|e| format!("cannot resume from `{}`: {}", self.path.display(), e))?;
+            restored.take_outputs(&mut state);
+            state = restored;
+            state.observer().resume(checkpoint.rep, checkpoint.metrics);
+            println!(
+                "Resuming repetition {} from step {}",
+                checkpoint.rep, checkpoint.schedule.step
+            );
+            first_rep = checkpoint.rep;
+            resumed = Some(checkpoint.schedule.restore(S::restore_agent));
+        }
+
+        for rep in first_rep..reps {
+            let mut schedule = match resumed.take() {
+                Some(schedule) => schedule,
+                None => {
+                    let mut schedule = Schedule::new();
+                    state.init(&mut schedule);
+                    schedule
+                }
+            };
+            while schedule.step < steps {
+                schedule.step(&mut state);
+                if state.end_condition(&mut schedule) {
+                    break;
+                }
+                if schedule.step % self.every == 0 {
+                    self.save(rep, &mut state, &mut schedule)?;
+                }
+            }
+            println!("Repetition {} done at step {}", rep, schedule.step);
+        }
+        Ok(())
+    }
+
+    fn save<S: Resumable>(
+        &self,
+        rep: u64,
+        state: &mut S,
+        schedule: &mut Schedule,
+    ) -> Result<(), String> {
+        let checkpoint = Checkpoint {
+            rep,
+            state: state.snapshot(),
+            schedule: ScheduleSnapshot::take(schedule, S::save_agent),
+            metrics: state.observer().time_series().clone(),
+        };
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let write = || -> io::Result<()> {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = BufWriter::new(File::create(&tmp)?);
+            serde_json::to_writer(&mut file, &checkpoint)?;
+            file.into_inner()?.sync_all()?;
+            fs::rename(&tmp, &self.path)
+        };
+        write().map_err(|e| format!("cannot write checkpoint `{}`: {}", self.path.display(), e))
+    }
+
+    fn load<S: DeserializeOwned, A: DeserializeOwned>(&self) -> Result<Checkpoint<S, A>, String> {
+        let file = File::open(&self.path)
+            .map_err(|e| format!("cannot read checkpoint `{}`: {}", self.path.display(), e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("invalid checkpoint `{}`: {}", self.path.display(), e))
+    }
+}
+
+/// Objects of the read buffer of `field`, bag by bag. The fields of the visualization engine
+/// cannot be iterated this way, so only headless runs save them.
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+pub fn save_objects<O>(field: &Field2D<O>) -> Vec<O>
+where
+    O: Location2D<Real2D> + Clone + Hash + Eq + Copy + Display,
+{
+    let objects = RefCell::new(Vec::new());
+    field.iter_objects(|_, object| objects.borrow_mut().push(*object));
+    objects.into_inner()
+}
+
+/// Write `objects` to an empty field, each at its own location. They are readable after the
+/// update the model gives the field.
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+pub fn restore_objects<O>(field: &Field2D<O>, objects: Vec<O>)
+where
+    O: Location2D<Real2D> + Clone + Hash + Eq + Copy + Display,
+{
+    for object in objects {
+        field.set_object_location(object, object.get_location());
+    }
+}
+
+/// Objects of a grid cell, in the order the grid keeps them.
+#[derive(Serialize, Deserialize)]
+pub struct Cell<O> {
+    pub x: i32,
+    pub y: i32,
+    pub objects: Vec<O>,
+}
+
+/// Non-empty cells of the read buffer of `grid`, column by column.
+pub fn save_grid<O: Eq + Hash + Copy>(grid: &DenseGrid2D<O>) -> Vec<Cell<O>> {
+    let mut cells = Vec::new();
+    for x in 0..grid.width {
+        for y in 0..grid.height {
+            if let Some(objects) = grid.get_objects(&Int2D { x, y }) {
+                cells.push(Cell { x, y, objects });
+            }
+        }
+    }
+    cells
+}
+
+/// Write `cells` to an empty grid. They are readable after the update the model gives the grid.
+pub fn restore_grid<O: Eq + Hash + Copy>(grid: &DenseGrid2D<O>, cells: Vec<Cell<O>>) {
+    for cell in cells {
+        let loc = Int2D {
+            x: cell.x,
+            y: cell.y,
+        };
+        for object in cell.objects {
+            grid.set_object_location(object, &loc);
+        }
+    }
+}
+
+/// Non-empty cells of the read buffer of a sparse `grid`, see `save_grid`.
+pub fn save_sparse_grid<O: Eq + Hash + Copy>(grid: &SparseGrid2D<O>) -> Vec<Cell<O>> {
+    let mut cells = Vec::new();
+    for x in 0..grid.width {
+        for y in 0..grid.height {
+            if let Some(objects) = grid.get_objects(&Int2D { x, y }) {
+                cells.push(Cell { x, y, objects });
+            }
+        }
+    }
+    cells
+}
+
+/// Write `cells` to an empty sparse grid, see `restore_grid`.
+pub fn restore_sparse_grid<O: Eq + Hash + Copy>(grid: &SparseGrid2D<O>, cells: Vec<Cell<O>>) {
+    for cell in cells {
+        let loc = Int2D {
+            x: cell.x,
+            y: cell.y,
+        };
+        for object in cell.objects {
+            grid.set_object_location(object, &loc);
+        }
+    }
+}
+
+/// Values of the read buffer of `grid`, column by column.
+pub fn save_values<T: Copy + PartialEq>(grid: &DenseNumberGrid2D<T>) -> Vec<Option<T>> {
+    let mut values = Vec::with_capacity((grid.width * grid.height) as usize);
+    for x in 0..grid.width {
+        for y in 0..grid.height {
+            values.push(grid.get_value(&Int2D { x, y }));
+        }
+    }
+    values
+}
+
+/// Write the `values` saved by `save_values` to an empty grid, see `restore_grid`.
+pub fn restore_values<T: Copy + PartialEq>(grid: &DenseNumberGrid2D<T>, values: Vec<Option<T>>) {
+    let mut values = values.into_iter();
+    for x in 0..grid.width {
+        for y in 0..grid.height {
+            if let Some(value) = values.next().flatten() {
+                grid.set_value_location(value, &Int2D { x, y });
+            }
+        }
+    }
+}
+
+/// Values of the read buffer of a sparse `grid` as `(x, y, value)`, column by column. Only the
+/// cells holding a value are saved.
+pub fn save_sparse_values<T: Copy + PartialEq>(grid: &SparseNumberGrid2D<T>) -> Vec<(i32, i32, T)> {
+    let mut values = Vec::new();
+    for x in 0..grid.width {
+        for y in 0..grid.height {
+            if let Some(value) = grid.get_value(&Int2D { x, y }) {
+                values.push((x, y, value));
+            }
+        }
+    }
+    values
+}
+
+/// Write the `values` saved by `save_sparse_values` to an empty grid, see `restore_grid`.
+pub fn restore_sparse_values<T: Copy + PartialEq>(
+    grid: &SparseNumberGrid2D<T>,
+    values: Vec<(i32, i32, T)>,
+) {
+    for (x, y, value) in values {
+        grid.set_value_location(value, &Int2D { x, y });
+    }
+}
+
+/// Serde mirror of `Real2D`, for fields marked `#[serde(with = "Real2DDef")]`.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Real2D")]
+pub struct Real2DDef {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Serde mirror of `Int2D`, for fields marked `#[serde(with = "Int2DDef")]`.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Int2D")]
+pub struct Int2DDef {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Serde functions of an `Option<Int2D>`, for fields marked `#[serde(with = "option_int2d")]`.
+pub mod option_int2d {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Loc(#[serde(with = "Int2DDef")] Int2D);
+
+    pub fn serialize<S: Serializer>(loc: &Option<Int2D>, serializer: S) -> Result<S::Ok, S::Error> {
+        loc.map(Loc).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Int2D>, D::Error> {
+        Ok(Option::<Loc>::deserialize(deserializer)?.map(|Loc(loc)| loc))
+    }
+}
@@ -0,0 +1,148 @@
+use clap::Args;
+use std::path::PathBuf;
+#[cfg(not(feature = "parallel"))]
+use std::thread;
+
+#[cfg(not(feature = "parallel"))]
+use crate::checkpoint::Checkpoints;
+use crate::observer::{Format, Observer, Output};
+#[cfg(not(feature = "parallel"))]
+use crate::sweep::{Design, Factor, Sweep};
+
+/// Flags of every headless example: where the metrics are exported and, with the sequential
+/// engine, the checkpoints of the run. Flattened into the `Cli` of each example.
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Directory where the per-step metrics are exported; nothing is written without it
+    #[arg(long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+
+    /// Name of the run, metrics go to `<output>/<run-id>/rep_<rep>_seed_<seed>.<format>`, each
+    /// repetition seeded with `--seed` plus the repetition
+    #[arg(long, default_value = "run")]
+    pub run_id: String,
+
+    /// Format of the exported metrics: csv, or parquet when built with the `parquet` feature
+    #[arg(long, default_value = "csv")]
+    pub format: Format,
+
+    #[cfg(not(feature = "parallel"))]
+    #[command(flatten)]
+    pub checkpoint: CheckpointArgs,
+}
+
+impl RunArgs {
+    /// Checkpoints of the run, when `--checkpoint` is given.
+    #[cfg(not(feature = "parallel"))]
+    pub fn checkpoints(&self) -> Option<Checkpoints> {
+        self.checkpoint.checkpoints()
+    }
+
+    /// Summary file of a sweep, `<output>/<run-id>/sweep.csv`.
+    #[cfg(not(feature = "parallel"))]
+    pub fn sweep_path(&self) -> PathBuf {
+        self.output
+            .clone()
+            .unwrap_or_default()
+            .join(&self.run_id)
+            .join("sweep.csv")
+    }
+
+    /// Observer of a run seeded with `seed`, exporting to `--output` when it is given.
+    pub fn observer(&self, seed: u64) -> Observer {
+        match &self.output {
+            Some(dir) => Observer::with_output(Output {
+                dir: dir.clone(),
+                run_id: self.run_id.clone(),
+                seed,
+                format: self.format,
+            }),
+            None => Observer::new(),
+        }
+    }
+}
+
+/// Checkpoint flags of a headless run, part of `RunArgs`. Flattened on their own into the `Cli`
+/// of the examples exporting no metrics.
+#[cfg(not(feature = "parallel"))]
+#[derive(Args, Debug)]
+pub struct CheckpointArgs {
+    /// Snapshot file of the run, rewritten every `--checkpoint-every` steps
+    #[arg(long, value_name = "FILE")]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Steps between two snapshots
+    #[arg(long, value_name = "N", default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    pub checkpoint_every: u64,
+
+    /// Restart from the `--checkpoint` file, when it exists
+    #[arg(long, requires = "checkpoint")]
+    pub resume: bool,
+}
+
+#[cfg(not(feature = "parallel"))]
+impl CheckpointArgs {
+    /// Checkpoints of the run, when `--checkpoint` is given.
+    pub fn checkpoints(&self) -> Option<Checkpoints> {
+        self.checkpoint.as_ref().map(|path| Checkpoints {
+            path: path.clone(),
+            every: self.checkpoint_every,
+            resume: self.resume,
+        })
+    }
+}
+
+/// Flags of a parameter sweep, flattened into the `Cli` of the examples implementing
+/// `Sweepable`.
+#[cfg(not(feature = "parallel"))]
+#[derive(Args, Debug)]
+pub struct SweepArgs {
+    /// Swept parameter, `NAME=LO:HI` or `NAME=V1,V2,...`; summarized in `<run-id>/sweep.csv`
+    #[arg(
+        long = "sweep",
+        value_name = "NAME=LEVELS",
+        requires = "output",
+        conflicts_with = "checkpoint"
+    )]
+    pub factors: Vec<Factor>,
+
+    /// Design of the sweep: grid, random or lhs (Latin hypercube)
+    #[arg(long, default_value = "grid")]
+    pub design: Design,
+
+    /// Levels of each range of a grid sweep, or points drawn by the random and lhs designs
+    #[arg(long, value_name = "N", default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    pub points: u64,
+
+    /// Worker threads of a sweep, one per core by default
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub threads: Option<u64>,
+}
+
+#[cfg(not(feature = "parallel"))]
+impl SweepArgs {
+    /// Parameter sweep, when `--sweep` is given. Each point is run `--reps` times.
+    pub fn sweep(&self) -> Option<Sweep> {
+        if self.factors.is_empty() {
+            return None;
+        }
+        let threads = match self.threads {
+            Some(threads) => threads as usize,
+            None => thread::available_parallelism().map_or(1, |n| n.get()),
+        };
+        Some(Sweep {
+            factors: self.factors.clone(),
+            design: self.design,
+            points: self.points as usize,
+            threads,
+        })
+    }
+}
+
+/// Parser of the `NAME=VALUE` overrides given with `--param`.
+pub fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) => Ok((name.trim().to_string(), value.to_string())),
+        None => Err(format!("expected NAME=VALUE, found `{}`", s)),
+    }
+}
@@ -0,0 +1,8 @@
+// The schedule of the `parallel` engine cannot be snapshotted, nor driven by a sweep
+#[cfg(not(feature = "parallel"))]
+pub mod checkpoint;
+pub mod cli;
+pub mod observer;
+pub mod rng;
+#[cfg(not(feature = "parallel"))]
+pub mod sweep;
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// File format of the exported time series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    /// Columnar Parquet file, available with the `parquet` feature.
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            #[cfg(feature = "parquet")]
+            Format::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            #[cfg(feature = "parquet")]
+            "parquet" => Ok(Format::Parquet),
+            #[cfg(not(feature = "parquet"))]
+            "parquet" => Err(String::from(
+                "parquet output needs the crate built with the `parquet` feature",
+            )),
+            _ => Err(format!("unknown format `{}`, expected csv or parquet", s)),
+        }
+    }
+}
+
+/// Destination of the metrics of a run, `<dir>/<run_id>/rep_<rep>_seed_<seed>.<format>` for
+/// each repetition. Repetition `rep` is seeded with `seed + rep`, as the replicates of a sweep.
+#[derive(Clone, Debug)]
+pub struct Output {
+    pub dir: PathBuf,
+    pub run_id: String,
+    pub seed: u64,
+    pub format: Format,
+}
+
+impl Output {
+    /// Name of the files of a repetition, without their extension: `rep_<rep>_seed_<seed>`.
+    pub fn stem(&self, rep: u64) -> String {
+        format!("rep_{}_seed_{}", rep, self.seed.wrapping_add(rep))
+    }
+
+    pub fn path(&self, rep: u64) -> PathBuf {
+        self.dir
+            .join(&self.run_id)
+            .join(format!("{}.{}", self.stem(rep), self.format.extension()))
+    }
+}
+
+/// Values recorded so far, one column per metric and one row per step.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeSeries {
+    pub names: Vec<String>,
+    pub steps: Vec<u64>,
+    pub values: Vec<Vec<f64>>,
+}
+
+/// Named metrics recorded once per step. The series are kept in memory and, when an output is
+/// set, exported: CSV rows are streamed as they are recorded, while a Parquet file is written
+/// once the run is over.
+#[derive(Default)]
+pub struct Observer {
+    data: TimeSeries,
+    output: Option<Output>,
+    csv: Option<BufWriter<File>>,
+    // Repetition being recorded, None until the first `reset`
+    rep: Option<u64>,
+}
+
+impl Observer {
+    pub fn new() -> Observer {
+        Observer::default()
+    }
+
+    pub fn with_output(output: Output) -> Observer {
+        let mut observer = Observer::new();
+        observer.output = Some(output);
+        observer
+    }
+
+    pub fn output(&self) -> Option<&Output> {
+        self.output.as_ref()
+    }
+
+    /// Close the current time series and start an empty one for the next repetition. The states
+    /// call it from `init`, so the first repetition is 0.
+    pub fn reset(&mut self) {
+        self.finish();
+        self.data = TimeSeries::default();
+        self.rep = Some(self.rep.map_or(0, |rep| rep + 1));
+    }
+
+    /// Repetition being recorded, counted by `reset`.
+    pub fn rep(&self) -> u64 {
+        self.rep.unwrap_or(0)
+    }
+
+    /// Seed of the repetition being recorded, `seed + rep`.
+    pub fn seed(&self, seed: u64) -> u64 {
+        seed.wrapping_add(self.rep())
+    }
+
+    /// Go on with the time series of repetition `rep` recorded by an earlier run, e.g. when
+    /// resuming from a checkpoint. The rows already recorded are exported again, so the output
+    /// ends up complete.
+    pub fn resume(&mut self, rep: u64, data: TimeSeries) {
+        self.csv = None;
+        self.rep = Some(rep);
+        self.data = data;
+        for row in 0..self.data.steps.len() {
+            if let Err(e) = self.write_csv_row(row) {
+                self.export_failed(e);
+                break;
+            }
+        }
+    }
+
+    /// Record the metrics of a step. Every call of a run must pass the same names, in the same
+    /// order.
+    pub fn record(&mut self, step: u64, metrics: &[(&str, f64)]) {
+        if self.data.steps.is_empty() {
+            self.data.names = metrics.iter().map(|(name, _)| name.to_string()).collect();
+            self.data.values = vec![Vec::new(); metrics.len()];
+        }
+        debug_assert!(self
+            .data
+            .names
+            .iter()
+            .eq(metrics.iter().map(|(name, _)| name)));
+
+        self.data.steps.push(step);
+        for (column, (_, value)) in self.data.values.iter_mut().zip(metrics) {
+            column.push(*value);
+        }
+
+        if let Err(e) = self.write_csv_row(self.data.steps.len() - 1) {
+            self.export_failed(e);
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.data.names
+    }
+
+    pub fn steps(&self) -> &[u64] {
+        &self.data.steps
+    }
+
+    /// Values of a metric, one per recorded step.
+    pub fn series(&self, name: &str) -> Option<&[f64]> {
+        let column = self.data.names.iter().position(|n| n == name)?;
+        Some(&self.data.values[column])
+    }
+
+    /// Everything recorded so far.
+    pub fn time_series(&self) -> &TimeSeries {
+        &self.data
+    }
+
+    /// Flush the exported time series. Called on reset and when the observer is dropped.
+    pub fn finish(&mut self) {
+        let result = match self.csv.take() {
+            Some(mut csv) => csv.flush(),
+            None => self.write_columnar(),
+        };
+        if let Err(e) = result {
+            self.export_failed(e);
+        }
+    }
+
+    fn write_csv_row(&mut self, row: usize) -> io::Result<()> {
+        match &self.output {
+            Some(output) if output.format == Format::Csv => {}
+            _ => return Ok(()),
+        }
+        if self.csv.is_none() {
+            let mut csv = BufWriter::new(create(self.output.as_ref().unwrap(), self.rep())?);
+            write!(csv, "step")?;
+            for name in &self.data.names {
+                write!(csv, ",{}", name)?;
+            }
+            writeln!(csv)?;
+            self.csv = Some(csv);
+        }
+        let csv = self.csv.as_mut().unwrap();
+        write!(csv, "{}", self.data.steps[row])?;
+        for column in &self.data.values {
+            write!(csv, ",{}", column[row])?;
+        }
+        writeln!(csv)
+    }
+
+    #[cfg(feature = "parquet")]
+    fn write_columnar(&self) -> io::Result<()> {
+        use parquet::data_type::{DoubleType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let output = match &self.output {
+            Some(output) if output.format == Format::Parquet && !self.data.steps.is_empty() => {
+                output
+            }
+            _ => return Ok(()),
+        };
+        let to_io = io::Error::other;
+
+        let mut schema = String::from("message metrics { REQUIRED INT64 step;");
+        for name in &self.data.names {
+            schema.push_str(&format!(" REQUIRED DOUBLE {};", name));
+        }
+        schema.push_str(" }");
+        let schema = Arc::new(parse_message_type(&schema).map_err(to_io)?);
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(create(output, self.rep())?, schema, properties)
+            .map_err(to_io)?;
+
+        let mut row_group = writer.next_row_group().map_err(to_io)?;
+        let steps: Vec<i64> = self.data.steps.iter().map(|&step| step as i64).collect();
+        let mut column = row_group.next_column().map_err(to_io)?.unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&steps, None, None)
+            .map_err(to_io)?;
+        column.close().map_err(to_io)?;
+        for values in &self.data.values {
+            let mut column = row_group.next_column().map_err(to_io)?.unwrap();
+            column
+                .typed::<DoubleType>()
+                .write_batch(values, None, None)
+                .map_err(to_io)?;
+            column.close().map_err(to_io)?;
+        }
+        row_group.close().map_err(to_io)?;
+        writer.close().map_err(to_io)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    fn write_columnar(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    // The state hooks cannot return errors: report the failure once and stop exporting.
+    fn export_failed(&mut self, e: io::Error) {
+        if let Some(output) = self.output.take() {
+            eprintln!(
+                "cannot export metrics to `{}`: {}",
+                output.path(self.rep()).display(),
+                e
+            );
+        }
+        self.csv = None;
+    }
+}
+
+impl Drop for Observer {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn create(output: &Output, rep: u64) -> io::Result<File> {
+    let path = output.path(rep);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    File::create(path)
+}
@@ -0,0 +1,26 @@
+use krabmaga::rand::SeedableRng;
+use krabmaga::rand_pcg::Pcg64Mcg;
+
+/// Random number generator used by the models. Every random number of a run comes from a
+/// generator derived from the seed of the run, so the same seed always replays the same
+/// trajectory.
+pub type ModelRng = Pcg64Mcg;
+
+/// Generator used by a state to set its model up in `init`.
+pub fn state_rng(seed: u64) -> ModelRng {
+    ModelRng::seed_from_u64(mix(seed))
+}
+
+/// Stream of an agent at a given step. It only depends on the seed, the agent id and the step,
+/// so an agent draws the same numbers whatever the order, or the thread, it is scheduled on.
+pub fn agent_rng(seed: u64, id: u64, step: u64) -> ModelRng {
+    ModelRng::seed_from_u64(mix(mix(mix(seed) ^ id) ^ step))
+}
+
+// SplitMix64 finalizer, spreads close inputs (ids, steps) over unrelated seeds.
+fn mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
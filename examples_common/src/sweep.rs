@@ -0,0 +1,354 @@
+use crate::observer::TimeSeries;
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use krabmaga::rand::seq::SliceRandom;
+use krabmaga::rand::{Rng, SeedableRng};
+use krabmaga::rand_pcg::Pcg64Mcg;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Parameters of a model, as a sweep reads and varies them.
+pub trait SweepParams: Clone + Sync {
+    fn seed(&self) -> u64;
+    fn set_seed(&mut self, seed: u64);
+    /// Steps of a run.
+    fn steps(&self) -> u64;
+    /// Replicates of every point.
+    fn reps(&self) -> u64;
+    /// Set a parameter by name, from its value as written on the command line.
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String>;
+    fn validate(&self) -> Result<(), String>;
+}
+
+/// Model that a sweep builds afresh for every run.
+pub trait Sweepable: State + Sized {
+    type Params: SweepParams;
+
+    /// Fails when the model cannot be set up, e.g. when a file it reads is missing.
+    fn build(params: Self::Params) -> Result<Self, String>;
+    /// Metrics recorded by the run, summarized once it is over.
+    fn metrics(&self) -> &TimeSeries;
+}
+
+/// Values a parameter takes in a sweep.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Levels {
+    /// Continuous range `LO:HI`, bounds included.
+    Range(f64, f64),
+    /// Listed values `V1,V2,...`, passed to the parameter as they are written.
+    Values(Vec<String>),
+}
+
+/// Parameter swept by name, written `NAME=LO:HI` or `NAME=V1,V2,...` on the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Factor {
+    pub name: String,
+    pub levels: Levels,
+}
+
+impl FromStr for Factor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Factor, String> {
+        let (name, spec) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected NAME=LO:HI or NAME=V1,V2,..., found `{}`", s))?;
+        let name = name.trim().to_string();
+        if ["seed", "reps"].contains(&name.to_ascii_lowercase().as_str()) {
+            return Err(format!(
+                "`{}` cannot be swept, it is set by the replicates",
+                name
+            ));
+        }
+        let levels = match spec.split_once(':') {
+            Some((lo, hi)) => {
+                let bound = |b: &str| {
+                    b.trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|b| b.is_finite())
+                        .ok_or_else(|| format!("invalid bound `{}` for parameter `{}`", b, name))
+                };
+                let (lo, hi) = (bound(lo)?, bound(hi)?);
+                if lo > hi {
+                    return Err(format!("empty range `{}` for parameter `{}`", spec, name));
+                }
+                Levels::Range(lo, hi)
+            }
+            None => Levels::Values(spec.split(',').map(|v| v.trim().to_string()).collect()),
+        };
+        Ok(Factor { name, levels })
+    }
+}
+
+/// How the points of a sweep are picked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Design {
+    /// Every combination of the levels: listed values, and `points` evenly spaced values of
+    /// each range.
+    Grid,
+    /// `points` independent uniform draws.
+    Random,
+    /// `points` draws of a Latin hypercube: each range, and each list, is split into `points`
+    /// strata and every stratum is drawn exactly once.
+    LatinHypercube,
+}
+
+impl FromStr for Design {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Design, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "grid" => Ok(Design::Grid),
+            "random" => Ok(Design::Random),
+            "lhs" | "latin-hypercube" => Ok(Design::LatinHypercube),
+            _ => Err(format!(
+                "unknown design `{}`, expected grid, random or lhs",
+                s
+            )),
+        }
+    }
+}
+
+/// Summary of a run of the sweep: how far it went and the statistics of every metric over the
+/// recorded steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub point: usize,
+    pub replicate: u64,
+    pub seed: u64,
+    /// Value given to each factor, as passed to the parameters.
+    pub values: Vec<String>,
+    pub steps: u64,
+    pub names: Vec<String>,
+    /// `[final, mean, min, max]` of each metric.
+    pub stats: Vec<[f64; 4]>,
+}
+
+impl Summary {
+    fn new(point: usize, replicate: u64, seed: u64, values: Vec<String>) -> Summary {
+        Summary {
+            point,
+            replicate,
+            seed,
+            values,
+            steps: 0,
+            names: Vec::new(),
+            stats: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, steps: u64, metrics: &TimeSeries) {
+        self.steps = steps;
+        self.names = metrics.names.clone();
+        self.stats = metrics
+            .values
+            .iter()
+            .map(|column| {
+                let mean = column.iter().sum::<f64>() / column.len().max(1) as f64;
+                let min = column.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = column.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                [column.last().copied().unwrap_or(f64::NAN), mean, min, max]
+            })
+            .collect();
+    }
+}
+
+/// Parameter sweep of a headless model: every point of the design is run `reps` times, each
+/// replicate with its own seed, on `threads` worker threads.
+pub struct Sweep {
+    pub factors: Vec<Factor>,
+    pub design: Design,
+    /// Levels of each range of a grid, or number of points of the other designs.
+    pub points: usize,
+    pub threads: usize,
+}
+
+impl Sweep {
+    /// Values of the factors at every point of the design. Random draws come from `seed`, so
+    /// the same seed gives the same points.
+    pub fn points(&self, seed: u64) -> Vec<Vec<String>> {
+        let n = self.points;
+        let mut rng = Pcg64Mcg::seed_from_u64(seed);
+        match self.design {
+            Design::Grid => {
+                let mut points = vec![Vec::new()];
+                for factor in &self.factors {
+                    let levels: Vec<String> = match &factor.levels {
+                        Levels::Range(lo, hi) => (0..n)
+                            .map(|i| {
+                                let t = if n > 1 { i as f64 / (n - 1) as f64 } else { 0. };
+                                level(lo + t * (hi - lo))
+                            })
+                            .collect(),
+                        Levels::Values(values) => values.clone(),
+                    };
+                    points = points
+                        .into_iter()
+                        .flat_map(|point| {
+                            levels.iter().map(move |level| {
+                                let mut point = point.clone();
+                                point.push(level.clone());
+                                point
+                            })
+                        })
+                        .collect();
+                }
+                points
+            }
+            Design::Random | Design::LatinHypercube => {
+                let mut points = vec![Vec::with_capacity(self.factors.len()); n];
+                for factor in &self.factors {
+                    let mut strata: Vec<usize> = (0..n).collect();
+                    if self.design == Design::LatinHypercube {
+                        strata.shuffle(&mut rng);
+                    }
+                    for (point, stratum) in points.iter_mut().zip(strata) {
+                        let u: f64 = rng.random();
+                        let t = match self.design {
+                            Design::LatinHypercube => (stratum as f64 + u) / n as f64,
+                            _ => u,
+                        };
+                        point.push(match &factor.levels {
+                            Levels::Range(lo, hi) => level(lo + t * (hi - lo)),
+                            Levels::Values(values) => {
+                                let i = (t * values.len() as f64) as usize;
+                                values[i.min(values.len() - 1)].clone()
+                            }
+                        });
+                    }
+                }
+                points
+            }
+        }
+    }
+
+    /// Run every replicate of every point, starting from `base`. Replicate `r` is seeded with
+    /// `base.seed + r` at every point, so the points are compared on the same random streams.
+    /// Points with invalid parameters are reported before anything runs.
+    pub fn run<S: Sweepable>(&self, base: &S::Params) -> Result<Vec<Summary>, String> {
+        if base.steps() == 0 {
+            return Err(String::from("a sweep needs at least one step per run"));
+        }
+        let mut runs = Vec::new();
+        for (point, values) in self.points(base.seed()).into_iter().enumerate() {
+            let mut params = base.clone();
+            let mut applied = Vec::with_capacity(values.len());
+            for (factor, value) in self.factors.iter().zip(values) {
+                applied.push(set(&mut params, &factor.name, value)?);
+            }
+            params
+                .validate()
+                .map_err(|e| format!("point {} of the sweep: {}", point, e))?;
+            for replicate in 0..base.reps() {
+                let mut params = params.clone();
+                params.set_seed(base.seed().wrapping_add(replicate));
+                runs.push((
+                    Summary::new(point, replicate, params.seed(), applied.clone()),
+                    params,
+                ));
+            }
+        }
+
+        let next = AtomicUsize::new(0);
+        let summaries = Mutex::new(Vec::with_capacity(runs.len()));
+        let failure = Mutex::new(None);
+        thread::scope(|scope| {
+            for _ in 0..self.threads.clamp(1, runs.len().max(1)) {
+                scope.spawn(|| {
+                    while let Some((summary, params)) =
+                        runs.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let mut summary = summary.clone();
+                        let steps = params.steps();
+                        let mut state = match S::build(params.clone()) {
+                            Ok(state) => state,
+                            Err(e) => {
+                                let e = format!("point {} of the sweep: {}", summary.point, e);
+                                failure.lock().unwrap().get_or_insert(e);
+                                continue;
+                            }
+                        };
+                        let mut schedule = Schedule::new();
+                        state.init(&mut schedule);
+                        while schedule.step < steps {
+                            schedule.step(&mut state);
+                            if state.end_condition(&mut schedule) {
+                                break;
+                            }
+                        }
+                        summary.record(schedule.step, state.metrics());
+                        summaries.lock().unwrap().push(summary);
+                    }
+                });
+            }
+        });
+        if let Some(e) = failure.into_inner().unwrap() {
+            return Err(e);
+        }
+        let mut summaries = summaries.into_inner().unwrap();
+        summaries.sort_by_key(|summary| (summary.point, summary.replicate));
+        Ok(summaries)
+    }
+
+    /// Write one row per run to a CSV file: the point, the replicate and its seed, the value of
+    /// each factor, the steps run, then the final, mean, minimum and maximum of every metric.
+    pub fn write(&self, path: &Path, summaries: &[Summary]) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut csv = BufWriter::new(File::create(path)?);
+        write!(csv, "point,replicate,seed")?;
+        for factor in &self.factors {
+            write!(csv, ",{}", factor.name)?;
+        }
+        write!(csv, ",steps")?;
+        for name in summaries.first().map_or(&[][..], |s| &s.names[..]) {
+            write!(csv, ",{0}_final,{0}_mean,{0}_min,{0}_max", name)?;
+        }
+        writeln!(csv)?;
+        for summary in summaries {
+            write!(
+                csv,
+                "{},{},{}",
+                summary.point, summary.replicate, summary.seed
+            )?;
+            for value in &summary.values {
+                write!(csv, ",{}", value)?;
+            }
+            write!(csv, ",{}", summary.steps)?;
+            for stats in &summary.stats {
+                for stat in stats {
+                    write!(csv, ",{}", stat)?;
+                }
+            }
+            writeln!(csv)?;
+        }
+        csv.flush()
+    }
+}
+
+// Drop the rounding noise of the arithmetic, so that 0.1 + 2 * 0.1 is written 0.3.
+fn level(value: f64) -> String {
+    ((value * 1e9).round() / 1e9).to_string()
+}
+
+// Set a swept parameter. Parameters that only take whole numbers get a value of a range rounded
+// to the nearest one; the value actually set is returned.
+fn set<P: SweepParams>(params: &mut P, name: &str, value: String) -> Result<String, String> {
+    match params.set(name, &value) {
+        Ok(()) => Ok(value),
+        Err(e) => match value.parse::<f64>() {
+            Ok(number) if number.fract() != 0. => {
+                let rounded = number.round().to_string();
+                params.set(name, &rounded).map(|_| rounded).map_err(|_| e)
+            }
+            _ => Err(e),
+        },
+    }
+}
@@ -0,0 +1,12 @@
+// The `headless` cfg stands for the sequential engine without a window: checkpoints are only
+// taken there, neither the parallel engine nor the visualizations can save their agents.
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(headless)");
+    let enabled = |feature: &str| std::env::var_os(format!("CARGO_FEATURE_{}", feature)).is_some();
+    if !["PARALLEL", "VISUALIZATION", "VISUALIZATION_WASM"]
+        .into_iter()
+        .any(enabled)
+    {
+        println!("cargo::rustc-cfg=headless");
+    }
+}
@@ -0,0 +1,79 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use examples_common::cli::{parse_key_value, RunArgs};
+use flockers::model::params::Params;
+
+/// Headless Flockers simulation.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// TOML or JSON scenario file with the model parameters
+    #[arg(long, value_name = "FILE")]
+    pub scenario: Option<PathBuf>,
+
+    /// Number of steps of each repetition
+    #[arg(long)]
+    pub steps: Option<u64>,
+
+    /// Number of repetitions
+    #[arg(long)]
+    pub reps: Option<u64>,
+
+    /// Seed of the random number generators
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Width of the field
+    #[arg(long)]
+    pub width: Option<f32>,
+
+    /// Height of the field
+    #[arg(long)]
+    pub height: Option<f32>,
+
+    /// Number of birds
+    #[arg(long)]
+    pub agents: Option<u32>,
+
+    /// Override a model parameter, e.g. `--param COHESION=1.2`
+    #[arg(long = "param", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+    pub params: Vec<(String, String)>,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+impl Cli {
+    /// Build the parameters of the run: the scenario file (or the defaults), then the named
+    /// flags, then every `--param`. The result is validated before being returned.
+    pub fn params(&self) -> Result<Params, String> {
+        let mut params = match &self.scenario {
+            Some(path) => Params::from_file(path)?,
+            None => Params::default(),
+        };
+        if let Some(steps) = self.steps {
+            params.steps = steps;
+        }
+        if let Some(reps) = self.reps {
+            params.reps = reps;
+        }
+        if let Some(seed) = self.seed {
+            params.seed = seed;
+        }
+        if let Some(width) = self.width {
+            params.width = width;
+        }
+        if let Some(height) = self.height {
+            params.height = height;
+        }
+        if let Some(agents) = self.agents {
+            params.num_agents = agents;
+        }
+        for (name, value) in &self.params {
+            params.set(name, value)?;
+        }
+        params.validate()?;
+        Ok(params)
+    }
+}
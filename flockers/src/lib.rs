@@ -0,0 +1,13 @@
+pub mod model;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+pub static COHESION: f32 = 0.8;
+pub static AVOIDANCE: f32 = 1.0;
+pub static RANDOMNESS: f32 = 1.1;
+pub static CONSISTENCY: f32 = 0.7;
+pub static MOMENTUM: f32 = 1.0;
+pub static JUMP: f32 = 0.7;
+pub static DISCRETIZATION: f32 = 10.0 / 1.5;
+pub static TOROIDAL: bool = true;
@@ -7,43 +7,56 @@ use {
 // Visualization specific imports
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 use {
-    crate::visualization::vis_state::VisState, krabmaga::bevy::prelude::Color,
+    flockers::visualization::vis_state::VisState, krabmaga::bevy::prelude::Color,
     krabmaga::visualization::visualization::Visualization,
 };
 
-use crate::model::state::Flocker;
+use flockers::model::params::Params;
+use flockers::model::state::Flocker;
 
-mod model;
-
-#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-mod visualization;
-
-pub static COHESION: f32 = 0.8;
-pub static AVOIDANCE: f32 = 1.0;
-pub static RANDOMNESS: f32 = 1.1;
-pub static CONSISTENCY: f32 = 0.7;
-pub static MOMENTUM: f32 = 1.0;
-pub static JUMP: f32 = 0.7;
-pub static DISCRETIZATION: f32 = 10.0 / 1.5;
-pub static TOROIDAL: bool = true;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+mod cli;
 
 // Main used when only the simulation should run, without any visualization.
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 fn main() {
-    let step = 200;
+    use clap::{CommandFactory, Parser};
+
+    let cli = cli::Cli::parse();
+    let params: Params = cli.params().unwrap_or_else(|e| {
+        cli::Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
+
+    let step = params.steps;
+    let reps = params.reps;
 
-    let dim = (800., 800.);
-    let num_agents = 64000;
-    let state = Flocker::new(dim, num_agents);
-    let _ = simulate_old!(state, step, 1, Info::Normal);
+    let observer = cli.run.observer(params.seed);
+    let mut state = Flocker::new(params);
+    state.observer = observer;
+    #[cfg(headless)]
+    if let Some(checkpoints) = cli.run.checkpoints() {
+        if let Err(e) = checkpoints.run(state, step, reps) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let _ = simulate_old!(state, step, reps, Info::Normal);
 }
 
 // Main used when a visualization feature is applied.
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 fn main() {
-    let dim = (200., 200.);
-    let num_agents = 100;
-    let state = Flocker::new(dim, num_agents);
+    let params = Params {
+        width: 200.,
+        height: 200.,
+        num_agents: 100,
+        ..Default::default()
+    };
+    let dim = params.dim();
+    let state = Flocker::new(params);
     Visualization::default()
         .with_window_dimensions(1000., 700.)
         .with_simulation_dimensions(dim.0, dim.1)
@@ -3,17 +3,23 @@ use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::field_2d::{toroidal_distance, toroidal_transform, Location2D};
 use krabmaga::engine::location::Real2D;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+#[cfg(headless)]
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
 use crate::model::state::Flocker;
-use crate::{AVOIDANCE, COHESION, CONSISTENCY, JUMP, MOMENTUM, RANDOMNESS};
+#[cfg(headless)]
+use examples_common::checkpoint::Real2DDef;
+use examples_common::rng;
 
 #[derive(Clone, Copy)]
+#[cfg_attr(headless, derive(Serialize, Deserialize))]
 pub struct Bird {
     pub id: u32,
+    #[cfg_attr(headless, serde(with = "Real2DDef"))]
     pub loc: Real2D,
+    #[cfg_attr(headless, serde(with = "Real2DDef"))]
     pub last_d: Real2D,
 }
 
@@ -26,6 +32,10 @@ impl Bird {
 impl Agent for Bird {
     fn step(&mut self, state: &mut dyn State) {
         let state = state.as_any().downcast_ref::<Flocker>().unwrap();
+        // a caught bird doesn't fly anymore, it leaves the field and the schedule
+        if state.is_caught(self.id) {
+            return;
+        }
         let vec = state
             .field1
             .get_neighbors_within_relax_distance(self.loc, 10.0);
@@ -100,10 +110,10 @@ impl Agent for Bird {
             };
 
             //randomness
-            let mut rng = rand::thread_rng();
-            let r1: f32 = rng.gen();
+            let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
+            let r1: f32 = rng.random();
             let x_rand = r1 * 2.0 - 1.0;
-            let r2: f32 = rng.gen();
+            let r2: f32 = rng.random();
             let y_rand = r2 * 2.0 - 1.0;
 
             let square = (x_rand * x_rand + y_rand * y_rand).sqrt();
@@ -113,23 +123,43 @@ impl Agent for Bird {
             };
         }
 
+        // escape, away from every predator within `escape_radius`
+        let mut escape = Real2D { x: 0.0, y: 0.0 };
+        let params = &state.params;
+        if params.predators > 0 {
+            for predator in state
+                .predators
+                .get_neighbors_within_relax_distance(self.loc, params.escape_radius)
+            {
+                let dx = toroidal_distance(self.loc.x, predator.loc.x, width);
+                let dy = toroidal_distance(self.loc.y, predator.loc.y, height);
+                let dis = (dx * dx + dy * dy).sqrt();
+                if dis > 0.0 && dis <= params.escape_radius {
+                    escape.x += dx / dis;
+                    escape.y += dy / dis;
+                }
+            }
+        }
+
         let mom = self.last_d;
 
-        let mut dx = COHESION * cohesion.x
-            + AVOIDANCE * avoidance.x
-            + CONSISTENCY * consistency.x
-            + RANDOMNESS * randomness.x
-            + MOMENTUM * mom.x;
-        let mut dy = COHESION * cohesion.y
-            + AVOIDANCE * avoidance.y
-            + CONSISTENCY * consistency.y
-            + RANDOMNESS * randomness.y
-            + MOMENTUM * mom.y;
+        let mut dx = params.cohesion * cohesion.x
+            + params.avoidance * avoidance.x
+            + params.consistency * consistency.x
+            + params.randomness * randomness.x
+            + params.momentum * mom.x
+            + params.escape * escape.x;
+        let mut dy = params.cohesion * cohesion.y
+            + params.avoidance * avoidance.y
+            + params.consistency * consistency.y
+            + params.randomness * randomness.y
+            + params.momentum * mom.y
+            + params.escape * escape.y;
 
         let dis = (dx * dx + dy * dy).sqrt();
         if dis > 0.0 {
-            dx = dx / dis * JUMP;
-            dy = dy / dis * JUMP;
+            dx = dx / dis * params.jump;
+            dy = dy / dis * params.jump;
         }
 
         self.last_d = Real2D { x: dx, y: dy };
@@ -143,6 +173,11 @@ impl Agent for Bird {
             .field1
             .set_object_location(*self, Real2D { x: loc_x, y: loc_y });
     }
+
+    fn is_stopped(&mut self, state: &mut dyn State) -> bool {
+        let state = state.as_any().downcast_ref::<Flocker>().unwrap();
+        state.is_caught(self.id)
+    }
 }
 
 impl Hash for Bird {
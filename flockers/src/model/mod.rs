@@ -1,2 +1,4 @@
 pub mod bird;
+pub mod params;
+pub mod predator;
 pub mod state;
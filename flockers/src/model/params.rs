@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{
+    AVOIDANCE, COHESION, CONSISTENCY, DISCRETIZATION, JUMP, MOMENTUM, RANDOMNESS, TOROIDAL,
+};
+
+/// Run settings and flocking weights of a Flockers simulation.
+/// Defaults match the values hard-coded in the original example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    /// Seed of the random number generators, the same seed replays the same run.
+    pub seed: u64,
+    pub width: f32,
+    pub height: f32,
+    pub num_agents: u32,
+    pub cohesion: f32,
+    pub avoidance: f32,
+    pub randomness: f32,
+    pub consistency: f32,
+    pub momentum: f32,
+    pub jump: f32,
+    pub discretization: f32,
+    pub toroidal: bool,
+    /// Predators hunting the flock, none by default.
+    pub predators: u32,
+    /// Distance a predator flies at each step.
+    pub predator_jump: f32,
+    /// Distance up to which a predator sees the birds it can pursue.
+    pub predator_vision: f32,
+    /// Distance from a predator under which a bird flees it.
+    pub escape_radius: f32,
+    /// Weight of the escape from the predators in the heading of a bird.
+    pub escape: f32,
+    /// Distance from a predator under which a bird can be caught.
+    pub catch_radius: f32,
+    /// Confusion of a predator by the flock: an attack succeeds with probability
+    /// 1 / (1 + confusion * (birds in sight - 1)), so 0 never fails.
+    pub confusion: f32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 200,
+            reps: 1,
+            seed: 0,
+            width: 800.,
+            height: 800.,
+            num_agents: 64000,
+            cohesion: COHESION,
+            avoidance: AVOIDANCE,
+            randomness: RANDOMNESS,
+            consistency: CONSISTENCY,
+            momentum: MOMENTUM,
+            jump: JUMP,
+            discretization: DISCRETIZATION,
+            toroidal: TOROIDAL,
+            predators: 0,
+            predator_jump: 1.0,
+            predator_vision: 30.0,
+            escape_radius: 20.0,
+            escape: 2.0,
+            catch_radius: 1.0,
+            confusion: 0.0,
+        }
+    }
+}
+
+impl Params {
+    /// Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    /// keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    pub fn dim(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Override a parameter by name. Names are matched case-insensitively, so both
+    /// `cohesion` and `COHESION` are accepted.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "steps" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "num_agents" | "agents" => self.num_agents = parse(name, value)?,
+            "cohesion" => self.cohesion = parse(name, value)?,
+            "avoidance" => self.avoidance = parse(name, value)?,
+            "randomness" => self.randomness = parse(name, value)?,
+            "consistency" => self.consistency = parse(name, value)?,
+            "momentum" => self.momentum = parse(name, value)?,
+            "jump" => self.jump = parse(name, value)?,
+            "discretization" => self.discretization = parse(name, value)?,
+            "toroidal" => self.toroidal = parse(name, value)?,
+            "predators" => self.predators = parse(name, value)?,
+            "predator_jump" => self.predator_jump = parse(name, value)?,
+            "predator_vision" => self.predator_vision = parse(name, value)?,
+            "escape_radius" => self.escape_radius = parse(name, value)?,
+            "escape" => self.escape = parse(name, value)?,
+            "catch_radius" => self.catch_radius = parse(name, value)?,
+            "confusion" => self.confusion = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    /// Check that every parameter is in its valid range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width <= 0. || self.height <= 0. {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                self.width, self.height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        if self.discretization <= 0. {
+            return Err(format!(
+                "`discretization` must be positive, found {}",
+                self.discretization
+            ));
+        }
+        if self.jump < 0. {
+            return Err(format!("`jump` must not be negative, found {}", self.jump));
+        }
+        for (name, value) in [
+            ("predator_jump", self.predator_jump),
+            ("predator_vision", self.predator_vision),
+            ("escape_radius", self.escape_radius),
+            ("escape", self.escape),
+            ("catch_radius", self.catch_radius),
+            ("confusion", self.confusion),
+        ] {
+            if !value.is_finite() || value < 0. {
+                return Err(format!(
+                    "`{}` must be a non-negative number, found {}",
+                    name, value
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
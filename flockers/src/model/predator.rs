@@ -0,0 +1,167 @@
+use core::fmt;
+use krabmaga::engine::agent::Agent;
+use krabmaga::engine::fields::field_2d::{toroidal_distance, toroidal_transform, Location2D};
+use krabmaga::engine::location::Real2D;
+use krabmaga::engine::state::State;
+use krabmaga::rand::Rng;
+#[cfg(headless)]
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+
+use crate::model::bird::Bird;
+use crate::model::state::Flocker;
+#[cfg(headless)]
+use examples_common::checkpoint::Real2DDef;
+use examples_common::rng;
+
+/// Radius of the crowd a predator counts around each bird it sees, the neighbourhood a bird
+/// flocks with.
+const CROWD_RADIUS: f32 = 10.0;
+
+/// A predator hunting the flock. It flies to the bird with the most flockmates around it among
+/// those it sees, and catches the nearest bird within `Params::catch_radius` of where it lands.
+#[derive(Clone, Copy)]
+#[cfg_attr(headless, derive(Serialize, Deserialize))]
+pub struct Predator {
+    pub id: u32,
+    #[cfg_attr(headless, serde(with = "Real2DDef"))]
+    pub loc: Real2D,
+    #[cfg_attr(headless, serde(with = "Real2DDef"))]
+    pub last_d: Real2D,
+    /// Birds caught since the start of the run.
+    pub caught: u32,
+}
+
+impl Predator {
+    pub fn new(id: u32, loc: Real2D) -> Self {
+        Predator {
+            id,
+            loc,
+            last_d: Real2D { x: 0., y: 0. },
+            caught: 0,
+        }
+    }
+}
+
+impl Agent for Predator {
+    fn step(&mut self, state: &mut dyn State) {
+        let state = state.as_any().downcast_ref::<Flocker>().unwrap();
+        let params = &state.params;
+        let width = state.dim.0;
+        let height = state.dim.1;
+        let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
+
+        // offset of a bird from a location, the shortest way around the field
+        let offset = |from: Real2D, bird: &Bird| {
+            let dx = toroidal_distance(bird.loc.x, from.x, width);
+            let dy = toroidal_distance(bird.loc.y, from.y, height);
+            (dx, dy, (dx * dx + dy * dy).sqrt())
+        };
+
+        let visible: Vec<Bird> = state
+            .field1
+            .get_neighbors_within_relax_distance(self.loc, params.predator_vision)
+            .into_iter()
+            .filter(|bird| {
+                offset(self.loc, bird).2 <= params.predator_vision && !state.is_caught(bird.id)
+            })
+            .collect();
+
+        // Pursuit of the densest part of the flock in sight: the bird with the most visible
+        // flockmates around it, the nearest one on a tie
+        let target = visible
+            .iter()
+            .map(|bird| {
+                let crowd = visible
+                    .iter()
+                    .filter(|other| offset(bird.loc, other).2 <= CROWD_RADIUS)
+                    .count();
+                (crowd, offset(self.loc, bird).2, bird)
+            })
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)))
+            .map(|(_, _, bird)| *bird);
+
+        let (mut dx, mut dy) = match target {
+            Some(bird) => {
+                let (dx, dy, _) = offset(self.loc, &bird);
+                (dx, dy)
+            }
+            // nothing in sight, the predator wanders off its heading
+            None => {
+                let r1: f32 = rng.random();
+                let r2: f32 = rng.random();
+                (
+                    self.last_d.x + (r1 * 2.0 - 1.0) * params.predator_jump,
+                    self.last_d.y + (r2 * 2.0 - 1.0) * params.predator_jump,
+                )
+            }
+        };
+        let dis = (dx * dx + dy * dy).sqrt();
+        if dis > 0.0 {
+            // a predator doesn't overshoot the bird it flies to
+            let jump = match target {
+                Some(_) => params.predator_jump.min(dis),
+                None => params.predator_jump,
+            };
+            dx = dx / dis * jump;
+            dy = dy / dis * jump;
+        }
+        self.last_d = Real2D { x: dx, y: dy };
+        self.loc = Real2D {
+            x: toroidal_transform(self.loc.x + dx, width),
+            y: toroidal_transform(self.loc.y + dy, height),
+        };
+
+        // Catch of the nearest bird within reach. With `confusion`, every other bird in sight
+        // makes the attack more likely to fail.
+        let prey = visible
+            .iter()
+            .map(|bird| (offset(self.loc, bird).2, bird))
+            .filter(|(distance, _)| *distance <= params.catch_radius)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, bird)| bird.id);
+        if let Some(prey) = prey {
+            let success = params.confusion <= 0.
+                || rng.random::<f32>() < 1. / (1. + params.confusion * (visible.len() - 1) as f32);
+            if success && state.catch(prey) {
+                self.caught += 1;
+            }
+        }
+
+        drop(visible);
+        state.predators.set_object_location(*self, self.loc);
+    }
+}
+
+impl Hash for Predator {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.id.hash(state);
+    }
+}
+
+impl Eq for Predator {}
+
+impl PartialEq for Predator {
+    fn eq(&self, other: &Predator) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Location2D<Real2D> for Predator {
+    fn get_location(self) -> Real2D {
+        self.loc
+    }
+
+    fn set_location(&mut self, loc: Real2D) {
+        self.loc = loc;
+    }
+}
+
+impl fmt::Display for Predator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "predator {} loc {}", self.id, self.loc)
+    }
+}
@@ -1,45 +1,94 @@
 use crate::model::bird::Bird;
-use crate::{DISCRETIZATION, TOROIDAL};
+use crate::model::params::Params;
+use crate::model::predator::Predator;
+#[cfg(headless)]
+use examples_common::checkpoint::{self, Resumable};
+use examples_common::observer::Observer;
+use examples_common::rng;
+#[cfg(headless)]
+use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::field::Field;
 use krabmaga::engine::fields::field_2d::Field2D;
 use krabmaga::engine::location::Real2D;
 use krabmaga::engine::schedule::Schedule;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+#[cfg(headless)]
+use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::collections::HashSet;
+use std::sync::RwLock;
 
 pub struct Flocker {
     pub step: u64,
     pub field1: Field2D<Bird>,
+    pub predators: Field2D<Predator>,
+    /// Birds caught by the predators since the start of the run.
+    pub caught: RwLock<HashSet<u32>>,
     pub initial_flockers: u32,
     pub dim: (f32, f32),
+    pub params: Params,
+    pub observer: Observer,
 }
 
 impl Flocker {
     #[allow(dead_code)]
-    pub fn new(dim: (f32, f32), initial_flockers: u32) -> Self {
+    pub fn new(params: Params) -> Self {
+        let dim = params.dim();
         Flocker {
             step: 0,
-            field1: Field2D::new(dim.0, dim.1, DISCRETIZATION, TOROIDAL),
-            initial_flockers,
+            field1: Field2D::new(dim.0, dim.1, params.discretization, params.toroidal),
+            predators: Field2D::new(dim.0, dim.1, params.discretization, params.toroidal),
+            caught: RwLock::new(HashSet::new()),
+            initial_flockers: params.num_agents,
             dim,
+            params,
+            observer: Observer::new(),
         }
     }
+
+    /// Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    /// `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
+
+    /// Whether a predator caught the bird `id`.
+    pub fn is_caught(&self, id: u32) -> bool {
+        self.params.predators > 0 && self.caught.read().unwrap().contains(&id)
+    }
+
+    /// Mark the bird `id` as caught, false if another predator got it first.
+    pub fn catch(&self, id: u32) -> bool {
+        self.caught.write().unwrap().insert(id)
+    }
 }
 
 impl State for Flocker {
     fn reset(&mut self) {
         self.step = 0;
-        self.field1 = Field2D::new(self.dim.0, self.dim.1, DISCRETIZATION, TOROIDAL);
+        self.field1 = Field2D::new(
+            self.dim.0,
+            self.dim.1,
+            self.params.discretization,
+            self.params.toroidal,
+        );
+        self.predators = Field2D::new(
+            self.dim.0,
+            self.dim.1,
+            self.params.discretization,
+            self.params.toroidal,
+        );
+        self.caught = RwLock::new(HashSet::new());
     }
 
     fn init(&mut self, schedule: &mut Schedule) {
-        let mut rng = rand::thread_rng();
+        self.observer.reset();
+        let mut rng = rng::state_rng(self.seed());
         // Should be moved in the init method on the model exploration changes
         for bird_id in 0..self.initial_flockers {
-            let r1: f32 = rng.gen();
-            let r2: f32 = rng.gen();
+            let r1: f32 = rng.random();
+            let r2: f32 = rng.random();
             let last_d = Real2D { x: 0., y: 0. };
             let loc = Real2D {
                 x: self.dim.0 * r1,
@@ -49,10 +98,56 @@ impl State for Flocker {
             self.field1.set_object_location(bird, loc);
             schedule.schedule_repeating(Box::new(bird), 0., 0);
         }
+        // predators are numbered after the birds and fly before them, so that a bird caught
+        // during a step is gone from the field at the end of it
+        for predator_id in self.initial_flockers..self.initial_flockers + self.params.predators {
+            let r1: f32 = rng.random();
+            let r2: f32 = rng.random();
+            let loc = Real2D {
+                x: self.dim.0 * r1,
+                y: self.dim.1 * r2,
+            };
+            let predator = Predator::new(predator_id, loc);
+            self.predators.set_object_location(predator, loc);
+            schedule.schedule_repeating(Box::new(predator), 0., -1);
+        }
     }
 
-    fn update(&mut self, _step: u64) {
+    fn update(&mut self, step: u64) {
         self.field1.lazy_update();
+        self.predators.lazy_update();
+        self.step = step;
+    }
+
+    fn after_step(&mut self, schedule: &mut Schedule) {
+        // polarization is the norm of the mean heading, 1 when every bird flies the same way
+        let (mut heading_x, mut heading_y, mut speed) = (0., 0., 0.);
+        let agents = schedule.get_all_events();
+        let birds: Vec<&Bird> = agents
+            .iter()
+            .filter_map(|agent| agent.downcast_ref::<Bird>())
+            .collect();
+        for bird in &birds {
+            let d = bird.last_d;
+            let norm = (d.x * d.x + d.y * d.y).sqrt();
+            if norm > 0. {
+                heading_x += d.x / norm;
+                heading_y += d.y / norm;
+            }
+            speed += norm;
+        }
+        let n = birds.len().max(1) as f32;
+        let polarization = (heading_x * heading_x + heading_y * heading_y).sqrt() / n;
+        let mut metrics = vec![
+            ("polarization", polarization as f64),
+            ("mean_speed", (speed / n) as f64),
+        ];
+        if self.params.predators > 0 {
+            metrics.push(("birds", birds.len() as f64));
+            metrics.push(("caught", self.caught.read().unwrap().len() as f64));
+        }
+        // `self.step` is the step just executed, `schedule.step` is a usize with `parallel`
+        self.observer.record(self.step + 1, &metrics);
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -71,3 +166,72 @@ impl State for Flocker {
         self
     }
 }
+
+/// Everything a `Flocker` needs to go on with a run, see `Resumable`.
+#[cfg(headless)]
+#[derive(Serialize, Deserialize)]
+pub struct FlockerSnapshot {
+    pub params: Params,
+    pub step: u64,
+    pub birds: Vec<Bird>,
+    #[serde(default)]
+    pub predators: Vec<Predator>,
+    #[serde(default)]
+    pub caught: Vec<u32>,
+}
+
+/// Any agent of the schedule, saved with its `kind` next to its fields.
+#[cfg(headless)]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FlockerAgent {
+    Bird(Bird),
+    Predator(Predator),
+}
+
+#[cfg(headless)]
+impl Resumable for Flocker {
+    type Snapshot = FlockerSnapshot;
+    type Agent = FlockerAgent;
+
+    fn snapshot(&self) -> FlockerSnapshot {
+        let mut caught: Vec<u32> = self.caught.read().unwrap().iter().copied().collect();
+        caught.sort_unstable();
+        FlockerSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            birds: checkpoint::save_objects(&self.field1),
+            predators: checkpoint::save_objects(&self.predators),
+            caught,
+        }
+    }
+
+    fn restore(snapshot: FlockerSnapshot) -> Result<Flocker, String> {
+        let mut state = Flocker::new(snapshot.params);
+        state.step = snapshot.step;
+        checkpoint::restore_objects(&state.field1, snapshot.birds);
+        checkpoint::restore_objects(&state.predators, snapshot.predators);
+        state.caught = RwLock::new(snapshot.caught.into_iter().collect());
+        state.field1.lazy_update();
+        state.predators.lazy_update();
+        Ok(state)
+    }
+
+    fn save_agent(agent: &dyn Agent) -> FlockerAgent {
+        match agent.downcast_ref::<Bird>() {
+            Some(bird) => FlockerAgent::Bird(*bird),
+            None => FlockerAgent::Predator(*agent.downcast_ref::<Predator>().unwrap()),
+        }
+    }
+
+    fn restore_agent(agent: FlockerAgent) -> Box<dyn Agent> {
+        match agent {
+            FlockerAgent::Bird(bird) => Box::new(bird),
+            FlockerAgent::Predator(predator) => Box::new(predator),
+        }
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
+    }
+}
@@ -1,2 +1,3 @@
 pub mod bird_vis;
+pub mod predator_vis;
 pub mod vis_state;
@@ -1,43 +1,46 @@
-use crate::model::state::WsgState;
-use crate::model::wolf::Wolf;
+use crate::model::predator::Predator;
+use crate::model::state::Flocker;
 use krabmaga::bevy::ecs as bevy_ecs;
 use krabmaga::bevy::prelude::{Component, Quat, Transform, Visibility};
 use krabmaga::engine::agent::Agent;
-use krabmaga::engine::location::Int2D;
 use krabmaga::engine::state::State;
 use krabmaga::visualization::agent_render::{AgentRender, SpriteType};
+use std::f32::consts::PI;
 
 #[derive(Component)]
-pub struct WolfVis {
-    pub id: u32,
+pub struct PredatorVis {
+    pub(crate) id: u32,
 }
 
-impl AgentRender for WolfVis {
+impl AgentRender for PredatorVis {
     fn sprite(&self, _agent: &Box<dyn Agent>, _state: &Box<&dyn State>) -> SpriteType {
-        SpriteType::Emoji(String::from("wolf"))
+        SpriteType::Emoji(String::from("dragon"))
     }
 
     fn location(&self, agent: &Box<dyn Agent>, state: &Box<&dyn State>) -> (f32, f32, f32) {
-        let state = state.as_any().downcast_ref::<WsgState>().unwrap();
-        let agent = agent.downcast_ref::<Wolf>().unwrap();
-        let loc = state.wolves_grid.get_location(*agent);
-        match loc {
-            Some(loc) => (loc.x as f32, loc.y as f32, 1.),
-            None => (agent.loc.x as f32, agent.loc.y as f32, 1.),
+        let state = state.as_any().downcast_ref::<Flocker>().unwrap();
+        let agent = agent.downcast_ref::<Predator>().unwrap();
+        // Drawn above the birds it hunts
+        match state.predators.get_location(*agent) {
+            Some(loc) => (loc.x, loc.y, 1.),
+            None => (agent.loc.x, agent.loc.y, 1.),
         }
     }
 
+    /// Twice the size of a bird, so that predators stand out of the flock
     fn scale(&self, _agent: &Box<dyn Agent>, _state: &Box<&dyn State>) -> (f32, f32) {
-        (0.002, 0.002)
+        (0.2, 0.2)
     }
 
+    /// Like the bird, the dragon emoji points to left by default
     fn rotation(&self, agent: &Box<dyn Agent>, _state: &Box<&dyn State>) -> f32 {
-        let agent = agent.downcast_ref::<Wolf>().unwrap();
-        if let Some(Int2D { x, y }) = agent.last {
-            ((y - agent.loc.y) as f32).atan2((x - agent.loc.x) as f32)
-        } else {
+        let concrete_agent = agent.downcast_ref::<Predator>().unwrap();
+        let rotation = if concrete_agent.last_d.x == 0. || concrete_agent.last_d.y == 0. {
             0.
-        }
+        } else {
+            concrete_agent.last_d.y.atan2(concrete_agent.last_d.x)
+        };
+        rotation + PI
     }
 
     fn update(
@@ -48,8 +51,9 @@ impl AgentRender for WolfVis {
         _visible: &mut Visibility,
     ) {
         let (loc_x, loc_y, z) = self.location(agent, state);
-        let (scale_x, scale_y) = self.scale(agent, state);
         let rotation = self.rotation(agent, state);
+        let (scale_x, scale_y) = self.scale(agent, state);
+
         let translation = &mut transform.translation;
         translation.x = loc_x;
         translation.y = loc_y;
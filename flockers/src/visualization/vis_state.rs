@@ -9,8 +9,10 @@ use krabmaga::visualization::simulation_descriptor::SimulationDescriptor;
 use krabmaga::visualization::visualization_state::VisualizationState;
 
 use crate::model::bird::Bird;
+use crate::model::predator::Predator;
 use crate::model::state::Flocker;
 use crate::visualization::bird_vis::BirdVis;
+use crate::visualization::predator_vis::PredatorVis;
 use krabmaga::bevy::ecs as bevy_ecs;
 use krabmaga::bevy::ecs::system::Resource;
 
@@ -33,12 +35,13 @@ impl VisualizationState<Flocker> for VisState {
         agent: &Box<dyn Agent>,
         _state: &Flocker,
     ) -> Option<Box<dyn AgentRender>> {
-        // In a multi-agent model, you may want to do so
-        // if let Some(bird) = agent.as_any().downcast_ref::<Bird>() {Box::new(BirdVis);} etc...
-        // We only have one agent here so we can directly return the correct AgentRender
-        Some(Box::new(BirdVis {
-            id: agent.downcast_ref::<Bird>().unwrap().id,
-        }))
+        match agent.downcast_ref::<Bird>() {
+            Some(bird) => Some(Box::new(BirdVis { id: bird.id })),
+            None => {
+                let predator = agent.downcast_ref::<Predator>()?;
+                Some(Box::new(PredatorVis { id: predator.id }))
+            }
+        }
     }
 
     fn get_agent(
@@ -48,14 +51,14 @@ impl VisualizationState<Flocker> for VisState {
     ) -> Option<Box<dyn Agent>> {
         // TODO we don't just need the agent associated to the agent render, we need the correct one so that we
         // can access data such as location etc...
+        // Predators are numbered after the birds, so an id is either a bird or a predator
         let state = state.as_any().downcast_ref::<Flocker>().unwrap();
-        match state.field1.get(&Bird::new(
-            agent_render.get_id(),
-            Real2D { x: 0., y: 0. },
-            Real2D { x: 0., y: 0. },
-        )) {
-            Some(matching_agent) => Some(Box::new(*matching_agent)),
-            None => None,
+        let id = agent_render.get_id();
+        let origin = Real2D { x: 0., y: 0. };
+        if let Some(bird) = state.field1.get(&Bird::new(id, origin, origin)) {
+            return Some(Box::new(*bird));
         }
+        let predator = state.predators.get(&Predator::new(id, origin))?;
+        Some(Box::new(*predator))
     }
 }
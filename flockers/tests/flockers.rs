@@ -0,0 +1,212 @@
+use std::path::Path;
+
+#[cfg(headless)]
+use examples_common::checkpoint::{Resumable, ScheduleSnapshot};
+use flockers::model::bird::Bird;
+use flockers::model::params::Params;
+use flockers::model::predator::Predator;
+use flockers::model::state::Flocker;
+#[cfg(headless)]
+use flockers::model::state::{FlockerAgent, FlockerSnapshot};
+use krabmaga::engine::agent::Agent;
+use krabmaga::engine::fields::field::Field;
+use krabmaga::engine::location::Real2D;
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+
+fn small_params(seed: u64) -> Params {
+    Params {
+        width: 200.,
+        height: 200.,
+        num_agents: 500,
+        seed,
+        ..Default::default()
+    }
+}
+
+fn init(params: Params) -> (Flocker, Schedule) {
+    let mut state = Flocker::new(params);
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    (state, schedule)
+}
+
+fn birds(schedule: &Schedule) -> Vec<Bird> {
+    let mut birds: Vec<Bird> = schedule
+        .get_all_events()
+        .iter()
+        .filter_map(|agent| agent.downcast_ref::<Bird>().copied())
+        .collect();
+    birds.sort_by_key(|bird| bird.id);
+    birds
+}
+
+// Location of every scheduled agent, birds and predators, by id
+#[cfg(headless)]
+fn locations(schedule: &Schedule) -> Vec<(u32, u32, u32)> {
+    let mut locations: Vec<(u32, u32, u32)> = schedule
+        .get_all_events()
+        .iter()
+        .map(|agent| match agent.downcast_ref::<Bird>() {
+            Some(bird) => (bird.id, bird.loc.x.to_bits(), bird.loc.y.to_bits()),
+            None => {
+                let predator = agent.downcast_ref::<Predator>().unwrap();
+                (
+                    predator.id,
+                    predator.loc.x.to_bits(),
+                    predator.loc.y.to_bits(),
+                )
+            }
+        })
+        .collect();
+    locations.sort_unstable();
+    locations
+}
+
+#[test]
+fn birds_are_conserved_and_wrap_around_the_field() {
+    let params = small_params(1);
+    let num_agents = params.num_agents as usize;
+    let (mut state, mut schedule) = init(params);
+
+    for _ in 0..50 {
+        schedule.step(&mut state);
+        let birds = birds(&schedule);
+        assert_eq!(birds.len(), num_agents);
+        for bird in birds {
+            assert!(bird.loc.x >= 0. && bird.loc.x < state.dim.0);
+            assert!(bird.loc.y >= 0. && bird.loc.y < state.dim.1);
+        }
+    }
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut state, mut schedule) = init(small_params(seed));
+        for _ in 0..30 {
+            schedule.step(&mut state);
+        }
+        birds(&schedule)
+            .iter()
+            .map(|bird| (bird.id, bird.loc.x.to_bits(), bird.loc.y.to_bits()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+// Checkpoints are only taken by the sequential headless engine
+#[cfg(headless)]
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let (mut state, mut schedule) = init(Params {
+        predators: 3,
+        catch_radius: 2.,
+        ..small_params(4)
+    });
+    for _ in 0..20 {
+        schedule.step(&mut state);
+    }
+    let saved = serde_json::to_string(&(
+        state.snapshot(),
+        ScheduleSnapshot::take(&mut schedule, Flocker::save_agent),
+    ))
+    .unwrap();
+    // every agent is saved with its kind
+    assert!(saved.contains(r#"{"kind":"bird","id":0,"#));
+    assert!(saved.contains(r#"{"kind":"predator","id":500,"#));
+    let (snapshot, events): (FlockerSnapshot, ScheduleSnapshot<FlockerAgent>) =
+        serde_json::from_str(&saved).unwrap();
+    let mut resumed = Flocker::restore(snapshot).unwrap();
+    let mut resumed_schedule = events.restore(Flocker::restore_agent);
+    assert_eq!(locations(&resumed_schedule), locations(&schedule));
+
+    for _ in 0..30 {
+        schedule.step(&mut state);
+        resumed_schedule.step(&mut resumed);
+    }
+    assert_eq!(resumed_schedule.step, schedule.step);
+    assert_eq!(locations(&resumed_schedule), locations(&schedule));
+    assert_eq!(
+        *resumed.caught.read().unwrap(),
+        *state.caught.read().unwrap()
+    );
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.toml");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}
+
+#[test]
+fn predators_catch_birds_and_remove_them_from_the_schedule() {
+    let params = Params {
+        predators: 5,
+        catch_radius: 2.,
+        ..small_params(3)
+    };
+    let num_agents = params.num_agents as usize;
+    let (mut state, mut schedule) = init(params);
+
+    for _ in 0..100 {
+        schedule.step(&mut state);
+        let birds = birds(&schedule);
+        let caught = state.caught.read().unwrap().clone();
+        assert_eq!(birds.len() + caught.len(), num_agents);
+        assert!(birds.iter().all(|bird| !caught.contains(&bird.id)));
+    }
+    let birds = state.observer.series("birds").unwrap();
+    let caught = state.observer.series("caught").unwrap();
+    assert!(*caught.last().unwrap() > 0.);
+    assert_eq!(
+        birds.last().unwrap() + caught.last().unwrap(),
+        num_agents as f64
+    );
+    let catches: u32 = schedule
+        .get_all_events()
+        .iter()
+        .filter_map(|agent| {
+            agent
+                .downcast_ref::<Predator>()
+                .map(|predator| predator.caught)
+        })
+        .sum();
+    assert_eq!(catches as f64, *caught.last().unwrap());
+}
+
+#[test]
+fn birds_flee_a_predator_within_the_escape_radius() {
+    let flee = |escape_radius| {
+        let mut state = Flocker::new(Params {
+            predators: 1,
+            escape_radius,
+            ..small_params(0)
+        });
+        let predator = Predator::new(1, Real2D { x: 55., y: 50. });
+        state.predators.set_object_location(predator, predator.loc);
+        state.predators.lazy_update();
+        let mut bird = Bird::new(0, Real2D { x: 50., y: 50. }, Real2D { x: 0., y: 0. });
+        bird.step(&mut state);
+        bird.loc
+    };
+    let loc = flee(10.);
+    assert!(loc.x < 50. && (loc.y - 50.).abs() < 1e-6);
+    // out of reach, a lone bird with no heading stays put
+    let loc = flee(4.);
+    assert_eq!((loc.x, loc.y), (50., 50.));
+}
+
+#[test]
+fn runs_without_predators_record_no_predator_metrics() {
+    let (mut state, mut schedule) = init(small_params(1));
+    schedule.step(&mut state);
+    assert_eq!(state.observer.names(), ["polarization", "mean_speed"]);
+    assert!(Params {
+        catch_radius: -1.,
+        ..Params::default()
+    }
+    .validate()
+    .is_err());
+}
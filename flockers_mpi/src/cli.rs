@@ -0,0 +1,61 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use examples_common::checkpoint::Checkpoints;
+use examples_common::cli::CheckpointArgs;
+
+/// Headless Flockers simulation, distributed over the processes of an MPI job.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Number of steps of each repetition
+    #[arg(long, default_value_t = 200)]
+    pub steps: u64,
+
+    /// Number of repetitions
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub reps: u64,
+
+    /// Seed of the random number generators, repetition `rep` is seeded with `seed + rep`
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Width of the field
+    #[arg(long, default_value_t = 1131., value_parser = positive)]
+    pub width: f32,
+
+    /// Height of the field
+    #[arg(long, default_value_t = 1131., value_parser = positive)]
+    pub height: f32,
+
+    /// Number of birds
+    #[arg(long, default_value_t = 128000, value_parser = clap::value_parser!(u32).range(1..))]
+    pub agents: u32,
+
+    #[command(flatten)]
+    pub checkpoint: CheckpointArgs,
+}
+
+impl Cli {
+    pub fn dim(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Checkpoints of process `rank`, when `--checkpoint` is given. Each process saves its own
+    /// part of the field, to `<FILE>.<rank>`.
+    pub fn checkpoints(&self, rank: i32) -> Option<Checkpoints> {
+        self.checkpoint.checkpoints().map(|mut checkpoints| {
+            let mut path = checkpoints.path.into_os_string();
+            path.push(format!(".{}", rank));
+            checkpoints.path = PathBuf::from(path);
+            checkpoints
+        })
+    }
+}
+
+fn positive(s: &str) -> Result<f32, String> {
+    match s.trim().parse::<f32>() {
+        Ok(value) if value.is_finite() && value > 0. => Ok(value),
+        _ => Err(format!("expected a positive number, found `{}`", s)),
+    }
+}
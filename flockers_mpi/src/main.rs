@@ -9,6 +9,9 @@ cfg_if! {
 
         mod model;
 
+        #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+        mod cli;
+
         // No visualization specific imports
         #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
         use {
@@ -46,13 +49,21 @@ cfg_if! {
 
         #[cfg(any(feature = "distributed_mpi"))]
         fn main() {
-            let step = 200;
-
-            let dim = (1131., 1131.);
-            let num_agents = 128000;
+            use clap::Parser;
 
-            let state = Flocker::new(dim, num_agents);
-            let _ = simulate_mpi!(state, step, 1, Info::Normal);
+            let cli = cli::Cli::parse();
+            let state = Flocker::new(cli.dim(), cli.agents, cli.seed);
+            match cli.checkpoints(UNIVERSE.world().rank()) {
+                Some(checkpoints) => {
+                    if let Err(e) = checkpoints.run(state, cli.steps, cli.reps) {
+                        eprintln!("error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                None => {
+                    let _ = simulate_mpi!(state, cli.steps, cli.reps, Info::Normal);
+                }
+            }
         }
 
         // Main used when a visualization feature is applied.
@@ -60,7 +71,7 @@ cfg_if! {
         fn main() {
             let dim = (200., 200.);
             let num_agents = 100;
-            let state = Flocker::new(dim, num_agents);
+            let state = Flocker::new(dim, num_agents, 0);
             Visualization::default()
                 .with_window_dimensions(1000., 700.)
                 .with_simulation_dimensions(dim.0 as f32, dim.1 as f32)
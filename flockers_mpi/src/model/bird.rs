@@ -12,19 +12,23 @@ cfg_if! {
         use krabmaga::engine::fields::kdtree_mpi::{toroidal_distance, toroidal_transform, Location2D};
         use krabmaga::engine::location::Real2D;
         use krabmaga::engine::state::State;
-        use krabmaga::rand;
         use krabmaga::rand::Rng;
         use krabmaga::UNIVERSE;
         use mpi::topology::Communicator;
+        use serde::{Deserialize, Serialize};
         use std::hash::{Hash, Hasher};
 
         use crate::model::state::Flocker;
         use crate::{AVOIDANCE, COHESION, CONSISTENCY, JUMP, MOMENTUM, RANDOMNESS};
+        use examples_common::checkpoint::Real2DDef;
+        use examples_common::rng;
 
-        #[derive(Clone, Copy)]
+        #[derive(Clone, Copy, Serialize, Deserialize)]
         pub struct Bird {
             pub id: u32,
+            #[serde(with = "Real2DDef")]
             pub loc: Real2D,
+            #[serde(with = "Real2DDef")]
             pub last_d: Real2D,
         }
 
@@ -143,10 +147,10 @@ cfg_if! {
                     };
 
                     //randomness
-                    let mut rng = rand::thread_rng();
-                    let r1: f32 = rng.gen();
+                    let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
+                    let r1: f32 = rng.random();
                     let x_rand = r1 * 2.0 - 1.0;
-                    let r2: f32 = rng.gen();
+                    let r2: f32 = rng.random();
                     let y_rand = r2 * 2.0 - 1.0;
 
                     let square = (x_rand * x_rand + y_rand * y_rand).sqrt();
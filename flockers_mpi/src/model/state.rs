@@ -5,17 +5,22 @@ cfg_if! {
     {
         use crate::model::bird::Bird;
         use crate::DISCRETIZATION;
+        use examples_common::checkpoint::Resumable;
+        use examples_common::observer::Observer;
+        use examples_common::rng;
+        use krabmaga::engine::agent::Agent;
         use krabmaga::engine::fields::field::Field;
         use krabmaga::engine::fields::kdtree_mpi::Kdtree;
         use krabmaga::engine::location::Real2D;
         use krabmaga::engine::schedule::Schedule;
         use krabmaga::engine::state::State;
+        use krabmaga::mpi::collective::CommunicatorCollectives;
         use krabmaga::mpi::point_to_point::Destination;
         use krabmaga::mpi::point_to_point::Source;
         use krabmaga::mpi::topology::Communicator;
-        use krabmaga::rand;
         use krabmaga::rand::Rng;
         use krabmaga::UNIVERSE;
+        use serde::{Deserialize, Serialize};
         use std::any::Any;
 
         pub struct Flocker {
@@ -23,18 +28,32 @@ cfg_if! {
             pub field1: Kdtree<Bird>,
             pub initial_flockers: u32,
             pub dim: (f32, f32),
+            /// Seed of the run: the agents are placed from it, and each bird draws from its own
+            /// stream of it, so the run is the same whatever the number of processes.
+            pub seed: u64,
+            /// Counts the repetitions, each one seeded apart, and carries them across checkpoints.
+            /// No metric is recorded yet.
+            pub observer: Observer,
         }
 
         impl Flocker {
             #[allow(dead_code)]
-            pub fn new(dim: (f32, f32), initial_flockers: u32) -> Self {
+            pub fn new(dim: (f32, f32), initial_flockers: u32, seed: u64) -> Self {
                 Flocker {
                     step: 0,
                     field1: Kdtree::create_tree(0, 0.0, 0.0, dim.0, dim.1, DISCRETIZATION, 25.),
                     initial_flockers,
                     dim,
+                    seed,
+                    observer: Observer::new(),
                 }
             }
+
+            /// Seed of the repetition being run, the seed of the run plus the repetition, see
+            /// `Observer::seed`. Every random draw of the run derives from it.
+            pub fn seed(&self) -> u64 {
+                self.observer.seed(self.seed)
+            }
         }
 
         impl State for Flocker {
@@ -50,7 +69,8 @@ cfg_if! {
             ///The other processes, instead, will be waiting to receive the agents from process 0.
             fn init(&mut self, schedule: &mut Schedule) {
                 let world = UNIVERSE.world();
-                let mut rng = rand::thread_rng();
+                self.observer.reset();
+                let mut rng = rng::state_rng(self.seed());
 
                 //Process 0 creates the agents
                 if world.rank() == 0 {
@@ -63,8 +83,8 @@ cfg_if! {
 
                     //For each initial agent...
                     for bird_id in 0..self.initial_flockers {
-                        let r1: f32 = rng.gen();
-                        let r2: f32 = rng.gen();
+                        let r1: f32 = rng.random();
+                        let r2: f32 = rng.random();
                         let last_d = Real2D { x: 0., y: 0. };
                         let loc = Real2D {
                             x: self.dim.0 * r1,
@@ -102,8 +122,9 @@ cfg_if! {
                 }
             }
 
-            fn update(&mut self, _step: u64) {
+            fn update(&mut self, step: u64) {
                 self.field1.lazy_update();
+                self.step = step;
             }
 
             ///The before_step function takes action before the start of the step.
@@ -111,6 +132,10 @@ cfg_if! {
             ///These agent will be inserted into the field in 'read' mode.
             ///This will make them visible to the other agents in the field in order to calculate their neighborhood.
             fn before_step(&mut self, _: &mut Schedule) {
+                //The exchange below uses ready sends, so the neighbors must have posted their receives:
+                //wait until every process is done with the previous step, its checkpoint included.
+                UNIVERSE.world().barrier();
+
                 let dummy = Bird {
                     id: 0,
                     loc: Real2D { x: 0., y: 0. },
@@ -190,5 +215,94 @@ cfg_if! {
                 self
             }
         }
+
+        /// Part of the run held by a process: each process saves the birds of its own subtree to
+        /// its own checkpoint file, and resumes from it on the same number of processes.
+        #[derive(Serialize, Deserialize)]
+        pub struct FlockerSnapshot {
+            pub rank: i32,
+            pub processes: i32,
+            pub step: u64,
+            pub dim: (f32, f32),
+            pub initial_flockers: u32,
+            pub seed: u64,
+            /// Birds of the subtree, bag by bag.
+            pub birds: Vec<Bird>,
+            /// Birds of the halo regions, sent to the neighbor processes at the next step.
+            pub prec_neighbors: Vec<Vec<Bird>>,
+            /// Schedule id of each bird of the subtree.
+            pub scheduled: Vec<(u32, u32)>,
+        }
+
+        impl Resumable for Flocker {
+            type Snapshot = FlockerSnapshot;
+            type Agent = Bird;
+
+            ///Snapshots are taken between two steps, once `update` has swapped the buffers of the field:
+            ///the write buffer is empty, and the read buffer only holds the birds of this process.
+            fn snapshot(&self) -> FlockerSnapshot {
+                let world = UNIVERSE.world();
+                let mut birds = Vec::new();
+                for bags in &self.field1.locs {
+                    for bag in bags.borrow().iter() {
+                        birds.extend(bag.iter().copied());
+                    }
+                }
+                let mut scheduled: Vec<(u32, u32)> = self
+                    .field1
+                    .scheduled_agent
+                    .iter()
+                    .map(|(&bird, &id)| (bird, id))
+                    .collect();
+                scheduled.sort_unstable();
+                FlockerSnapshot {
+                    rank: world.rank(),
+                    processes: world.size(),
+                    step: self.step,
+                    dim: self.dim,
+                    initial_flockers: self.initial_flockers,
+                    seed: self.seed,
+                    birds,
+                    prec_neighbors: self.field1.prec_neighbors.clone(),
+                    scheduled,
+                }
+            }
+
+            ///Birds are inserted bag by bag, so each bag keeps its order, and the halo regions are
+            ///restored as saved, so the neighbors receive the birds in the same order as well.
+            fn restore(snapshot: FlockerSnapshot) -> Result<Flocker, String> {
+                let world = UNIVERSE.world();
+                if snapshot.rank != world.rank() || snapshot.processes != world.size() {
+                    return Err(format!(
+                        "the checkpoint was taken by process {} of {}, not by process {} of {}",
+                        snapshot.rank,
+                        snapshot.processes,
+                        world.rank(),
+                        world.size()
+                    ));
+                }
+                let mut state = Flocker::new(snapshot.dim, snapshot.initial_flockers, snapshot.seed);
+                state.step = snapshot.step;
+                for bird in snapshot.birds {
+                    state.field1.insert(bird, bird.loc);
+                }
+                state.field1.lazy_update();
+                state.field1.prec_neighbors = snapshot.prec_neighbors;
+                state.field1.scheduled_agent = snapshot.scheduled.into_iter().collect();
+                Ok(state)
+            }
+
+            fn save_agent(agent: &dyn Agent) -> Bird {
+                *agent.downcast_ref::<Bird>().unwrap()
+            }
+
+            fn restore_agent(bird: Bird) -> Box<dyn Agent> {
+                Box::new(bird)
+            }
+
+            fn observer(&mut self) -> &mut Observer {
+                &mut self.observer
+            }
+        }
     }
 }
@@ -0,0 +1,9 @@
+pub mod model;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+/* pub static STEP: u64 = 10;
+pub static WIDTH: i32 = 6400;
+pub static HEIGHT: i32 = 6400;
+pub const DENSITY: f64 = 0.7; */
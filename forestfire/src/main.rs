@@ -4,44 +4,82 @@ use krabmaga::simulate;
 // Visualization specific imports
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 use {
-    crate::visualization::forest_vis::ForestVis, krabmaga::bevy::app::FixedUpdate,
-    krabmaga::bevy::prelude::Color, krabmaga::engine::fields::dense_object_grid_2d::DenseGrid2D,
+    forestfire::model::forest::Tree, forestfire::visualization::forest_vis::ForestVis,
+    krabmaga::bevy::app::FixedUpdate, krabmaga::bevy::prelude::Color,
+    krabmaga::engine::fields::dense_object_grid_2d::DenseGrid2D,
     krabmaga::visualization::fields::object_grid_2d::RenderObjectGrid2D,
     krabmaga::visualization::visualization::Visualization,
 };
 
 // Global imports (needed for the simulation to run)
-use crate::model::forest::Forest;
-use crate::model::forest::Tree;
+use forestfire::model::forest::Forest;
+use forestfire::model::params::Params;
 
-mod model;
-
-/* pub static STEP: u64 = 10;
-pub static WIDTH: i32 = 6400;
-pub static HEIGHT: i32 = 6400;
-pub const DENSITY: f64 = 0.7; */
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+mod cli;
 
 // Main used when only the simulation should run, without any visualization.
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 fn main() {
-    let step = 100;
-    let dim: (i32, i32) = (200, 200);
-    let density: f64 = 0.7;
-    let forest = Forest::new(dim, density);
-    let _ = simulate!(forest, step, 10);
-}
+    use clap::{CommandFactory, Parser};
 
-#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-mod visualization;
+    let cli = cli::Cli::parse();
+    let params: Params = cli.params().unwrap_or_else(|e| {
+        cli::Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
+
+    let step = params.steps;
+    let reps = params.reps;
+
+    if let Some(sweep) = cli.sweep.sweep() {
+        let path = cli.run.sweep_path();
+        let result = sweep.run::<Forest>(&params).and_then(|summaries| {
+            sweep
+                .write(&path, &summaries)
+                .map_err(|e| format!("cannot write `{}`: {}", path.display(), e))?;
+            println!(
+                "{} runs summarized in `{}`",
+                summaries.len(),
+                path.display()
+            );
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let observer = cli.run.observer(params.seed);
+    let mut forest = Forest::new(params);
+    forest.observer = observer;
+    match cli.run.checkpoints() {
+        Some(checkpoints) => {
+            if let Err(e) = checkpoints.run(forest, step, reps) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            simulate!(forest, step, reps);
+        }
+    }
+}
 
 // Main used when a visualization feature is applied.
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 fn main() {
     // Initialize the simulation and its visualization here.
-    let dim: (i32, i32) = (50, 50);
-    let density: f64 = 0.7;
+    let params = Params {
+        width: 50,
+        height: 50,
+        ..Default::default()
+    };
 
-    let state = Forest::new(dim, density);
+    let state = Forest::new(params);
     let mut app = Visualization::default()
         .with_simulation_dimensions(state.dim.0 as f32, state.dim.1 as f32)
         .with_window_dimensions(1000., 720.)
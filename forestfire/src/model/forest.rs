@@ -1,17 +1,23 @@
+use crate::model::params::Params;
 use crate::model::spread::Spread;
 use core::fmt;
+use examples_common::checkpoint::{self, Cell, Resumable};
+use examples_common::observer::{Observer, TimeSeries};
+use examples_common::rng;
+use examples_common::sweep::Sweepable;
+use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::dense_object_grid_2d::DenseGrid2D;
 use krabmaga::engine::fields::field::Field;
 use krabmaga::engine::location::Int2D;
 use krabmaga::engine::schedule::Schedule;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::hash::Hash;
 use std::hash::Hasher;
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Status {
     Green,   // tree alive - the fire can spread here
     Burning, // burning tree - the fire is here
@@ -28,7 +34,7 @@ impl fmt::Display for Status {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Tree {
     pub id: i32,
     pub status: Status,
@@ -67,14 +73,15 @@ pub struct Forest {
     pub burning: i32,
     pub green: i32,
     pub dim: (i32, i32),
-    pub density: f64,
+    pub params: Params,
+    pub observer: Observer,
 }
 
 impl Forest {
-    pub fn new(dim: (i32, i32), density: f64) -> Forest {
+    pub fn new(params: Params) -> Forest {
+        let dim = params.dim();
         Forest {
             step: 0,
-            density,
             dim,
             field: DenseGrid2D::new(dim.0, dim.1),
             before_burned: 0,
@@ -83,9 +90,17 @@ impl Forest {
             burned: 0,
             burning: 0,
             green: 0,
+            params,
+            observer: Observer::new(),
         }
     }
 
+    // Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    // `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
+
     pub fn as_state_mut(&mut self) -> &mut dyn State {
         self
     }
@@ -108,13 +123,14 @@ impl State for Forest {
 
     fn init(&mut self, schedule: &mut Schedule) {
         self.step = 0;
+        self.observer.reset();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::state_rng(self.seed());
         let mut ids = 0;
         // generate the trees to populate the forest
         for i in 0..self.dim.0 {
             for j in 0..self.dim.1 {
-                if rng.gen_bool(self.density) {
+                if rng.random_bool(self.params.density) {
                     let mut status_tree = Status::Green;
                     if i == 0 {
                         // Set the trees at the left edge on fire
@@ -152,8 +168,32 @@ impl State for Forest {
         self
     }
 
-    fn after_step(&mut self, _schedule: &mut Schedule) {
+    fn after_step(&mut self, schedule: &mut Schedule) {
         self.step += 1;
+
+        // count the trees written by the spreader during this step
+        self.green = 0;
+        self.burning = 0;
+        self.burned = 0;
+        for i in 0..self.dim.0 {
+            for j in 0..self.dim.1 {
+                if let Some(trees) = self.field.get_objects_unbuffered(&Int2D { x: i, y: j }) {
+                    match trees[0].status {
+                        Status::Green => self.green += 1,
+                        Status::Burning => self.burning += 1,
+                        Status::Burned => self.burned += 1,
+                    }
+                }
+            }
+        }
+        self.observer.record(
+            schedule.step + 1,
+            &[
+                ("green", self.green as f64),
+                ("burning", self.burning as f64),
+                ("burned", self.burned as f64),
+            ],
+        );
     }
 
     fn end_condition(&mut self, _schedule: &mut Schedule) -> bool {
@@ -200,3 +240,65 @@ impl State for Forest {
         false
     }
 }
+
+/// Everything a `Forest` needs to go on with a run, see `Resumable`.
+#[derive(Serialize, Deserialize)]
+pub struct ForestSnapshot {
+    pub params: Params,
+    pub step: u64,
+    pub trees: Vec<Cell<Tree>>,
+    pub green: i32,
+    pub burning: i32,
+    pub burned: i32,
+}
+
+impl Resumable for Forest {
+    type Snapshot = ForestSnapshot;
+    type Agent = Spread;
+
+    fn snapshot(&self) -> ForestSnapshot {
+        ForestSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            trees: checkpoint::save_grid(&self.field),
+            green: self.green,
+            burning: self.burning,
+            burned: self.burned,
+        }
+    }
+
+    fn restore(snapshot: ForestSnapshot) -> Result<Forest, String> {
+        let mut forest = Forest::new(snapshot.params);
+        forest.step = snapshot.step;
+        checkpoint::restore_grid(&forest.field, snapshot.trees);
+        forest.field.lazy_update();
+        forest.green = snapshot.green;
+        forest.burning = snapshot.burning;
+        forest.burned = snapshot.burned;
+        Ok(forest)
+    }
+
+    fn save_agent(agent: &dyn Agent) -> Spread {
+        *agent.downcast_ref::<Spread>().unwrap()
+    }
+
+    fn restore_agent(spread: Spread) -> Box<dyn Agent> {
+        Box::new(spread)
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
+    }
+}
+
+impl Sweepable for Forest {
+    type Params = Params;
+
+    fn build(params: Params) -> Result<Self, String> {
+        Ok(Forest::new(params))
+    }
+
+    fn metrics(&self) -> &TimeSeries {
+        self.observer.time_series()
+    }
+}
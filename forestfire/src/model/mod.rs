@@ -1,2 +1,3 @@
 pub mod forest;
+pub mod params;
 pub mod spread;
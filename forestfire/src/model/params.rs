@@ -0,0 +1,125 @@
+use examples_common::sweep::SweepParams;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Run settings of a Forest Fire simulation.
+/// Defaults match the values hard-coded in the original example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    /// Seed of the random number generators, the same seed replays the same run.
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    /// Probability that a cell of the field holds a tree.
+    pub density: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 100,
+            reps: 10,
+            seed: 0,
+            width: 200,
+            height: 200,
+            density: 0.7,
+        }
+    }
+}
+
+impl Params {
+    /// Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    /// keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    pub fn dim(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Override a parameter by name. Names are matched case-insensitively, so both
+    /// `density` and `DENSITY` are accepted.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "steps" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "density" => self.density = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    /// Check that every parameter is in its valid range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                self.width, self.height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        probability("density", self.density)
+    }
+}
+
+impl SweepParams for Params {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    fn reps(&self) -> u64 {
+        self.reps
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        Params::set(self, name, value)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        Params::validate(self)
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
+
+fn probability(name: &str, value: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{}` must be in [0, 1], found {}", name, value))
+    }
+}
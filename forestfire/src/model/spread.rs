@@ -1,15 +1,16 @@
 use crate::model::forest::Forest;
 use crate::model::forest::Status;
-use crate::Tree;
+use crate::model::forest::Tree;
 use core::fmt;
 use krabmaga::engine::agent::Agent;
 use krabmaga::engine::location::Int2D;
 use krabmaga::engine::schedule::Schedule;
 use krabmaga::engine::state::State;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::hash::{Hash, Hasher};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Spread {
     pub id: u32,
 }
@@ -1,5 +1,5 @@
 use crate::model::forest::Forest;
-use crate::Tree;
+use crate::model::forest::Tree;
 use krabmaga::bevy::ecs as bevy_ecs;
 use krabmaga::bevy::ecs::system::Resource;
 use krabmaga::bevy::prelude::Commands;
@@ -0,0 +1,178 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use examples_common::checkpoint::{Resumable, ScheduleSnapshot};
+use examples_common::sweep::{Design, Sweep};
+use forestfire::model::forest::{Forest, ForestSnapshot, Status};
+use forestfire::model::params::Params;
+use forestfire::model::spread::Spread;
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+
+fn small_params(seed: u64) -> Params {
+    Params {
+        width: 50,
+        height: 50,
+        seed,
+        ..Default::default()
+    }
+}
+
+fn init(params: Params) -> (Forest, Schedule) {
+    let mut forest = Forest::new(params);
+    let mut schedule = Schedule::new();
+    forest.init(&mut schedule);
+    (forest, schedule)
+}
+
+// Trees only move forward: Green, then Burning, then Burned
+fn stage(status: Status) -> u8 {
+    match status {
+        Status::Green => 0,
+        Status::Burning => 1,
+        Status::Burned => 2,
+    }
+}
+
+// Stage of every tree on the field, by id
+fn trees(forest: &Forest) -> HashMap<i32, u8> {
+    let trees = RefCell::new(HashMap::new());
+    forest.field.iter_objects(|_, tree| {
+        trees.borrow_mut().insert(tree.id, stage(tree.status));
+    });
+    trees.into_inner()
+}
+
+#[test]
+fn trees_never_turn_back_to_green() {
+    let (mut forest, mut schedule) = init(small_params(1));
+    schedule.step(&mut forest);
+    let mut previous = trees(&forest);
+    assert!(previous.values().any(|&stage| stage > 0));
+
+    for _ in 0..60 {
+        schedule.step(&mut forest);
+        let current = trees(&forest);
+        assert_eq!(current.len(), previous.len());
+        for (id, stage) in current.iter() {
+            assert!(*stage >= previous[id], "tree {} went back", id);
+        }
+        previous = current;
+    }
+}
+
+#[test]
+fn burning_trees_burn_out_in_one_step() {
+    let (mut forest, mut schedule) = init(small_params(2));
+    schedule.step(&mut forest);
+    let mut previous = trees(&forest);
+
+    for _ in 0..60 {
+        schedule.step(&mut forest);
+        let current = trees(&forest);
+        for (id, stage) in previous.iter() {
+            if *stage == 1 {
+                assert_eq!(current[id], 2, "tree {} is still burning", id);
+            }
+        }
+        previous = current;
+    }
+}
+
+#[test]
+fn observer_counts_every_tree() {
+    let (mut forest, mut schedule) = init(small_params(3));
+    for _ in 0..30 {
+        schedule.step(&mut forest);
+    }
+    let trees = trees(&forest).len() as f64;
+    let observer = &forest.observer;
+    assert_eq!(observer.steps(), (1..=30).collect::<Vec<u64>>());
+    let green = observer.series("green").unwrap();
+    let burning = observer.series("burning").unwrap();
+    let burned = observer.series("burned").unwrap();
+    for step in 0..30 {
+        assert_eq!(green[step] + burning[step] + burned[step], trees);
+    }
+    assert!(burned[29] > 0.);
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut forest, mut schedule) = init(small_params(seed));
+        for _ in 0..20 {
+            schedule.step(&mut forest);
+        }
+        trees(&forest)
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let (mut forest, mut schedule) = init(small_params(4));
+    for _ in 0..10 {
+        schedule.step(&mut forest);
+    }
+    let saved = serde_json::to_string(&(
+        forest.snapshot(),
+        ScheduleSnapshot::take(&mut schedule, Forest::save_agent),
+    ))
+    .unwrap();
+    let (snapshot, events): (ForestSnapshot, ScheduleSnapshot<Spread>) =
+        serde_json::from_str(&saved).unwrap();
+    let mut resumed = Forest::restore(snapshot).unwrap();
+    let mut resumed_schedule = events.restore(Forest::restore_agent);
+    assert_eq!(trees(&resumed), trees(&forest));
+
+    for _ in 0..20 {
+        schedule.step(&mut forest);
+        resumed_schedule.step(&mut resumed);
+    }
+    assert_eq!(resumed_schedule.step, schedule.step);
+    assert_eq!(trees(&resumed), trees(&forest));
+    assert_eq!(resumed.burned, forest.burned);
+}
+
+#[test]
+fn density_sweep_writes_one_row_per_run() {
+    let sweep = Sweep {
+        factors: vec!["density=0.2:0.8".parse().unwrap()],
+        design: Design::Random,
+        points: 4,
+        threads: 2,
+    };
+    let params = Params {
+        steps: 30,
+        reps: 3,
+        ..small_params(0)
+    };
+    let summaries = sweep.run::<Forest>(&params).unwrap();
+    assert_eq!(summaries.len(), 12);
+    for summary in &summaries {
+        let density: f64 = summary.values[0].parse().unwrap();
+        assert!((0.2..=0.8).contains(&density));
+        assert!(summary.steps <= 30);
+        for [last, mean, min, max] in &summary.stats {
+            assert!(min <= mean && mean <= max && min <= last && last <= max);
+        }
+    }
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("density_sweep.csv");
+    sweep.write(&path, &summaries).unwrap();
+    let csv = fs::read_to_string(path).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 13);
+    assert!(lines[0].starts_with("point,replicate,seed,density,steps,"));
+    assert!(lines[12].starts_with("3,2,2,"));
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.toml");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}
@@ -1,12 +1,9 @@
-#[cfg(any(feature = "bayesian"))]
+#[cfg(feature = "bayesian")]
 use krabmaga::{engine::schedule::Schedule, engine::state::State, explore::bayesian::*};
 use krabmaga::{rand::prelude::*, *};
 
 // Global imports (needed for the simulation to run)
-use crate::model::forest::Forest;
-use crate::model::forest::Tree;
-
-mod model;
+use forestfire_bayesian::model::forest::Forest;
 
 pub const ITERATIONS: usize = 10;
 pub const INIT_ELEMENTS: usize = 4;
@@ -21,36 +18,36 @@ lazy_static! {
     pub static ref RNG: Mutex<StdRng> = Mutex::new(StdRng::seed_from_u64(10));
 }
 
-#[cfg(not(any(feature = "bayesian")))]
+#[cfg(not(feature = "bayesian"))]
 fn main() {
     let density = 100.;
     let dim: (i32, i32) = (200, 200);
-    let mut _forest = Forest::new(dim, density);
+    let mut _forest = Forest::new(dim, density, 0);
     println!("No bayesian feature enabled");
 }
 
-#[cfg(any(feature = "bayesian"))]
+#[cfg(feature = "bayesian")]
 fn main() {
     let (x, y) = bayesian_search!(init_population, objective, get_points, ITERATIONS);
 
     println!("---\nFinal res: Point {:?}, val {y}", x);
 }
 
-#[cfg(any(feature = "bayesian"))]
+#[cfg(feature = "bayesian")]
 fn init_population() -> Vec<Vec<f64>> {
     let mut x_init: Vec<Vec<f64>> = Vec::with_capacity(INIT_ELEMENTS);
 
     let mut rng = RNG.lock().unwrap();
 
     for _ in 0..INIT_ELEMENTS {
-        let density = rng.gen_range(0.01..=1.0_f64); // forest density
+        let density = rng.random_range(0.01..=1.0_f64); // forest density
         x_init.push(vec![density]);
     }
 
     x_init
 }
 
-#[cfg(any(feature = "bayesian"))]
+#[cfg(feature = "bayesian")]
 fn objective(x: &[f64]) -> f64 {
     let density = x[0];
     let n_step = 500;
@@ -58,9 +55,11 @@ fn objective(x: &[f64]) -> f64 {
     let dim: (i32, i32) = (200, 200);
     let mut steps_tot = 0;
 
-    let mut forest = Forest::new(dim, density);
+    let mut forest = Forest::new(dim, density, 0);
 
-    for _ in 0..reps {
+    for rep in 0..reps {
+        // A different seed for each repetition, the same ones for every evaluated point
+        forest.seed = rep;
         let mut schedule = Schedule::new();
         forest.init(&mut schedule);
         for _ in 0..n_step {
@@ -78,14 +77,13 @@ fn objective(x: &[f64]) -> f64 {
     steps_tot as f64 / reps as f64
 }
 
-#[cfg(any(feature = "bayesian"))]
+#[cfg(feature = "bayesian")]
 fn get_points(_x: &[Vec<f64>]) -> Vec<Vec<f64>> {
     let mut rng = RNG.lock().unwrap();
 
     let trial_x: Vec<Vec<f64>> = (0..BATCH_SIZE)
-        .into_iter()
         .map(|_| {
-            let density = rng.gen_range(0.1..=1.0_f64); // density
+            let density = rng.random_range(0.1..=1.0_f64); // density
             vec![density]
         })
         .collect();
@@ -5,8 +5,8 @@ use krabmaga::engine::fields::field::Field;
 use krabmaga::engine::location::Int2D;
 use krabmaga::engine::schedule::Schedule;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
-use krabmaga::rand::Rng;
+use krabmaga::rand::rngs::StdRng;
+use krabmaga::rand::{Rng, SeedableRng};
 use std::any::Any;
 use std::hash::Hash;
 use std::hash::Hasher;
@@ -68,13 +68,15 @@ pub struct Forest {
     pub green: i32,
     pub dim: (i32, i32),
     pub density: f64,
+    pub seed: u64,
 }
 
 impl Forest {
-    pub fn new(dim: (i32, i32), density: f64) -> Forest {
+    pub fn new(dim: (i32, i32), density: f64, seed: u64) -> Forest {
         Forest {
             step: 0,
             density,
+            seed,
             dim,
             field: DenseGrid2D::new(dim.0, dim.1),
             before_burned: 0,
@@ -100,12 +102,12 @@ impl State for Forest {
     fn init(&mut self, schedule: &mut Schedule) {
         self.step = 0;
 
-        let mut rng = rand::thread_rng();
+        let mut rng = StdRng::seed_from_u64(self.seed);
         let mut ids = 0;
         // generate the trees to populate the forest
         for i in 0..self.dim.0 {
             for j in 0..self.dim.1 {
-                if rng.gen_bool(self.density) {
+                if rng.random_bool(self.density) {
                     let mut status_tree = Status::Green;
                     if i == 0 {
                         // Set the trees at the left edge on fire
@@ -1,6 +1,6 @@
 use crate::model::forest::Forest;
 use crate::model::forest::Status;
-use crate::Tree;
+use crate::model::forest::Tree;
 use core::fmt;
 use krabmaga::engine::agent::Agent;
 use krabmaga::engine::location::Int2D;
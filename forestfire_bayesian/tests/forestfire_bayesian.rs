@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use forestfire_bayesian::model::forest::{Forest, Status};
+use krabmaga::engine::location::Int2D;
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+
+fn init(density: f64, seed: u64) -> (Forest, Schedule) {
+    let mut forest = Forest::new((50, 50), density, seed);
+    let mut schedule = Schedule::new();
+    forest.init(&mut schedule);
+    (forest, schedule)
+}
+
+// Trees only move forward: Green, then Burning, then Burned
+fn stage(status: Status) -> u8 {
+    match status {
+        Status::Green => 0,
+        Status::Burning => 1,
+        Status::Burned => 2,
+    }
+}
+
+// Stage of every tree on the field, by id
+fn trees(forest: &Forest) -> HashMap<i32, u8> {
+    let trees = RefCell::new(HashMap::new());
+    forest.field.iter_objects(|_, tree| {
+        trees.borrow_mut().insert(tree.id, stage(tree.status));
+    });
+    trees.into_inner()
+}
+
+#[test]
+fn fire_spreads_from_the_left_edge_and_never_goes_back() {
+    let (mut forest, mut schedule) = init(0.7, 1);
+    schedule.step(&mut forest);
+    for y in 0..forest.dim.1 {
+        if let Some(trees) = forest.field.get_objects(&Int2D { x: 0, y }) {
+            assert!(trees[0].status != Status::Green);
+        }
+    }
+
+    let mut previous = trees(&forest);
+    for _ in 0..60 {
+        schedule.step(&mut forest);
+        let current = trees(&forest);
+        assert_eq!(current.len(), previous.len());
+        for (id, stage) in current.iter() {
+            assert!(*stage >= previous[id], "tree {} went back", id);
+        }
+        previous = current;
+    }
+}
+
+#[test]
+fn objective_runs_replay_with_the_same_seed() {
+    // what the objective of the optimization measures: the steps until the fire stops
+    let run = |density, seed| {
+        let (mut forest, mut schedule) = init(density, seed);
+        for _ in 0..500 {
+            schedule.step(&mut forest);
+            if forest.end_condition(&mut schedule) {
+                break;
+            }
+        }
+        (forest.step, trees(&forest))
+    };
+    let (steps, trees) = run(0.7, 3);
+    assert!(steps < 500, "the fire never stopped");
+    assert!(trees.values().all(|&stage| stage != 1));
+    assert_eq!(run(0.7, 3), (steps, trees));
+    assert!(run(0.2, 3).0 < steps, "a sparse forest burns out sooner");
+}
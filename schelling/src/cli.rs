@@ -0,0 +1,82 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use examples_common::cli::{parse_key_value, RunArgs, SweepArgs};
+use schelling::model::params::Params;
+
+/// Headless Schelling segregation simulation.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// TOML or JSON scenario file with the model parameters
+    #[arg(long, value_name = "FILE")]
+    pub scenario: Option<PathBuf>,
+
+    /// Number of steps of each repetition
+    #[arg(long)]
+    pub steps: Option<u64>,
+
+    /// Number of repetitions
+    #[arg(long)]
+    pub reps: Option<u64>,
+
+    /// Seed of the random number generators
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Width of the field
+    #[arg(long)]
+    pub width: Option<i32>,
+
+    /// Height of the field
+    #[arg(long)]
+    pub height: Option<i32>,
+
+    /// Number of agents
+    #[arg(long)]
+    pub agents: Option<u32>,
+
+    /// Override a model parameter, e.g. `--param SIMILAR_WANTED=4`
+    #[arg(long = "param", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+    pub params: Vec<(String, String)>,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    #[command(flatten)]
+    pub sweep: SweepArgs,
+}
+
+impl Cli {
+    /// Build the parameters of the run: the scenario file (or the defaults), then the named
+    /// flags, then every `--param`. The result is validated before being returned.
+    pub fn params(&self) -> Result<Params, String> {
+        let mut params = match &self.scenario {
+            Some(path) => Params::from_file(path)?,
+            None => Params::default(),
+        };
+        if let Some(steps) = self.steps {
+            params.steps = steps;
+        }
+        if let Some(reps) = self.reps {
+            params.reps = reps;
+        }
+        if let Some(seed) = self.seed {
+            params.seed = seed;
+        }
+        if let Some(width) = self.width {
+            params.width = width;
+        }
+        if let Some(height) = self.height {
+            params.height = height;
+        }
+        if let Some(agents) = self.agents {
+            params.num_agents = agents;
+        }
+        for (name, value) in &self.params {
+            params.set(name, value)?;
+        }
+        params.validate()?;
+        Ok(params)
+    }
+}
@@ -0,0 +1,10 @@
+pub mod model;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+pub const PERC: f32 = 0.5;
+pub const SIMILAR_WANTED: u32 = 3;
+/* pub static WIDTH: i32 = 100;
+pub static HEIGHT: i32 = 100;
+pub const NUM_AGENTS: u32 = 320; */
@@ -4,48 +4,82 @@ use krabmaga::*;
 // Visualization specific imports
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 use {
-    crate::visualization::world_vis::WorldVis, krabmaga::bevy::app::FixedUpdate,
-    krabmaga::bevy::prelude::Color, krabmaga::engine::fields::sparse_object_grid_2d::SparseGrid2D,
+    krabmaga::bevy::app::FixedUpdate, krabmaga::bevy::prelude::Color,
+    krabmaga::engine::fields::sparse_object_grid_2d::SparseGrid2D,
     krabmaga::visualization::fields::object_grid_2d::RenderObjectGrid2D,
-    krabmaga::visualization::visualization::Visualization,
+    krabmaga::visualization::visualization::Visualization, schelling::model::world::Patch,
+    schelling::visualization::world_vis::WorldVis,
 };
 
 // Global imports (needed for the simulation to run)
-use crate::model::world::Patch;
-use crate::model::world::World;
+use schelling::model::params::Params;
+use schelling::model::world::World;
 
-mod model;
-
-pub const PERC: f32 = 0.5;
-pub const SIMILAR_WANTED: u32 = 3;
-
-/* pub static WIDTH: i32 = 100;
-pub static HEIGHT: i32 = 100;
-pub const NUM_AGENTS: u32 = 320; */
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+mod cli;
 
 // Main used when only the simulation should run, without any visualization.
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 fn main() {
-    //testing deploy
-    let step = 10;
-    let dim: (i32, i32) = (20, 20);
-    let num_agents = 320;
+    use clap::{CommandFactory, Parser};
 
-    let world = World::new(dim, num_agents);
+    let cli = cli::Cli::parse();
+    let params: Params = cli.params().unwrap_or_else(|e| {
+        cli::Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
 
-    simulate!(world, step, 10);
-}
+    let step = params.steps;
+    let reps = params.reps;
 
-#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-mod visualization;
+    if let Some(sweep) = cli.sweep.sweep() {
+        let path = cli.run.sweep_path();
+        let result = sweep.run::<World>(&params).and_then(|summaries| {
+            sweep
+                .write(&path, &summaries)
+                .map_err(|e| format!("cannot write `{}`: {}", path.display(), e))?;
+            println!(
+                "{} runs summarized in `{}`",
+                summaries.len(),
+                path.display()
+            );
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let observer = cli.run.observer(params.seed);
+    let mut world = World::new(params);
+    world.observer = observer;
+    match cli.run.checkpoints() {
+        Some(checkpoints) => {
+            if let Err(e) = checkpoints.run(world, step, reps) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            simulate!(world, step, reps);
+        }
+    }
+}
 
 // Main used when a visualization feature is applied.
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 fn main() {
     // Initialize the simulation and its visualization here.
-    let dim: (i32, i32) = (25, 25);
-    let num_agents = 320;
-    let world = World::new(dim, num_agents);
+    let params = Params {
+        width: 25,
+        height: 25,
+        ..Default::default()
+    };
+    let dim = params.dim();
+    let world = World::new(params);
     let mut app = Visualization::default()
         .with_simulation_dimensions(dim.0 as f32, dim.1 as f32)
         .with_window_dimensions(1000., 720.)
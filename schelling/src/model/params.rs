@@ -0,0 +1,146 @@
+use examples_common::sweep::SweepParams;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{PERC, SIMILAR_WANTED};
+
+/// Run settings and model coefficients of a Schelling simulation.
+/// Defaults match the values hard-coded in the original example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    /// Seed of the random number generators, the same seed replays the same run.
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    pub num_agents: u32,
+    /// Fraction of the agents that are `Red`, the others are `Blue`.
+    pub perc: f32,
+    /// Number of similar neighbours an agent needs to be happy.
+    pub similar_wanted: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 10,
+            reps: 10,
+            seed: 0,
+            width: 20,
+            height: 20,
+            num_agents: 320,
+            perc: PERC,
+            similar_wanted: SIMILAR_WANTED,
+        }
+    }
+}
+
+impl Params {
+    /// Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    /// keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    pub fn dim(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Override a parameter by name. Names are matched case-insensitively, so both
+    /// `similar_wanted` and `SIMILAR_WANTED` are accepted.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "steps" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "num_agents" | "agents" => self.num_agents = parse(name, value)?,
+            "perc" => self.perc = parse(name, value)?,
+            "similar_wanted" => self.similar_wanted = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    /// Check that every parameter is in its valid range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                self.width, self.height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        if self.num_agents as i64 > self.width as i64 * self.height as i64 {
+            return Err(format!(
+                "{} agents do not fit in a {}x{} field",
+                self.num_agents, self.width, self.height
+            ));
+        }
+        if self.similar_wanted > 8 {
+            return Err(format!(
+                "`similar_wanted` must be at most 8, found {}",
+                self.similar_wanted
+            ));
+        }
+        probability("perc", self.perc as f64)
+    }
+}
+
+impl SweepParams for Params {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    fn reps(&self) -> u64 {
+        self.reps
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        Params::set(self, name, value)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        Params::validate(self)
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
+
+fn probability(name: &str, value: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{}` must be in [0, 1], found {}", name, value))
+    }
+}
@@ -1,15 +1,17 @@
+use crate::model::world::Patch;
 use crate::model::world::World;
-use crate::Patch;
-use crate::SIMILAR_WANTED;
 use core::fmt;
+use examples_common::rng;
 use krabmaga::engine::agent::Agent;
 use krabmaga::engine::location::Int2D;
 use krabmaga::engine::schedule::{Schedule, ScheduleOptions};
 use krabmaga::engine::state::State;
+use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::hash::{Hash, Hasher};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Updater {
     pub id: u32,
 }
@@ -17,9 +19,22 @@ pub struct Updater {
 impl Agent for Updater {
     fn step(&mut self, state: &mut dyn State) {
         let real_state = state.as_any().downcast_ref::<World>().unwrap();
-        let updates = RefCell::new(Vec::<(Patch, Int2D)>::new());
-
-        real_state.field.iter_objects(|loc, value| {
+        // `iter_objects` walks a hash map: visit the patches in id order instead, so that they
+        // draw their destination in the same order on every run.
+        let patches = RefCell::new(Vec::<(Int2D, Patch)>::new());
+        real_state
+            .field
+            .iter_objects(|loc, value| patches.borrow_mut().push((*loc, *value)));
+        let mut patches = patches.into_inner();
+        patches.sort_by_key(|(_, patch)| patch.id);
+
+        // Unhappy patches move to a cell that is empty at the start of the step, drawn from the
+        // seeded stream of the updater instead of `get_random_empty_bag`.
+        let empty_bags = real_state.field.get_empty_bags();
+        let mut rng = rng::agent_rng(real_state.seed(), self.id as u64, real_state.step);
+        let mut updates = Vec::<(Patch, Int2D)>::with_capacity(patches.len());
+
+        for (loc, value) in patches.iter() {
             let x = loc.x;
             let y = loc.y;
             //let mut neighbors = 0.0;
@@ -53,30 +68,23 @@ impl Agent for Updater {
                     }
                 }
             }
-            let mut updates = updates.borrow_mut();
 
-            if similar < SIMILAR_WANTED {
-                let nloc = real_state.field.get_random_empty_bag();
-                match nloc {
-                    Some(rloc) => {
-                        updates.push((*value, rloc));
-                    }
-                    None => {
-                        updates.push((*value, *loc));
-                    }
+            if similar < real_state.params.similar_wanted {
+                if empty_bags.is_empty() {
+                    updates.push((*value, *loc));
+                } else {
+                    let rloc = empty_bags[rng.random_range(0..empty_bags.len())];
+                    updates.push((*value, rloc));
                 }
             } else {
                 // agent ok nothing to do
                 updates.push((*value, *loc));
             }
-        });
+        }
 
-        let mut updates = updates.borrow_mut();
         for obj in updates.iter() {
             real_state.field.set_object_location(obj.0, &obj.1);
         }
-
-        updates.clear();
     }
 
     fn before_step(
@@ -1,19 +1,23 @@
+use crate::model::params::Params;
 use crate::model::updater::Updater;
 use core::fmt;
+use examples_common::checkpoint::{self, Cell, Resumable};
+use examples_common::observer::{Observer, TimeSeries};
+use examples_common::rng;
+use examples_common::sweep::Sweepable;
+use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::field::Field;
 use krabmaga::engine::fields::sparse_object_grid_2d::SparseGrid2D;
 use krabmaga::engine::location::Int2D;
 use krabmaga::engine::schedule::Schedule;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::hash::Hash;
 use std::hash::Hasher;
 
-use crate::PERC;
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Status {
     Red,
     Blue,
@@ -28,7 +32,7 @@ impl fmt::Display for Status {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Patch {
     pub id: u32,
     pub value: Status,
@@ -62,18 +66,29 @@ pub struct World {
     pub field: SparseGrid2D<Patch>,
     pub dim: (i32, i32),
     pub num_agents: u32,
+    pub params: Params,
+    pub observer: Observer,
 }
 
 impl World {
-    pub fn new(dim: (i32, i32), num_agents: u32) -> World {
+    pub fn new(params: Params) -> World {
+        let dim = params.dim();
         World {
             step: 0,
             field: SparseGrid2D::new(dim.0, dim.1),
             dim,
-            num_agents,
+            num_agents: params.num_agents,
+            params,
+            observer: Observer::new(),
         }
     }
 
+    // Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    // `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
+
     pub fn as_state_mut(&mut self) -> &mut dyn State {
         self
     }
@@ -96,14 +111,15 @@ impl State for World {
     fn init(&mut self, schedule: &mut Schedule) {
         //println!("init system by state");
         self.step = 0;
+        self.observer.reset();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::state_rng(self.seed());
 
         for i in 0..self.num_agents {
-            let xx: i32 = rng.gen_range(0..self.dim.0);
-            let yy: i32 = rng.gen_range(0..self.dim.1);
+            let xx: i32 = rng.random_range(0..self.dim.0);
+            let yy: i32 = rng.random_range(0..self.dim.1);
 
-            if i < ((self.num_agents as f32) * PERC).ceil() as u32 {
+            if i < ((self.num_agents as f32) * self.params.perc).ceil() as u32 {
                 self.field.set_object_location(
                     Patch {
                         id: i,
@@ -144,11 +160,117 @@ impl State for World {
 
     fn before_step(&mut self, _schedule: &mut Schedule) {}
 
-    fn after_step(&mut self, _schedule: &mut Schedule) {
+    fn after_step(&mut self, schedule: &mut Schedule) {
         self.step += 1;
+
+        // segregation index: mean fraction of similar neighbours, over the patches with at
+        // least one neighbour, read from the locations written during this step
+        let mut similar_fraction = 0.;
+        let mut with_neighbours = 0;
+        let mut unhappy = 0;
+        for x in 0..self.dim.0 {
+            for y in 0..self.dim.1 {
+                let patches = match self.field.get_objects_unbuffered(&Int2D { x, y }) {
+                    Some(patches) => patches,
+                    None => continue,
+                };
+                for patch in patches {
+                    let (mut similar, mut neighbours) = (0, 0);
+                    for i in -1..=1 {
+                        for j in -1..=1 {
+                            let loc_n = Int2D { x: x + i, y: y + j };
+                            if (i == 0 && j == 0)
+                                || loc_n.x < 0
+                                || loc_n.y < 0
+                                || loc_n.x >= self.dim.0
+                                || loc_n.y >= self.dim.1
+                            {
+                                continue;
+                            }
+                            if let Some(neighbor) = self.field.get_objects_unbuffered(&loc_n) {
+                                neighbours += 1;
+                                if neighbor[0].value == patch.value {
+                                    similar += 1;
+                                }
+                            }
+                        }
+                    }
+                    if neighbours > 0 {
+                        similar_fraction += similar as f64 / neighbours as f64;
+                        with_neighbours += 1;
+                    }
+                    if similar < self.params.similar_wanted {
+                        unhappy += 1;
+                    }
+                }
+            }
+        }
+        self.observer.record(
+            schedule.step + 1,
+            &[
+                (
+                    "segregation",
+                    similar_fraction / with_neighbours.max(1) as f64,
+                ),
+                ("unhappy", unhappy as f64),
+            ],
+        );
     }
 
     fn end_condition(&mut self, _schedule: &mut Schedule) -> bool {
         false
     }
 }
+
+/// Everything a `World` needs to go on with a run, see `Resumable`.
+#[derive(Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    pub params: Params,
+    pub step: u64,
+    pub patches: Vec<Cell<Patch>>,
+}
+
+impl Resumable for World {
+    type Snapshot = WorldSnapshot;
+    type Agent = Updater;
+
+    fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            patches: checkpoint::save_sparse_grid(&self.field),
+        }
+    }
+
+    fn restore(snapshot: WorldSnapshot) -> Result<World, String> {
+        let mut world = World::new(snapshot.params);
+        world.step = snapshot.step;
+        checkpoint::restore_sparse_grid(&world.field, snapshot.patches);
+        world.field.lazy_update();
+        Ok(world)
+    }
+
+    fn save_agent(agent: &dyn Agent) -> Updater {
+        *agent.downcast_ref::<Updater>().unwrap()
+    }
+
+    fn restore_agent(updater: Updater) -> Box<dyn Agent> {
+        Box::new(updater)
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
+    }
+}
+
+impl Sweepable for World {
+    type Params = Params;
+
+    fn build(params: Params) -> Result<Self, String> {
+        Ok(World::new(params))
+    }
+
+    fn metrics(&self) -> &TimeSeries {
+        self.observer.time_series()
+    }
+}
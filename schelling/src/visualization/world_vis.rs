@@ -1,5 +1,5 @@
+use crate::model::world::Patch;
 use crate::model::world::World;
-use crate::Patch;
 use krabmaga::bevy::ecs as bevy_ecs;
 use krabmaga::bevy::ecs::system::Resource;
 use krabmaga::bevy::prelude::Commands;
@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+use std::path::Path;
+
+use examples_common::checkpoint::{Resumable, ScheduleSnapshot};
+use examples_common::sweep::{Design, Sweep};
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use schelling::model::params::Params;
+use schelling::model::updater::Updater;
+use schelling::model::world::{Status, World, WorldSnapshot};
+
+fn init(params: Params) -> (World, Schedule) {
+    let mut world = World::new(params);
+    let mut schedule = Schedule::new();
+    world.init(&mut schedule);
+    (world, schedule)
+}
+
+// (id, x, y, is red) of every patch on the field, sorted by id
+fn patches(world: &World) -> Vec<(u32, i32, i32, bool)> {
+    let patches = RefCell::new(Vec::new());
+    world.field.iter_objects(|loc, patch| {
+        patches
+            .borrow_mut()
+            .push((patch.id, loc.x, loc.y, patch.value == Status::Red))
+    });
+    let mut patches = patches.into_inner();
+    patches.sort();
+    patches
+}
+
+#[test]
+fn agent_count_is_conserved() {
+    let params = Params::default();
+    let num_agents = params.num_agents;
+    let reds = (num_agents as f32 * params.perc).ceil() as usize;
+    let (mut world, mut schedule) = init(params);
+
+    for _ in 0..20 {
+        schedule.step(&mut world);
+        let patches = patches(&world);
+        let ids: Vec<u32> = patches.iter().map(|p| p.0).collect();
+        assert_eq!(ids, (0..num_agents).collect::<Vec<_>>());
+        assert_eq!(patches.iter().filter(|p| p.3).count(), reds);
+    }
+}
+
+#[test]
+fn patches_stay_inside_the_field() {
+    let (mut world, mut schedule) = init(Params::default());
+    for _ in 0..20 {
+        schedule.step(&mut world);
+        for (_, x, y, _) in patches(&world) {
+            assert!(x >= 0 && x < world.dim.0 && y >= 0 && y < world.dim.1);
+        }
+    }
+}
+
+#[test]
+fn segregation_index_is_a_fraction() {
+    let params = Params::default();
+    let num_agents = params.num_agents as f64;
+    let (mut world, mut schedule) = init(params);
+    for _ in 0..20 {
+        schedule.step(&mut world);
+    }
+    let segregation = world.observer.series("segregation").unwrap();
+    let unhappy = world.observer.series("unhappy").unwrap();
+    assert_eq!(segregation.len(), 20);
+    assert!(segregation.iter().all(|s| (0.0..=1.0).contains(s)));
+    assert!(unhappy.iter().all(|&u| (0.0..=num_agents).contains(&u)));
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut world, mut schedule) = init(Params {
+            seed,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            schedule.step(&mut world);
+        }
+        patches(&world)
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let (mut world, mut schedule) = init(Params {
+        seed: 4,
+        ..Default::default()
+    });
+    for _ in 0..5 {
+        schedule.step(&mut world);
+    }
+    let saved = serde_json::to_string(&(
+        world.snapshot(),
+        ScheduleSnapshot::take(&mut schedule, World::save_agent),
+    ))
+    .unwrap();
+    let (snapshot, events): (WorldSnapshot, ScheduleSnapshot<Updater>) =
+        serde_json::from_str(&saved).unwrap();
+    let mut resumed = World::restore(snapshot).unwrap();
+    let mut resumed_schedule = events.restore(World::restore_agent);
+    assert_eq!(patches(&resumed), patches(&world));
+
+    for _ in 0..15 {
+        schedule.step(&mut world);
+        resumed_schedule.step(&mut resumed);
+    }
+    assert_eq!(resumed_schedule.step, schedule.step);
+    assert_eq!(patches(&resumed), patches(&world));
+}
+
+#[test]
+fn whole_number_parameters_are_swept_by_rounding() {
+    let sweep = Sweep {
+        factors: vec![
+            "SIMILAR_WANTED=1:4".parse().unwrap(),
+            "PERC=0.3:0.7".parse().unwrap(),
+        ],
+        design: Design::Grid,
+        points: 3,
+        threads: 2,
+    };
+    let params = Params {
+        steps: 5,
+        reps: 1,
+        ..Params::default()
+    };
+    let summaries = sweep.run::<World>(&params).unwrap();
+    let values: Vec<_> = summaries.iter().map(|s| s.values.clone()).collect();
+    assert_eq!(values[0], ["1", "0.3"]);
+    // 2.5 is not a valid `similar_wanted`, the sweep sets 3
+    assert_eq!(values[3], ["3", "0.3"]);
+    assert_eq!(values[8], ["4", "0.7"]);
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.toml");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}
@@ -0,0 +1,7 @@
+pub mod model;
+
+// generic model parameters
+pub static INIT_EDGES: usize = 1;
+pub const NUM_NODES: u32 = 1_000;
+
+pub const STEP: u64 = 51; // 51 - 37
@@ -1,16 +1,13 @@
 use krabmaga::*;
 
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 use krabmaga::{engine::schedule::Schedule, engine::state::State, rand::Rng};
 use rand::prelude::*;
 
-#[cfg(any(feature = "distributed_mpi"))]
-use {crate::model::state::EpidemicNetworkState, std::cmp::Ordering::Equal};
-mod model;
-
-// generic model parameters
-pub static INIT_EDGES: usize = 1;
-pub const NUM_NODES: u32 = 1_000;
+#[cfg(feature = "distributed_mpi")]
+use sir_ga_exploration::{NUM_NODES, STEP};
+#[cfg(feature = "distributed_mpi")]
+use {sir_ga_exploration::model::state::EpidemicNetworkState, std::cmp::Ordering::Equal};
 
 // GA specific parameters
 lazy_static! {
@@ -37,15 +34,14 @@ lazy_static! {
     pub static ref RNG: Mutex<StdRng> = Mutex::new(StdRng::seed_from_u64(0));
 }
 
-pub const STEP: u64 = 51; // 51 - 37
 pub const DAY: usize = 45; // 45 - 31
 
-#[cfg(not(any(feature = "distributed_mpi")))]
+#[cfg(not(feature = "distributed_mpi"))]
 fn main() {
     println!("No bayesian feature enabled");
 }
 
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 fn main() {
     let result = explore_ga_distributed_mpi!(
         init_population,
@@ -67,7 +63,7 @@ fn main() {
         let _res = write_csv(&name, &result);
     }
 }
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 fn fitness(computed_ind: &mut Vec<(EpidemicNetworkState, Schedule)>) -> f32 {
     let mut avg_results: Vec<f32> = vec![0.0; DAY];
 
@@ -92,13 +88,13 @@ fn fitness(computed_ind: &mut Vec<(EpidemicNetworkState, Schedule)>) -> f32 {
 // we want to minimize the fitness, therefore the comparison
 // return true, meaning that fitness1 is better than fitness2,
 // if fitness1 is lower than fitness 2
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 fn cmp(fitness1: &f32, fitness2: &f32) -> bool {
     *fitness1 < *fitness2
 }
 
 // function that initialize the populatin
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 fn init_population() -> Vec<String> {
     // create an array of EpidemicNetworkState
     let mut population = Vec::new();
@@ -117,7 +113,7 @@ fn init_population() -> Vec<String> {
     population
 }
 
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 fn selection(population_fitness: &mut Vec<(String, f32)>) {
     let mut min_fitness = 1.;
     for individual_fitness in population_fitness.iter_mut() {
@@ -138,7 +134,7 @@ fn selection(population_fitness: &mut Vec<(String, f32)>) {
     population_fitness.sort_by(|s1, s2| s1.1.partial_cmp(&s2.1).unwrap_or(Equal));
 }
 
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 fn crossover(population: &mut Vec<String>) {
     let mut children: Vec<String> = Vec::new();
 
@@ -302,7 +298,7 @@ fn crossover(population: &mut Vec<String>) {
     *population = children;
 }
 
-#[cfg(any(feature = "distributed_mpi"))]
+#[cfg(feature = "distributed_mpi")]
 fn mutation(individual: &mut String) {
     let new_ind: String;
     let new_individual: Vec<&str> = individual.split(';').collect();
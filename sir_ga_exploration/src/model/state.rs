@@ -186,10 +186,10 @@ impl State for EpidemicNetworkState {
         let infected_nodes = &self.infected_nodes;
         let mut counter = 0;
         let mut value = 0;
-        for i in 3..infected_nodes.len() {
-            if infected_nodes[i] != 0 {
+        for &infected in infected_nodes.iter().skip(3) {
+            if infected != 0 {
                 counter += 1;
-                value += infected_nodes[i];
+                value += infected;
             }
         }
         if value == 0 {
@@ -200,8 +200,8 @@ impl State for EpidemicNetworkState {
 
         // count the daily infection
         let mut newly_infected = 0;
-        for i in 0..infected_nodes.len() {
-            newly_infected += infected_nodes[i];
+        for infected in infected_nodes.iter() {
+            newly_infected += infected;
         }
 
         // compute the daily weekly average of infection
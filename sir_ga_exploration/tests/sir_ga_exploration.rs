@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use sir_ga_exploration::model::node::{NetNode, NodeStatus};
+use sir_ga_exploration::model::state::EpidemicNetworkState;
+use sir_ga_exploration::{NUM_NODES, STEP};
+
+fn init(parameters: &str) -> (EpidemicNetworkState, Schedule) {
+    let mut state = EpidemicNetworkState::new_with_parameters(0, parameters);
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    (state, schedule)
+}
+
+// Nodes only move forward: Susceptible, then Infected, then Resistant
+fn stage(status: NodeStatus) -> u8 {
+    match status {
+        NodeStatus::Susceptible => 0,
+        NodeStatus::Infected => 1,
+        NodeStatus::Resistant => 2,
+    }
+}
+
+// Stage of every scheduled node, by id
+fn nodes(schedule: &Schedule) -> HashMap<u32, u8> {
+    schedule
+        .get_all_events()
+        .iter()
+        .map(|event| {
+            let node = event.downcast_ref::<NetNode>().unwrap();
+            (node.id, stage(node.status))
+        })
+        .collect()
+}
+
+// Steps the epidemic until it dies out or the observed days run out
+fn run(state: &mut EpidemicNetworkState, schedule: &mut Schedule) -> Vec<HashMap<u32, u8>> {
+    let mut history = Vec::new();
+    for _ in 0..STEP - 1 {
+        schedule.step(state);
+        history.push(nodes(schedule));
+        if state.end_condition(schedule) {
+            break;
+        }
+    }
+    history
+}
+
+#[test]
+fn one_node_is_infected_at_start() {
+    let (_, schedule) = init("0.1;0.05;0.1;10");
+    let nodes = nodes(&schedule);
+    assert_eq!(nodes.len(), NUM_NODES as usize);
+    assert_eq!(nodes.values().filter(|&&stage| stage == 1).count(), 1);
+    assert!(nodes.values().all(|&stage| stage < 2));
+}
+
+#[test]
+fn nodes_never_go_back_to_an_earlier_status() {
+    let (mut state, mut schedule) = init("0.3;0.1;0.2;10");
+    let mut previous = nodes(&schedule);
+    for current in run(&mut state, &mut schedule) {
+        assert_eq!(current.len(), previous.len());
+        for (id, stage) in current.iter() {
+            assert!(*stage >= previous[id], "node {} went back", id);
+        }
+        previous = current;
+    }
+    assert!(previous.values().any(|&stage| stage == 2));
+}
+
+#[test]
+fn same_parameters_replay_same_epidemic() {
+    let epidemic = |parameters| {
+        let (mut state, mut schedule) = init(parameters);
+        run(&mut state, &mut schedule);
+        (nodes(&schedule), state.weekly_infected)
+    };
+    assert_eq!(epidemic("0.3;0.1;0.2;10"), epidemic("0.3;0.1;0.2;10"));
+}
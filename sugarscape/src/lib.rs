@@ -0,0 +1,6 @@
+pub mod model;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+pub const MAX_SUGAR: u32 = 3;
@@ -1,3 +1,4 @@
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 use krabmaga::*;
 
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
@@ -8,33 +9,75 @@ use {
     krabmaga::visualization::visualization::Visualization,
 };
 
-use crate::model::state::Environment;
-#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-use crate::visualization::environment_vis::EnvironmentVis;
-
-mod model;
+use sugarscape::model::params::Params;
+use sugarscape::model::state::Environment;
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-mod visualization;
+use sugarscape::visualization::environment_vis::EnvironmentVis;
 
-pub const MAX_SUGAR: u32 = 3;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+mod cli;
 
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 fn main() {
-    let step = 50;
+    use clap::{CommandFactory, Parser};
+
+    let cli = cli::Cli::parse();
+    let params: Params = cli.params().unwrap_or_else(|e| {
+        cli::Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
+
+    let step = params.steps;
+    let reps = params.reps;
 
-    let dim = (64, 64);
-    let num_agents = 64;
+    if let Some(sweep) = cli.sweep.sweep() {
+        let path = cli.run.sweep_path();
+        let result = sweep.run::<Environment>(&params).and_then(|summaries| {
+            sweep
+                .write(&path, &summaries)
+                .map_err(|e| format!("cannot write `{}`: {}", path.display(), e))?;
+            println!(
+                "{} runs summarized in `{}`",
+                summaries.len(),
+                path.display()
+            );
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    let state = Environment::new(dim, num_agents);
-    let _ = simulate!(state, step, 10);
+    let observer = cli.run.observer(params.seed);
+    let mut state = Environment::new(params);
+    state.observer = observer;
+    match cli.run.checkpoints() {
+        Some(checkpoints) => {
+            if let Err(e) = checkpoints.run(state, step, reps) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            simulate!(state, step, reps);
+        }
+    }
 }
 
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 fn main() {
-    let dim = (75, 75);
-    let num_agents = 150;
+    let params = Params {
+        width: 75,
+        height: 75,
+        num_agents: 150,
+        ..Default::default()
+    };
+    let dim = params.dim();
 
-    let state = Environment::new(dim, num_agents);
+    let state = Environment::new(params);
     let mut app = Visualization::default()
         .with_window_dimensions(1000.0, 600.0)
         .with_simulation_dimensions(dim.0 as f32, dim.1 as f32)
@@ -1,2 +1,3 @@
 pub mod eater;
+pub mod params;
 pub mod state;
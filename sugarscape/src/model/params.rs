@@ -0,0 +1,141 @@
+use examples_common::sweep::SweepParams;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Run settings and agent endowments of a Sugarscape simulation.
+/// Defaults match the values hard-coded in the original example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    /// Seed of the random number generators, the same seed replays the same run.
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    pub num_agents: u32,
+    /// Vision of an eater is drawn uniformly in `1..=max_vision`.
+    pub max_vision: u32,
+    /// Metabolism of an eater is drawn uniformly in `1..=max_metabolism`.
+    pub max_metabolism: u32,
+    pub max_age: u32,
+    pub initial_wealth: i32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 50,
+            reps: 10,
+            seed: 0,
+            width: 64,
+            height: 64,
+            num_agents: 64,
+            max_vision: 3,
+            max_metabolism: 3,
+            max_age: 20,
+            initial_wealth: 20,
+        }
+    }
+}
+
+impl Params {
+    /// Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    /// keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    pub fn dim(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Override a parameter by name. Names are matched case-insensitively, so both
+    /// `max_age` and `MAX_AGE` are accepted.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "steps" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "num_agents" | "agents" => self.num_agents = parse(name, value)?,
+            "max_vision" => self.max_vision = parse(name, value)?,
+            "max_metabolism" => self.max_metabolism = parse(name, value)?,
+            "max_age" => self.max_age = parse(name, value)?,
+            "initial_wealth" => self.initial_wealth = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    /// Check that every parameter is in its valid range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                self.width, self.height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        if self.max_vision == 0 || self.max_metabolism == 0 {
+            return Err(String::from(
+                "`max_vision` and `max_metabolism` must be at least 1",
+            ));
+        }
+        if self.initial_wealth <= 0 {
+            return Err(format!(
+                "`initial_wealth` must be positive, found {}",
+                self.initial_wealth
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl SweepParams for Params {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    fn reps(&self) -> u64 {
+        self.reps
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        Params::set(self, name, value)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        Params::validate(self)
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
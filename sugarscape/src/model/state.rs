@@ -1,7 +1,13 @@
 use std::any::Any;
 
 use crate::model::eater::Eater;
+use crate::model::params::Params;
 use core::fmt;
+use examples_common::checkpoint::{self, Cell, Resumable};
+use examples_common::observer::{Observer, TimeSeries};
+use examples_common::rng;
+use examples_common::sweep::Sweepable;
+use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::dense_number_grid_2d::DenseNumberGrid2D;
 use krabmaga::engine::fields::dense_object_grid_2d::DenseGrid2D;
 use krabmaga::engine::fields::field::Field;
@@ -9,9 +15,10 @@ use krabmaga::{
     engine::{location::Int2D, schedule::Schedule, state::State},
     rand::Rng,
 };
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct Patch {
     pub id: u32,
@@ -60,18 +67,29 @@ pub struct Environment {
     pub dim: (i32, i32),
     pub num_agents: u32,
     pub eaters: DenseGrid2D<Eater>,
+    pub params: Params,
+    pub observer: Observer,
 }
 
 impl Environment {
-    pub fn new(dim: (i32, i32), num_agents: u32) -> Environment {
+    pub fn new(params: Params) -> Environment {
+        let dim = params.dim();
         Environment {
             step: 0,
             field: DenseNumberGrid2D::new(dim.0, dim.1),
             dim,
-            num_agents,
+            num_agents: params.num_agents,
             eaters: DenseGrid2D::new(dim.0, dim.1),
+            params,
+            observer: Observer::new(),
         }
     }
+
+    // Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    // `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
 }
 
 impl State for Environment {
@@ -94,8 +112,9 @@ impl State for Environment {
     //Initializes all the patches and eaters
     fn init(&mut self, schedule: &mut Schedule) {
         self.step = 0;
+        self.observer.reset();
         let mut id = 0;
-        let mut rng = krabmaga::rand::thread_rng();
+        let mut rng = rng::state_rng(self.seed());
 
         let bottom_left_mid = (
             (self.dim.0 as f32 * 0.25) as i32,
@@ -149,7 +168,7 @@ impl State for Environment {
                 }
 
                 let pos = Int2D { x: i, y: j };
-                let sugar_growback = rng.gen_range(0..4);
+                let sugar_growback = rng.random_range(0..4);
                 let patch = Patch::new(id, sugar_amount, sugar_growback);
                 id += 1;
                 self.field.set_value_location(patch, &pos);
@@ -158,17 +177,17 @@ impl State for Environment {
 
         //Initializes the Agents
         for i in 0..self.num_agents {
-            let xx = rng.gen_range(0..self.dim.0);
-            let yy = rng.gen_range(0..self.dim.1);
+            let xx = rng.random_range(0..self.dim.0);
+            let yy = rng.random_range(0..self.dim.1);
             let pos = Int2D { x: xx, y: yy };
             let agent = Eater {
                 id: i,
                 position: pos,
-                vision: rng.gen_range(1..4),
-                metabolism: rng.gen_range(1..4),
+                vision: rng.random_range(1..=self.params.max_vision),
+                metabolism: rng.random_range(1..=self.params.max_metabolism),
                 age: 0,
-                max_age: 20,
-                wealth: 20,
+                max_age: self.params.max_age,
+                wealth: self.params.initial_wealth,
             };
 
             self.eaters.set_object_location(agent, &pos);
@@ -191,5 +210,86 @@ impl State for Environment {
     fn as_state(&self) -> &dyn State {
         self
     }
-    fn after_step(&mut self, _schedule: &mut Schedule) {}
+
+    //Records the wealth and the age of the eaters
+    fn after_step(&mut self, schedule: &mut Schedule) {
+        let eaters = schedule.get_all_events();
+        let n = eaters.len().max(1) as f64;
+        let (mut wealth, mut max_wealth, mut age) = (0., f64::MIN, 0.);
+        for agent in &eaters {
+            let eater = agent.downcast_ref::<Eater>().unwrap();
+            wealth += eater.wealth as f64;
+            max_wealth = f64::max(max_wealth, eater.wealth as f64);
+            age += eater.age as f64;
+        }
+        self.observer.record(
+            schedule.step + 1,
+            &[
+                ("eaters", eaters.len() as f64),
+                ("mean_wealth", wealth / n),
+                (
+                    "max_wealth",
+                    if eaters.is_empty() { 0. } else { max_wealth },
+                ),
+                ("mean_age", age / n),
+            ],
+        );
+    }
+}
+
+/// Everything an `Environment` needs to go on with a run, see `Resumable`.
+#[derive(Serialize, Deserialize)]
+pub struct EnvironmentSnapshot {
+    pub params: Params,
+    pub step: u64,
+    pub patches: Vec<Option<Patch>>,
+    pub eaters: Vec<Cell<Eater>>,
+}
+
+impl Resumable for Environment {
+    type Snapshot = EnvironmentSnapshot;
+    type Agent = Eater;
+
+    fn snapshot(&self) -> EnvironmentSnapshot {
+        EnvironmentSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            patches: checkpoint::save_values(&self.field),
+            eaters: checkpoint::save_grid(&self.eaters),
+        }
+    }
+
+    fn restore(snapshot: EnvironmentSnapshot) -> Result<Environment, String> {
+        let mut state = Environment::new(snapshot.params);
+        state.step = snapshot.step;
+        checkpoint::restore_values(&state.field, snapshot.patches);
+        checkpoint::restore_grid(&state.eaters, snapshot.eaters);
+        state.field.lazy_update();
+        state.eaters.lazy_update();
+        Ok(state)
+    }
+
+    fn save_agent(agent: &dyn Agent) -> Eater {
+        *agent.downcast_ref::<Eater>().unwrap()
+    }
+
+    fn restore_agent(eater: Eater) -> Box<dyn Agent> {
+        Box::new(eater)
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
+    }
+}
+
+impl Sweepable for Environment {
+    type Params = Params;
+
+    fn build(params: Params) -> Result<Self, String> {
+        Ok(Environment::new(params))
+    }
+
+    fn metrics(&self) -> &TimeSeries {
+        self.observer.time_series()
+    }
 }
@@ -13,7 +13,6 @@ use krabmaga::visualization::asset_handle_factory::AssetHandleFactoryResource;
 use krabmaga::visualization::fields::number_grid_2d::BatchRender;
 use krabmaga::visualization::simulation_descriptor::SimulationDescriptor;
 use krabmaga::visualization::visualization_state::VisualizationState;
-use krabmaga::Rng;
 
 #[derive(Clone, Resource)]
 pub struct EnvironmentVis;
@@ -47,14 +46,10 @@ impl VisualizationState<Environment> for EnvironmentVis {
         agent_render: &Box<dyn AgentRender>,
         state: &Box<&dyn State>,
     ) -> Option<Box<dyn Agent>> {
-        let mut rng = krabmaga::rand::thread_rng();
         let state = state.as_any().downcast_ref::<Environment>().unwrap();
         match state.eaters.get(&Eater {
             id: agent_render.get_id(),
-            position: Int2D {
-                x: rng.gen_range(0..20),
-                y: rng.gen_range(0..20),
-            },
+            position: Int2D { x: 0, y: 0 },
             vision: 4,
             metabolism: 50,
             age: 0,
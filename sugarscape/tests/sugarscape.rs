@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use examples_common::checkpoint::{Resumable, ScheduleSnapshot};
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use sugarscape::model::eater::Eater;
+use sugarscape::model::params::Params;
+use sugarscape::model::state::{Environment, EnvironmentSnapshot};
+
+fn init(params: Params) -> (Environment, Schedule) {
+    let mut state = Environment::new(params);
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    (state, schedule)
+}
+
+fn eaters(schedule: &Schedule) -> Vec<Eater> {
+    let mut eaters: Vec<Eater> = schedule
+        .get_all_events()
+        .iter()
+        .map(|agent| *agent.downcast_ref::<Eater>().unwrap())
+        .collect();
+    eaters.sort_by_key(|eater| eater.id);
+    eaters
+}
+
+#[test]
+fn eaters_are_replaced_rather_than_removed() {
+    let params = Params::default();
+    let num_agents = params.num_agents as usize;
+    let (mut state, mut schedule) = init(params);
+
+    for _ in 0..100 {
+        schedule.step(&mut state);
+        let eaters = eaters(&schedule);
+        assert_eq!(eaters.len(), num_agents);
+        for eater in eaters {
+            assert!(eater.position.x >= 0 && eater.position.x < state.dim.0);
+            assert!(eater.position.y >= 0 && eater.position.y < state.dim.1);
+            assert!(eater.age <= eater.max_age);
+        }
+    }
+}
+
+#[test]
+fn eaters_keep_the_traits_drawn_at_init() {
+    let params = Params::default();
+    let (max_vision, max_metabolism) = (params.max_vision, params.max_metabolism);
+    let (mut state, mut schedule) = init(params);
+    schedule.step(&mut state);
+    let initial = eaters(&schedule);
+
+    for _ in 0..50 {
+        schedule.step(&mut state);
+        for (before, after) in initial.iter().zip(eaters(&schedule)) {
+            assert_eq!(before.vision, after.vision);
+            assert_eq!(before.metabolism, after.metabolism);
+            assert!(after.vision >= 1 && after.vision <= max_vision);
+            assert!(after.metabolism >= 1 && after.metabolism <= max_metabolism);
+        }
+    }
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut state, mut schedule) = init(Params {
+            seed,
+            ..Default::default()
+        });
+        for _ in 0..50 {
+            schedule.step(&mut state);
+        }
+        eaters(&schedule)
+            .iter()
+            .map(|e| (e.id, e.position.x, e.position.y, e.wealth, e.age))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let trajectory = |schedule: &Schedule| {
+        eaters(schedule)
+            .iter()
+            .map(|e| (e.id, e.position.x, e.position.y, e.wealth, e.age))
+            .collect::<Vec<_>>()
+    };
+    let (mut state, mut schedule) = init(Params {
+        seed: 3,
+        ..Default::default()
+    });
+    for _ in 0..30 {
+        schedule.step(&mut state);
+    }
+    let saved = serde_json::to_string(&(
+        state.snapshot(),
+        ScheduleSnapshot::take(&mut schedule, Environment::save_agent),
+    ))
+    .unwrap();
+    let (snapshot, events): (EnvironmentSnapshot, ScheduleSnapshot<Eater>) =
+        serde_json::from_str(&saved).unwrap();
+    let mut resumed = Environment::restore(snapshot).unwrap();
+    let mut resumed_schedule = events.restore(Environment::restore_agent);
+
+    for _ in 0..30 {
+        schedule.step(&mut state);
+        resumed_schedule.step(&mut resumed);
+    }
+    assert_eq!(trajectory(&resumed_schedule), trajectory(&schedule));
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.toml");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}
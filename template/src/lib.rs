@@ -0,0 +1,7 @@
+pub mod model;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+pub static DISCRETIZATION: f32 = 10.0 / 1.5;
+pub static TOROIDAL: bool = true;
@@ -4,32 +4,47 @@ use krabmaga::*;
 // Visualization specific imports
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 use {
-    crate::visualization::sea_vis::SeaVis, krabmaga::bevy::prelude::Color,
-    krabmaga::visualization::visualization::Visualization,
+    krabmaga::bevy::prelude::Color, krabmaga::visualization::visualization::Visualization,
+    template::visualization::sea_vis::SeaVis,
 };
 
 // Global imports (needed for the simulation to run)
-use crate::model::sea::Sea;
+use template::model::params::Params;
+use template::model::sea::Sea;
 
-mod model;
-
-#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-mod visualization;
-
-pub static DISCRETIZATION: f32 = 10.0 / 1.5;
-pub static TOROIDAL: bool = true;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+mod cli;
 
 // Main used when only the simulation should run, without any visualization.
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 fn main() {
-    let step = 100;
+    use clap::{CommandFactory, Parser};
 
-    let num_agents = 20;
-    let dim: (f32, f32) = (400., 400.);
+    // Parameters come from their defaults, overridden by the command line (see `--help`)
+    let cli = cli::Cli::parse();
+    let params: Params = cli.params().unwrap_or_else(|e| {
+        cli::Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
 
-    let state = Sea::new(dim, num_agents);
+    let step = params.steps;
+    let reps = params.reps;
 
-    simulate!(state, step, 10);
+    let observer = cli.run.observer(params.seed);
+    let mut state = Sea::new(params);
+    state.observer = observer;
+    match cli.run.checkpoints() {
+        Some(checkpoints) => {
+            if let Err(e) = checkpoints.run(state, step, reps) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            simulate!(state, step, reps);
+        }
+    }
 }
 
 // Main used when a visualization feature is applied.
@@ -37,10 +52,13 @@ fn main() {
 fn main() {
     // Initialize the simulation and its visualization here.
 
-    let num_agents = 10;
-    let dim: (f32, f32) = (400., 400.);
+    let params = Params {
+        num_agents: 10,
+        ..Default::default()
+    };
+    let dim = params.dim();
 
-    let state = Sea::new(dim, num_agents);
+    let state = Sea::new(params);
     Visualization::default()
         .with_window_dimensions(800., 800.)
         .with_simulation_dimensions(dim.0, dim.1)
@@ -1,18 +1,34 @@
 use crate::model::sea::Sea;
 use core::fmt;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use examples_common::checkpoint::Real2DDef;
+use examples_common::rng;
 use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::field_2d::{toroidal_transform, Location2D};
 use krabmaga::engine::location::Real2D;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
 /// The most basic agent should implement Clone, Copy and Agent to be able to be inserted in a Schedule.
 #[derive(Clone, Copy)]
+#[cfg_attr(
+    not(any(feature = "visualization", feature = "visualization_wasm")),
+    derive(Serialize, Deserialize)
+)]
 pub struct Crab {
     pub id: u32,
+    #[cfg_attr(
+        not(any(feature = "visualization", feature = "visualization_wasm")),
+        serde(with = "Real2DDef")
+    )]
     pub loc: Real2D,
+    #[cfg_attr(
+        not(any(feature = "visualization", feature = "visualization_wasm")),
+        serde(with = "Real2DDef")
+    )]
     pub last_d: Real2D,
     pub dir_x: f32,
     pub dir_y: f32,
@@ -22,12 +38,12 @@ impl Agent for Crab {
     /// Put the code that should happen for each step, for each agent here.
     fn step(&mut self, state: &mut dyn State) {
         let state = state.as_any().downcast_ref::<Sea>().unwrap();
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
 
-        if rng.gen_bool(0.5) {
+        if rng.random_bool(0.5) {
             self.dir_x -= 1.0;
         }
-        if rng.gen_bool(0.5) {
+        if rng.random_bool(0.5) {
             self.dir_y -= 1.0;
         }
 
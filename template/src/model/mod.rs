@@ -1,2 +1,3 @@
 pub mod crab;
+pub mod params;
 pub mod sea;
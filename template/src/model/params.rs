@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{DISCRETIZATION, TOROIDAL};
+
+/// Put the parameters of your model here. The state constructor takes this struct, so every value
+/// can be overridden from the command line without recompiling.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    /// Seed of the random number generators, the same seed replays the same run.
+    pub seed: u64,
+    pub width: f32,
+    pub height: f32,
+    pub num_agents: u32,
+    pub discretization: f32,
+    pub toroidal: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 100,
+            reps: 10,
+            seed: 0,
+            width: 400.,
+            height: 400.,
+            num_agents: 20,
+            discretization: DISCRETIZATION,
+            toroidal: TOROIDAL,
+        }
+    }
+}
+
+impl Params {
+    /// Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    /// keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    pub fn dim(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Override a parameter by name, as done by `--param NAME=VALUE`. Add a line for each new
+    /// parameter of your model.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "steps" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "num_agents" | "agents" => self.num_agents = parse(name, value)?,
+            "discretization" => self.discretization = parse(name, value)?,
+            "toroidal" => self.toroidal = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    /// Check that every parameter is in its valid range. Add a check for each new parameter.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width <= 0. || self.height <= 0. {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                self.width, self.height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        if self.discretization <= 0. {
+            return Err(format!(
+                "`discretization` must be positive, found {}",
+                self.discretization
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
@@ -1,12 +1,20 @@
 use std::any::Any;
 
 use super::crab::Crab;
-use crate::{DISCRETIZATION, TOROIDAL};
+use super::params::Params;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use examples_common::checkpoint::{self, Resumable};
+use examples_common::observer::Observer;
+use examples_common::rng;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use krabmaga::engine::agent::Agent;
 use krabmaga::engine::fields::field::Field;
 use krabmaga::{
     engine::{fields::field_2d::Field2D, location::Real2D, schedule::Schedule, state::State},
-    rand::{self, Rng},
+    rand::Rng,
 };
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use serde::{Deserialize, Serialize};
 
 /// Expand the state definition according to your model, for example by having a grid struct field to
 /// store the agents' locations.
@@ -15,17 +23,28 @@ pub struct Sea {
     pub field: Field2D<Crab>,
     pub dim: (f32, f32),
     pub num_agents: u32,
+    pub params: Params,
+    pub observer: Observer,
 }
 
 impl Sea {
-    pub fn new(dim: (f32, f32), num_agents: u32) -> Sea {
+    pub fn new(params: Params) -> Sea {
+        let dim = params.dim();
         Sea {
             step: 0,
-            field: Field2D::new(dim.0, dim.1, DISCRETIZATION, TOROIDAL),
+            field: Field2D::new(dim.0, dim.1, params.discretization, params.toroidal),
             dim,
-            num_agents,
+            num_agents: params.num_agents,
+            params,
+            observer: Observer::new(),
         }
     }
+
+    /// Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    /// `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
 }
 
 impl State for Sea {
@@ -38,19 +57,25 @@ impl State for Sea {
     /// Put the code that should be executed to reset simulation state
     fn reset(&mut self) {
         self.step = 0;
-        self.field = Field2D::new(self.dim.0, self.dim.1, DISCRETIZATION, TOROIDAL);
+        self.field = Field2D::new(
+            self.dim.0,
+            self.dim.1,
+            self.params.discretization,
+            self.params.toroidal,
+        );
     }
 
     /// Put the code that should be executed to initialize simulation:
     /// Agent creation and schedule set-up
     fn init(&mut self, schedule: &mut Schedule) {
         self.step = 0;
+        self.observer.reset();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::state_rng(self.seed());
 
         for i in 0..self.num_agents {
-            let r1: f32 = rng.gen();
-            let r2: f32 = rng.gen();
+            let r1: f32 = rng.random();
+            let r2: f32 = rng.random();
             let last_d = Real2D { x: 0., y: 0. };
             let loc = Real2D {
                 x: self.dim.0 * r1,
@@ -83,7 +108,57 @@ impl State for Sea {
     fn as_state(&self) -> &dyn State {
         self
     }
-    fn after_step(&mut self, _schedule: &mut Schedule) {
-        self.step += 1
+
+    /// Record the metrics of your model here, they are exported when the run is given `--output`.
+    fn after_step(&mut self, schedule: &mut Schedule) {
+        self.step += 1;
+        self.observer.record(
+            schedule.step + 1,
+            &[("crabs", schedule.get_all_events().len() as f64)],
+        );
+    }
+}
+
+/// Everything the sea needs to go on with a run. Extend it with the state your model adds.
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+#[derive(Serialize, Deserialize)]
+pub struct SeaSnapshot {
+    pub params: Params,
+    pub step: u64,
+    pub crabs: Vec<Crab>,
+}
+
+/// Lets a run be checkpointed with `--checkpoint` and resumed with `--resume`.
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+impl Resumable for Sea {
+    type Snapshot = SeaSnapshot;
+    type Agent = Crab;
+
+    fn snapshot(&self) -> SeaSnapshot {
+        SeaSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            crabs: checkpoint::save_objects(&self.field),
+        }
+    }
+
+    fn restore(snapshot: SeaSnapshot) -> Result<Sea, String> {
+        let mut sea = Sea::new(snapshot.params);
+        sea.step = snapshot.step;
+        checkpoint::restore_objects(&sea.field, snapshot.crabs);
+        sea.field.lazy_update();
+        Ok(sea)
+    }
+
+    fn save_agent(agent: &dyn Agent) -> Crab {
+        *agent.downcast_ref::<Crab>().unwrap()
+    }
+
+    fn restore_agent(crab: Crab) -> Box<dyn Agent> {
+        Box::new(crab)
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
     }
 }
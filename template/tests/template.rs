@@ -0,0 +1,105 @@
+use std::path::Path;
+
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use examples_common::checkpoint::{Resumable, ScheduleSnapshot};
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use template::model::crab::Crab;
+use template::model::params::Params;
+use template::model::sea::Sea;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+use template::model::sea::SeaSnapshot;
+
+fn init(params: Params) -> (Sea, Schedule) {
+    let mut state = Sea::new(params);
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    (state, schedule)
+}
+
+fn crabs(schedule: &Schedule) -> Vec<Crab> {
+    let mut crabs: Vec<Crab> = schedule
+        .get_all_events()
+        .iter()
+        .map(|agent| *agent.downcast_ref::<Crab>().unwrap())
+        .collect();
+    crabs.sort_by_key(|crab| crab.id);
+    crabs
+}
+
+// (id, x, y) of every crab, bit for bit
+fn locations(schedule: &Schedule) -> Vec<(u32, u32, u32)> {
+    crabs(schedule)
+        .iter()
+        .map(|crab| (crab.id, crab.loc.x.to_bits(), crab.loc.y.to_bits()))
+        .collect()
+}
+
+#[test]
+fn crabs_are_conserved_and_stay_in_the_sea() {
+    let params = Params::default();
+    let num_agents = params.num_agents as usize;
+    let (mut state, mut schedule) = init(params);
+
+    for _ in 0..100 {
+        schedule.step(&mut state);
+        let crabs = crabs(&schedule);
+        assert_eq!(crabs.len(), num_agents);
+        for crab in crabs {
+            assert!(crab.loc.x >= 0. && crab.loc.x < state.dim.0);
+            assert!(crab.loc.y >= 0. && crab.loc.y < state.dim.1);
+        }
+    }
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut state, mut schedule) = init(Params {
+            seed,
+            ..Default::default()
+        });
+        for _ in 0..50 {
+            schedule.step(&mut state);
+        }
+        locations(&schedule)
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+// Checkpoints are only taken by the headless runs
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let (mut state, mut schedule) = init(Params {
+        seed: 4,
+        ..Default::default()
+    });
+    for _ in 0..20 {
+        schedule.step(&mut state);
+    }
+    let saved = serde_json::to_string(&(
+        state.snapshot(),
+        ScheduleSnapshot::take(&mut schedule, Sea::save_agent),
+    ))
+    .unwrap();
+    let (snapshot, events): (SeaSnapshot, ScheduleSnapshot<Crab>) =
+        serde_json::from_str(&saved).unwrap();
+    let mut resumed = Sea::restore(snapshot).unwrap();
+    let mut resumed_schedule = events.restore(Sea::restore_agent);
+    assert_eq!(locations(&resumed_schedule), locations(&schedule));
+
+    for _ in 0..30 {
+        schedule.step(&mut state);
+        resumed_schedule.step(&mut resumed);
+    }
+    assert_eq!(resumed_schedule.step, schedule.step);
+    assert_eq!(locations(&resumed_schedule), locations(&schedule));
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.toml");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}
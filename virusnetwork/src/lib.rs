@@ -0,0 +1,14 @@
+pub mod model;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+static DISCRETIZATION: f32 = 10.0 / 1.5;
+static TOROIDAL: bool = false;
+///Initial infected nodes
+pub static INITIAL_INFECTED_PROB: f64 = 0.01;
+pub static INIT_EDGES: usize = 2;
+pub static VIRUS_SPREAD_CHANCE: f64 = 0.3;
+pub static VIRUS_CHECK_FREQUENCY: f64 = 0.2;
+pub static RECOVERY_CHANCE: f64 = 0.30;
+pub static GAIN_RESISTANCE_CHANCE: f64 = 0.20;
@@ -1,48 +1,82 @@
-extern crate krabmaga;
-
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 use krabmaga::*;
 
-use model::state::EpidemicNetworkState;
+use virusnetwork::model::params::Params;
+use virusnetwork::model::state::EpidemicNetworkState;
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 use {
-    crate::visualization::vis_state::VisState, krabmaga::bevy::prelude::Color,
-    krabmaga::bevy::prelude::FixedUpdate, krabmaga::visualization::fields::network::NetworkRender,
+    krabmaga::bevy::prelude::Color, krabmaga::bevy::prelude::FixedUpdate,
+    krabmaga::visualization::fields::network::NetworkRender,
     krabmaga::visualization::visualization::Visualization,
+    virusnetwork::visualization::vis_state::VisState,
 };
 
-mod model;
-
-static DISCRETIZATION: f32 = 10.0 / 1.5;
-static TOROIDAL: bool = false;
-///Initial infected nodes
-pub static INITIAL_INFECTED_PROB: f64 = 0.01;
-pub static INIT_EDGES: usize = 2;
-pub static VIRUS_SPREAD_CHANCE: f64 = 0.3;
-pub static VIRUS_CHECK_FREQUENCY: f64 = 0.2;
-pub static RECOVERY_CHANCE: f64 = 0.30;
-pub static GAIN_RESISTANCE_CHANCE: f64 = 0.20;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+mod cli;
 
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 fn main() {
-    let step: u64 = 110;
-    let dim: (f32, f32) = (100., 100.);
-    let num_nodes = 3_000;
-    let epidemic_network = EpidemicNetworkState::new(dim, num_nodes, DISCRETIZATION, TOROIDAL);
+    use clap::{CommandFactory, Parser};
 
-    simulate!(epidemic_network, step, 10);
-}
+    let cli = cli::Cli::parse();
+    let params: Params = cli.params().unwrap_or_else(|e| {
+        cli::Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
 
-#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-mod visualization;
+    let step = params.steps;
+    let reps = params.reps;
+    if let Some(sweep) = cli.sweep.sweep() {
+        let path = cli.run.sweep_path();
+        let result = sweep
+            .run::<EpidemicNetworkState>(&params)
+            .and_then(|summaries| {
+                sweep
+                    .write(&path, &summaries)
+                    .map_err(|e| format!("cannot write `{}`: {}", path.display(), e))?;
+                println!(
+                    "{} runs summarized in `{}`",
+                    summaries.len(),
+                    path.display()
+                );
+                Ok(())
+            });
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let observer = cli.run.observer(params.seed);
+    let mut epidemic_network = EpidemicNetworkState::new(params);
+    epidemic_network.observer = observer;
+    match cli.run.checkpoints() {
+        Some(checkpoints) => {
+            if let Err(e) = checkpoints.run(epidemic_network, step, reps) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            simulate!(epidemic_network, step, reps);
+        }
+    }
+}
 
 // Main used when a visualization feature is applied.
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 fn main() {
     // Initialize the simulation and its visualization here.
-    let dim: (f32, f32) = (500., 500.);
-    let num_nodes = 100;
-    let epidemic_network = EpidemicNetworkState::new(dim, num_nodes, DISCRETIZATION, TOROIDAL);
+    let params = Params {
+        width: 500.,
+        height: 500.,
+        num_nodes: 100,
+        ..Default::default()
+    };
+    let dim = params.dim();
+    let epidemic_network = EpidemicNetworkState::new(params);
 
     let mut app = Visualization::default()
         .with_window_dimensions(1000., 700.)
@@ -4,25 +4,27 @@ use std::{
 };
 
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::{
     engine::{agent::Agent, fields::field_2d::Location2D, location::Real2D},
     rand::Rng,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::model::state::EpidemicNetworkState;
-use crate::{GAIN_RESISTANCE_CHANCE, RECOVERY_CHANCE, VIRUS_CHECK_FREQUENCY, VIRUS_SPREAD_CHANCE};
+use examples_common::checkpoint::Real2DDef;
+use examples_common::rng;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NodeStatus {
     Susceptible,
     Infected,
     Resistant,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct NetNode {
     pub id: u32,
+    #[serde(with = "Real2DDef")]
     pub loc: Real2D,
     pub status: NodeStatus,
     pub virus_detected: bool,
@@ -45,24 +47,21 @@ impl Agent for NetNode {
             .as_any()
             .downcast_ref::<EpidemicNetworkState>()
             .unwrap();
+        let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
 
         match self.status {
             NodeStatus::Infected => {
                 if !self.virus_detected {
                     //Scan Virus
-                    let mut rng = rand::thread_rng();
-                    self.virus_detected = rng.gen_bool(VIRUS_CHECK_FREQUENCY);
+                    self.virus_detected = rng.random_bool(state.params.virus_check_frequency);
                 }
-                if self.virus_detected {
-                    let mut rng = rand::thread_rng();
-                    if rng.gen_bool(RECOVERY_CHANCE) {
-                        self.virus_detected = false;
-
-                        if rng.gen_bool(GAIN_RESISTANCE_CHANCE) {
-                            self.status = NodeStatus::Resistant;
-                        } else {
-                            self.status = NodeStatus::Susceptible;
-                        }
+                if self.virus_detected && rng.random_bool(state.params.recovery_chance) {
+                    self.virus_detected = false;
+
+                    if rng.random_bool(state.params.gain_resistance_chance) {
+                        self.status = NodeStatus::Resistant;
+                    } else {
+                        self.status = NodeStatus::Susceptible;
                     }
                 }
             }
@@ -72,10 +71,11 @@ impl Agent for NetNode {
                 if neighborhood.is_none() {
                     return;
                 };
-                let mut rng = rand::thread_rng();
                 let neighborhood = neighborhood.unwrap();
                 for edge in &neighborhood {
-                    if rng.gen_bool(VIRUS_SPREAD_CHANCE) && self.status == NodeStatus::Susceptible {
+                    if rng.random_bool(state.params.virus_spread_chance)
+                        && self.status == NodeStatus::Susceptible
+                    {
                         let node = state.network.get_object(edge.v).unwrap();
                         match node.status {
                             NodeStatus::Infected => {
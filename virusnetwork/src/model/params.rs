@@ -0,0 +1,169 @@
+use examples_common::sweep::SweepParams;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{
+    DISCRETIZATION, GAIN_RESISTANCE_CHANCE, INITIAL_INFECTED_PROB, INIT_EDGES, RECOVERY_CHANCE,
+    TOROIDAL, VIRUS_CHECK_FREQUENCY, VIRUS_SPREAD_CHANCE,
+};
+
+/// Run settings and epidemic coefficients of a Virus on a Network simulation.
+/// Defaults match the values hard-coded in the original example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    /// Seed of the random number generators, the same seed replays the same run.
+    pub seed: u64,
+    pub width: f32,
+    pub height: f32,
+    pub num_nodes: u32,
+    pub discretization: f32,
+    pub toroidal: bool,
+    pub initial_infected_prob: f64,
+    pub init_edges: usize,
+    pub virus_spread_chance: f64,
+    pub virus_check_frequency: f64,
+    pub recovery_chance: f64,
+    pub gain_resistance_chance: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 110,
+            reps: 10,
+            seed: 0,
+            width: 100.,
+            height: 100.,
+            num_nodes: 3_000,
+            discretization: DISCRETIZATION,
+            toroidal: TOROIDAL,
+            initial_infected_prob: INITIAL_INFECTED_PROB,
+            init_edges: INIT_EDGES,
+            virus_spread_chance: VIRUS_SPREAD_CHANCE,
+            virus_check_frequency: VIRUS_CHECK_FREQUENCY,
+            recovery_chance: RECOVERY_CHANCE,
+            gain_resistance_chance: GAIN_RESISTANCE_CHANCE,
+        }
+    }
+}
+
+impl Params {
+    /// Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    /// keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    pub fn dim(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Override a parameter by name. Names are matched case-insensitively, so both
+    /// `virus_spread_chance` and `VIRUS_SPREAD_CHANCE` are accepted.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_ascii_lowercase().as_str() {
+            "steps" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "num_nodes" | "agents" => self.num_nodes = parse(name, value)?,
+            "discretization" => self.discretization = parse(name, value)?,
+            "toroidal" => self.toroidal = parse(name, value)?,
+            "initial_infected_prob" => self.initial_infected_prob = parse(name, value)?,
+            "init_edges" => self.init_edges = parse(name, value)?,
+            "virus_spread_chance" => self.virus_spread_chance = parse(name, value)?,
+            "virus_check_frequency" => self.virus_check_frequency = parse(name, value)?,
+            "recovery_chance" => self.recovery_chance = parse(name, value)?,
+            "gain_resistance_chance" => self.gain_resistance_chance = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    /// Check that every parameter is in its valid range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width <= 0. || self.height <= 0. {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                self.width, self.height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        if self.discretization <= 0. {
+            return Err(format!(
+                "`discretization` must be positive, found {}",
+                self.discretization
+            ));
+        }
+        if self.init_edges == 0 || self.init_edges >= self.num_nodes as usize {
+            return Err(format!(
+                "`init_edges` must be between 1 and the number of nodes minus one, found {}",
+                self.init_edges
+            ));
+        }
+        probability("initial_infected_prob", self.initial_infected_prob)?;
+        probability("virus_spread_chance", self.virus_spread_chance)?;
+        probability("virus_check_frequency", self.virus_check_frequency)?;
+        probability("recovery_chance", self.recovery_chance)?;
+        probability("gain_resistance_chance", self.gain_resistance_chance)
+    }
+}
+
+impl SweepParams for Params {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    fn reps(&self) -> u64 {
+        self.reps
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        Params::set(self, name, value)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        Params::validate(self)
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
+
+fn probability(name: &str, value: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{}` must be in [0, 1], found {}", name, value))
+    }
+}
@@ -1,12 +1,17 @@
 use crate::model::node::{NetNode, NodeStatus};
-use crate::{INITIAL_INFECTED_PROB, INIT_EDGES};
-use krabmaga::engine::fields::network::Network;
+use crate::model::params::Params;
+use examples_common::checkpoint::Resumable;
+use examples_common::observer::{Observer, TimeSeries};
+use examples_common::rng;
+use examples_common::sweep::Sweepable;
+use krabmaga::engine::agent::Agent;
+use krabmaga::engine::fields::network::{EdgeOptions, Network};
 use krabmaga::engine::fields::{field::Field, field_2d::Field2D};
 use krabmaga::engine::location::Real2D;
 use krabmaga::engine::schedule::Schedule;
 use krabmaga::engine::state::State;
-use krabmaga::rand;
 use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 
 pub struct EpidemicNetworkState {
@@ -17,20 +22,31 @@ pub struct EpidemicNetworkState {
     pub toroidal: bool,
     pub dim: (f32, f32),
     pub num_nodes: u32,
+    pub params: Params,
+    pub observer: Observer,
 }
 
 impl EpidemicNetworkState {
-    pub fn new(dim: (f32, f32), num_nodes: u32, d: f32, t: bool) -> EpidemicNetworkState {
+    pub fn new(params: Params) -> EpidemicNetworkState {
+        let dim = params.dim();
         EpidemicNetworkState {
             step: 0,
-            field1: Field2D::new(dim.0, dim.1, d, t),
+            field1: Field2D::new(dim.0, dim.1, params.discretization, params.toroidal),
             network: Network::new(false),
-            discretization: d,
-            toroidal: t,
+            discretization: params.discretization,
+            toroidal: params.toroidal,
             dim,
-            num_nodes,
+            num_nodes: params.num_nodes,
+            params,
+            observer: Observer::new(),
         }
     }
+
+    /// Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    /// `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
 }
 
 impl State for EpidemicNetworkState {
@@ -38,21 +54,23 @@ impl State for EpidemicNetworkState {
         self.step = 0;
         self.field1 = Field2D::new(self.dim.0, self.dim.1, self.discretization, self.toroidal);
         self.network = Network::new(false);
+        self.observer.reset();
     }
 
     fn init(&mut self, schedule: &mut Schedule) {
         let mut node_set = Vec::new();
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::state_rng(self.seed());
         self.reset();
         for node_id in 0..self.num_nodes {
-            let r1: f32 = rng.gen();
-            let r2: f32 = rng.gen();
+            let r1: f32 = rng.random();
+            let r2: f32 = rng.random();
 
-            let init_status: NodeStatus = if rng.gen_bool(INITIAL_INFECTED_PROB) || node_id == 0 {
-                NodeStatus::Infected
-            } else {
-                NodeStatus::Susceptible
-            };
+            let init_status: NodeStatus =
+                if rng.random_bool(self.params.initial_infected_prob) || node_id == 0 {
+                    NodeStatus::Infected
+                } else {
+                    NodeStatus::Susceptible
+                };
 
             let node = NetNode::new(
                 node_id,
@@ -67,8 +85,11 @@ impl State for EpidemicNetworkState {
             schedule.schedule_repeating(Box::new(node), 0.0, 0);
             node_set.push(node);
         }
-        self.network
-            .preferential_attachment_BA(&node_set, INIT_EDGES);
+        self.network.preferential_attachment_BA_with_seed(
+            &node_set,
+            self.params.init_edges,
+            self.seed(),
+        );
     }
 
     fn update(&mut self, step: u64) {
@@ -93,32 +114,114 @@ impl State for EpidemicNetworkState {
         self
     }
 
-    fn after_step(&mut self, _schedule: &mut Schedule) {
-        // let mut susceptible: usize = 0;
-        // let mut infected: usize = 0;
-        // let mut resistant: usize = 0;
-        // let agents = schedule.get_all_events();
-
-        // for n in agents {
-        //     let agent = n.downcast_ref::<NetNode>().unwrap();
-        //     match agent.status {
-        //         NodeStatus::Susceptible => {
-        //             susceptible += 1;
-        //         }
-        //         NodeStatus::Infected => {
-        //             infected += 1;
-        //         }
-        //         NodeStatus::Resistant => {
-        //             resistant += 1;
-        //         }
-        //     }
-        // }
-        // println!(
-        //     "Susceptible: {:?} Infected: {:?} Resistant: {:?} Tot: {:?}",
-        //     susceptible,
-        //     infected,
-        //     resistant,
-        //     susceptible + infected + resistant
-        // );
+    fn after_step(&mut self, schedule: &mut Schedule) {
+        let mut susceptible: usize = 0;
+        let mut infected: usize = 0;
+        let mut resistant: usize = 0;
+        let agents = schedule.get_all_events();
+
+        for n in agents {
+            let agent = n.downcast_ref::<NetNode>().unwrap();
+            match agent.status {
+                NodeStatus::Susceptible => {
+                    susceptible += 1;
+                }
+                NodeStatus::Infected => {
+                    infected += 1;
+                }
+                NodeStatus::Resistant => {
+                    resistant += 1;
+                }
+            }
+        }
+        self.observer.record(
+            schedule.step + 1,
+            &[
+                ("susceptible", susceptible as f64),
+                ("infected", infected as f64),
+                ("resistant", resistant as f64),
+            ],
+        );
+    }
+}
+
+/// Everything an `EpidemicNetworkState` needs to go on with a run, see `Resumable`.
+#[derive(Serialize, Deserialize)]
+pub struct EpidemicNetworkSnapshot {
+    pub params: Params,
+    pub step: u64,
+    /// Nodes by network id.
+    pub nodes: Vec<NetNode>,
+    /// `(u, v)` of every edge, in the order the nodes see them. The model only adds simple edges.
+    pub edges: Vec<(u32, u32)>,
+}
+
+impl Resumable for EpidemicNetworkState {
+    type Snapshot = EpidemicNetworkSnapshot;
+    type Agent = NetNode;
+
+    fn snapshot(&self) -> EpidemicNetworkSnapshot {
+        let nodes: Vec<NetNode> = (0..self.num_nodes)
+            .map_while(|id| self.network.get_object(id))
+            .collect();
+        let mut edges = Vec::new();
+        for node in &nodes {
+            for edge in self.network.get_edges(*node).unwrap_or_default() {
+                edges.push((edge.u, edge.v));
+            }
+        }
+        EpidemicNetworkSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            nodes,
+            edges,
+        }
+    }
+
+    fn restore(snapshot: EpidemicNetworkSnapshot) -> Result<EpidemicNetworkState, String> {
+        let mut state = EpidemicNetworkState::new(snapshot.params);
+        state.step = snapshot.step;
+        for node in &snapshot.nodes {
+            state.field1.set_object_location(*node, node.loc);
+            state.network.add_node(*node);
+        }
+        // Both directions of an undirected edge are saved: add them one at a time, so that each
+        // node gets its edges back in the same order
+        state.network.direct = true;
+        for (u, v) in snapshot.edges {
+            state.network.add_edge(
+                snapshot.nodes[u as usize],
+                snapshot.nodes[v as usize],
+                EdgeOptions::Simple,
+            );
+        }
+        state.network.direct = false;
+        state.field1.lazy_update();
+        state.network.update();
+        Ok(state)
+    }
+
+    fn save_agent(agent: &dyn Agent) -> NetNode {
+        *agent.downcast_ref::<NetNode>().unwrap()
+    }
+
+    fn restore_agent(node: NetNode) -> Box<dyn Agent> {
+        Box::new(node)
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
+    }
+}
+
+impl Sweepable for EpidemicNetworkState {
+    type Params = Params;
+
+    fn build(params: Params) -> Result<Self, String> {
+        Ok(EpidemicNetworkState::new(params))
+    }
+
+    fn metrics(&self) -> &TimeSeries {
+        self.observer.time_series()
     }
 }
@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use examples_common::checkpoint::{Resumable, ScheduleSnapshot};
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use virusnetwork::model::node::{NetNode, NodeStatus};
+use virusnetwork::model::params::Params;
+use virusnetwork::model::state::{EpidemicNetworkSnapshot, EpidemicNetworkState};
+
+fn small_params(seed: u64) -> Params {
+    Params {
+        num_nodes: 300,
+        seed,
+        ..Default::default()
+    }
+}
+
+fn init(params: Params) -> (EpidemicNetworkState, Schedule) {
+    let mut state = EpidemicNetworkState::new(params);
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    (state, schedule)
+}
+
+fn nodes(schedule: &Schedule) -> Vec<NetNode> {
+    let mut nodes: Vec<NetNode> = schedule
+        .get_all_events()
+        .iter()
+        .map(|agent| *agent.downcast_ref::<NetNode>().unwrap())
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    nodes
+}
+
+#[test]
+fn resistant_nodes_stay_resistant() {
+    let params = small_params(1);
+    let num_nodes = params.num_nodes as usize;
+    let (mut state, mut schedule) = init(params);
+    let mut resistant = vec![false; num_nodes];
+
+    for _ in 0..100 {
+        schedule.step(&mut state);
+        let nodes = nodes(&schedule);
+        assert_eq!(nodes.len(), num_nodes);
+        for node in nodes {
+            let id = node.id as usize;
+            assert!(!resistant[id] || node.status == NodeStatus::Resistant);
+            resistant[id] = node.status == NodeStatus::Resistant;
+        }
+    }
+    assert!(resistant.iter().any(|&r| r));
+}
+
+#[test]
+fn the_first_node_starts_infected() {
+    let (_state, schedule) = init(small_params(2));
+    assert!(nodes(&schedule)[0].status == NodeStatus::Infected);
+}
+
+#[test]
+fn observer_counts_every_node() {
+    let params = small_params(3);
+    let num_nodes = params.num_nodes as f64;
+    let (mut state, mut schedule) = init(params);
+    for _ in 0..20 {
+        schedule.step(&mut state);
+    }
+    let observer = &state.observer;
+    assert_eq!(observer.names(), ["susceptible", "infected", "resistant"]);
+    assert_eq!(observer.steps().len(), 20);
+    for step in 0..20 {
+        let total: f64 = observer
+            .names()
+            .iter()
+            .map(|name| observer.series(name).unwrap()[step])
+            .sum();
+        assert_eq!(total, num_nodes);
+    }
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut state, mut schedule) = init(small_params(seed));
+        for _ in 0..50 {
+            schedule.step(&mut state);
+        }
+        nodes(&schedule)
+            .iter()
+            .map(|node| (node.id, node.status as u8, node.virus_detected))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let statuses = |schedule: &Schedule| {
+        nodes(schedule)
+            .iter()
+            .map(|node| (node.id, node.status as u8, node.virus_detected))
+            .collect::<Vec<_>>()
+    };
+    let (mut state, mut schedule) = init(small_params(3));
+    for _ in 0..20 {
+        schedule.step(&mut state);
+    }
+    let saved = serde_json::to_string(&(
+        state.snapshot(),
+        ScheduleSnapshot::take(&mut schedule, EpidemicNetworkState::save_agent),
+    ))
+    .unwrap();
+    let (snapshot, events): (EpidemicNetworkSnapshot, ScheduleSnapshot<NetNode>) =
+        serde_json::from_str(&saved).unwrap();
+    let mut resumed = EpidemicNetworkState::restore(snapshot).unwrap();
+    let mut resumed_schedule = events.restore(EpidemicNetworkState::restore_agent);
+
+    for _ in 0..30 {
+        schedule.step(&mut state);
+        resumed_schedule.step(&mut resumed);
+    }
+    assert_eq!(statuses(&resumed_schedule), statuses(&schedule));
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.json");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}
@@ -0,0 +1,205 @@
+use crate::model::species::FoodWeb;
+use examples_common::observer::{Output, TimeSeries};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Statistics of the population of a species over a run.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PopulationStats {
+    pub species: String,
+    pub mean: f64,
+    pub variance: f64,
+    /// First step the species was extinct at, if it died out.
+    pub extinction: Option<u64>,
+    /// Dominant period of the oscillations, in steps, measured before the extinction.
+    pub period: Option<f64>,
+}
+
+/// How far the oscillations of a predator lag behind those of its prey.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PhaseLag {
+    pub predator: String,
+    pub prey: String,
+    /// Lag, in steps, at which the population of the predator best follows the one of its prey.
+    pub lag: Option<u64>,
+    /// The lag as a fraction of the period of the prey: 0.25 in the Lotka-Volterra model.
+    pub phase: Option<f64>,
+}
+
+/// End-of-run analysis of the populations, to be compared with the Lotka-Volterra behaviour:
+/// predator and prey oscillating with the same period, the predator a quarter of a cycle behind.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Analysis {
+    pub steps: usize,
+    pub populations: Vec<PopulationStats>,
+    pub lags: Vec<PhaseLag>,
+}
+
+impl Analysis {
+    /// Analyse the population of every species of `web` recorded in `series`, and the lag of
+    /// every predator behind each of its prey.
+    pub fn new(series: &TimeSeries, web: &FoodWeb) -> Analysis {
+        let column = |name: &str| {
+            series
+                .names
+                .iter()
+                .position(|n| n == name)
+                .map_or(&[][..], |i| &series.values[i][..])
+        };
+        let populations: Vec<PopulationStats> = web
+            .species
+            .iter()
+            .map(|species| {
+                let values = column(&species.name);
+                let alive = alive(values);
+                let (mean, variance) = mean_variance(values);
+                PopulationStats {
+                    species: species.name.clone(),
+                    mean,
+                    variance,
+                    extinction: (alive < values.len()).then(|| series.steps[alive]),
+                    period: dominant_period(&values[..alive]),
+                }
+            })
+            .collect();
+
+        let mut lags = Vec::new();
+        for (predator, species) in web.species.iter().enumerate() {
+            for &prey in web.prey(predator) {
+                let (x, y) = (column(&web.species[prey].name), column(&species.name));
+                let n = alive(x).min(alive(y));
+                let period = populations[prey].period;
+                let lag = phase_lag(&x[..n], &y[..n], period);
+                lags.push(PhaseLag {
+                    predator: species.name.clone(),
+                    prey: web.species[prey].name.clone(),
+                    lag: lag.map(|lag| lag as u64),
+                    phase: lag.zip(period).map(|(lag, period)| lag as f64 / period),
+                });
+            }
+        }
+
+        Analysis {
+            steps: series.steps.len(),
+            populations,
+            lags,
+        }
+    }
+
+    /// Where the analysis of repetition `rep` is written:
+    /// `<dir>/<run_id>/analysis_rep_<rep>_seed_<seed>.json`, next to its metrics.
+    pub fn path(output: &Output, rep: u64) -> PathBuf {
+        output
+            .dir
+            .join(&output.run_id)
+            .join(format!("analysis_{}.json", output.stem(rep)))
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut json = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut json, self)?;
+        writeln!(json)?;
+        json.flush()
+    }
+}
+
+/// Mean and (population) variance of a series, both 0 when it is empty. Sums are taken around
+/// the first value, so that a constant series has exactly 0 variance.
+pub fn mean_variance(values: &[f64]) -> (f64, f64) {
+    let Some(&first) = values.first() else {
+        return (0., 0.);
+    };
+    let n = values.len() as f64;
+    let (sum, sum_sq) = values.iter().fold((0., 0.), |(sum, sum_sq), &v| {
+        let d = v - first;
+        (sum + d, sum_sq + d * d)
+    });
+    let shift = sum / n;
+    (first + shift, (sum_sq / n - shift * shift).max(0.))
+}
+
+/// Autocorrelation of a series at lags `0..=max_lag`, around its mean. Empty when the series
+/// does not vary.
+pub fn autocorrelation(values: &[f64], max_lag: usize) -> Vec<f64> {
+    let (mean, variance) = mean_variance(values);
+    if variance == 0. {
+        return Vec::new();
+    }
+    let energy = variance * values.len() as f64;
+    (0..=max_lag.min(values.len().saturating_sub(1)))
+        .map(|lag| {
+            values
+                .iter()
+                .zip(&values[lag..])
+                .map(|(a, b)| (a - mean) * (b - mean))
+                .sum::<f64>()
+                / energy
+        })
+        .collect()
+}
+
+/// Dominant period of the oscillations of a series, from its autocorrelation: the lag of the
+/// highest peak once the autocorrelation has dropped below 0, refined between steps by fitting a
+/// parabola through the peak. `None` when the series does not oscillate at least once over its
+/// first half.
+pub fn dominant_period(values: &[f64]) -> Option<f64> {
+    let r = autocorrelation(values, values.len() / 2);
+    let start = r.iter().position(|&r| r < 0.)?;
+    let peak = (start.max(1)..r.len().saturating_sub(1))
+        .filter(|&k| r[k] > 0. && r[k] >= r[k - 1] && r[k] >= r[k + 1])
+        .max_by(|&a, &b| r[a].total_cmp(&r[b]))?;
+    let (left, mid, right) = (r[peak - 1], r[peak], r[peak + 1]);
+    let curvature = left - 2. * mid + right;
+    let offset = if curvature < 0. {
+        0.5 * (left - right) / curvature
+    } else {
+        0.
+    };
+    Some(peak as f64 + offset)
+}
+
+/// Correlation between `x` and `y` shifted back by `lag` steps, i.e. of `x[t]` with `y[t + lag]`.
+pub fn cross_correlation(x: &[f64], y: &[f64], lag: usize) -> f64 {
+    let n = x.len().min(y.len());
+    if lag >= n {
+        return 0.;
+    }
+    let (x, y) = (&x[..n - lag], &y[lag..n]);
+    let (mx, vx) = mean_variance(x);
+    let (my, vy) = mean_variance(y);
+    if vx == 0. || vy == 0. {
+        return 0.;
+    }
+    let covariance = x
+        .iter()
+        .zip(y)
+        .map(|(a, b)| (a - mx) * (b - my))
+        .sum::<f64>()
+        / x.len() as f64;
+    covariance / (vx * vy).sqrt()
+}
+
+/// Lag, in steps, at which the `predator` series best correlates with the `prey` one. It is
+/// looked for within one period of the prey when known, otherwise within half the series.
+pub fn phase_lag(prey: &[f64], predator: &[f64], period: Option<f64>) -> Option<usize> {
+    let n = prey.len().min(predator.len());
+    let max_lag = match period {
+        Some(period) => (period.round() as usize).min(n / 2),
+        None => n / 2,
+    };
+    (0..max_lag)
+        .map(|lag| (lag, cross_correlation(prey, predator, lag)))
+        .filter(|(_, r)| *r > 0.)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(lag, _)| lag)
+}
+
+// Number of values before the series first reaches 0.
+fn alive(values: &[f64]) -> usize {
+    values.iter().position(|&v| v == 0.).unwrap_or(values.len())
+}
@@ -0,0 +1,16 @@
+pub mod analysis;
+#[cfg(feature = "snapshot")]
+pub mod frames;
+pub mod model;
+pub mod palette;
+
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+pub mod visualization;
+
+pub const ENERGY_CONSUME: f64 = 1.0;
+pub const GRASS_REGROWTH_TIME: u16 = 20;
+pub const GAIN_ENERGY_SHEEP: f64 = 4.0;
+pub const GAIN_ENERGY_WOLF: f64 = 20.0;
+pub const SHEEP_REPR: f64 = 0.2;
+pub const WOLF_REPR: f64 = 0.1;
+pub const MOMENTUM_PROBABILITY: f64 = 0.8;
@@ -3,51 +3,89 @@ use krabmaga::*;
 
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 use {
-    crate::visualization::vis_state::VisState, krabmaga::bevy::prelude::Color,
-    krabmaga::bevy::prelude::FixedUpdate,
+    krabmaga::bevy::prelude::Color, krabmaga::bevy::prelude::FixedUpdate,
     krabmaga::engine::fields::dense_number_grid_2d::DenseNumberGrid2D,
     krabmaga::visualization::fields::number_grid_2d::BatchRender,
     krabmaga::visualization::visualization::Visualization,
+    wolfsheepgrass::visualization::vis_state::VisState,
 };
 
-use crate::model::state::WsgState;
-
-mod model;
-
-pub const ENERGY_CONSUME: f64 = 1.0;
-
-pub const FULL_GROWN: u16 = 20;
-
-pub const GAIN_ENERGY_SHEEP: f64 = 4.0;
-pub const GAIN_ENERGY_WOLF: f64 = 20.0;
-
-pub const SHEEP_REPR: f64 = 0.2;
-pub const WOLF_REPR: f64 = 0.1;
+#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+use wolfsheepgrass::model::params::Params;
+use wolfsheepgrass::model::state::WsgState;
 
-pub const MOMENTUM_PROBABILITY: f64 = 0.8;
+#[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+mod cli;
 
 #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
 fn main() {
-    let step = 200;
+    use clap::{CommandFactory, Parser};
 
-    let dim: (i32, i32) = (50, 50);
-    let initial_animals: (u32, u32) = ((200. * 0.6) as u32, (200. * 0.4) as u32);
+    let cli = cli::Cli::parse();
+    let params = cli.params().unwrap_or_else(|e| {
+        cli::Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit()
+    });
 
-    let state = WsgState::new(dim, initial_animals);
-    let _ = simulate!(state, step, 10);
-}
+    let step = params.steps;
+    let reps = params.reps;
 
-#[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
-mod visualization;
+    if let Some(sweep) = cli.sweep.sweep() {
+        let path = cli.run.sweep_path();
+        let result = sweep.run::<WsgState>(&params).and_then(|summaries| {
+            sweep
+                .write(&path, &summaries)
+                .map_err(|e| format!("cannot write `{}`: {}", path.display(), e))?;
+            println!(
+                "{} runs summarized in `{}`",
+                summaries.len(),
+                path.display()
+            );
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let observer = cli.run.observer(params.seed);
+    let mut state = WsgState::new(params).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    });
+    state.observer = observer;
+    #[cfg(feature = "snapshot")]
+    {
+        state.frames = cli.frames();
+    }
+    match cli.run.checkpoints() {
+        Some(checkpoints) => {
+            if let Err(e) = checkpoints.run(state, step, reps) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            simulate!(state, step, reps);
+        }
+    }
+}
 
 // Main used when a visualization feature is applied
 #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
 fn main() {
-    let dim: (i32, i32) = (25, 25);
-
-    let initial_animals: (u32, u32) = ((60. * 0.6) as u32, (60. * 0.4) as u32);
+    let mut params = Params {
+        width: 25,
+        height: 25,
+        ..Default::default()
+    };
+    params.set_agents(60);
+    let dim = params.dim();
 
-    let state = WsgState::new(dim, initial_animals);
+    let state = WsgState::new(params).unwrap();
     let mut app = Visualization::default()
         .with_background_color(Color::rgb(255., 255., 255.))
         .with_simulation_dimensions(dim.0 as f32, dim.1 as f32)
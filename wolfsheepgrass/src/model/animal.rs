@@ -0,0 +1,205 @@
+use crate::model::genome::Genome;
+use crate::model::lifecycle::{Cause, Individual};
+use crate::model::state::{LifeState, WsgState};
+use examples_common::checkpoint::{option_int2d, Int2DDef};
+use examples_common::rng;
+
+use core::fmt;
+use krabmaga::engine::agent::Agent;
+use krabmaga::engine::location::Int2D;
+use krabmaga::engine::state::State;
+use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// An animal of any species of the food web. What it eats, and how it lives and moves, is looked
+/// up in its species.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Animal {
+    pub id: u32,
+    /// Index of the species in the food web.
+    pub species: usize,
+    pub animal_state: LifeState,
+    #[serde(with = "Int2DDef")]
+    pub loc: Int2D,
+    #[serde(with = "option_int2d")]
+    pub last: Option<Int2D>,
+    pub energy: f64,
+    /// Steps lived so far.
+    pub age: u32,
+    pub genome: Genome,
+}
+
+impl Animal {
+    pub fn new(id: u32, species: usize, loc: Int2D, energy: f64, genome: Genome) -> Animal {
+        Animal {
+            id,
+            species,
+            loc,
+            last: None,
+            energy,
+            age: 0,
+            genome,
+            animal_state: LifeState::Alive,
+        }
+    }
+
+    fn die(&mut self, cause: Cause, state: &mut WsgState) {
+        self.animal_state = LifeState::Dead;
+        state.lifecycles[self.species].death(*self, cause, &state.layers[self.species]);
+    }
+
+    #[allow(dead_code)]
+    pub fn as_agent(self) -> Box<dyn Agent> {
+        Box::new(self)
+    }
+}
+
+impl Agent for Animal {
+    fn step(&mut self, state: &mut dyn State) {
+        let state = state.as_any_mut().downcast_mut::<WsgState>().unwrap();
+        let web = Arc::clone(&state.food_web);
+        let me = self.species;
+        let species = &web.species[me];
+        // CHECK IF I AM DEAD
+        if self.animal_state == LifeState::Dead {
+            return;
+        }
+        // an animal eaten earlier in the step does not act, and is not scheduled again
+        if state.lifecycles[me].is_dead(self) {
+            self.animal_state = LifeState::Dead;
+            return;
+        }
+        self.age += 1;
+        if species.max_age > 0 && self.age > species.max_age {
+            self.die(Cause::OldAge, state);
+            return;
+        }
+
+        //MOVE
+        let mut rng = rng::agent_rng(state.seed(), self.id as u64, state.step);
+        let vision = self.genome.vision.round() as i32;
+        // Fleeing the nearest predator comes before looking for food
+        let mut to = None;
+        if species.flees {
+            to = state
+                .world
+                .nearest(self.loc, vision, |cell| {
+                    web.predators(me)
+                        .iter()
+                        .any(|&predator| !state.animals_at(predator, cell, me).is_empty())
+                })
+                .and_then(|predator| state.world.away(self.loc, predator));
+        }
+        if to.is_none() && species.seeks_food {
+            let grass = |cell: &Int2D| {
+                web.grazes(me)
+                    && matches!(state.grass_field.get_value(cell), Some(grass) if grass.grown)
+            };
+            let prey = |cell: &Int2D| {
+                web.prey(me)
+                    .iter()
+                    .any(|&prey| !state.animals_at(prey, cell, me).is_empty())
+            };
+            to = state
+                .world
+                .nearest(self.loc, vision, |cell| grass(cell) || prey(cell))
+                .map(|food| state.world.toward(self.loc, food));
+        }
+        let to = to.unwrap_or_else(|| {
+            state
+                .world
+                .wander(self.loc, self.last, self.genome.momentum, &mut rng)
+        });
+        self.last = Some(self.loc);
+        self.loc = to;
+
+        state.layers[me].set_object_location(*self, &self.loc);
+
+        //EAT
+        // prey first, in the order of the diet, then grass
+        let mut eaten = false;
+        for &prey in web.prey(me) {
+            if let Some(mut victim) = state.animals_at(prey, &self.loc, me).first().copied() {
+                victim.animal_state = LifeState::Dead;
+                state.lifecycles[prey].death(victim, Cause::Predation, &state.layers[prey]);
+                self.energy += species.gain_energy;
+                eaten = true;
+                break;
+            }
+        }
+        let grazed = state.grass_field.get_value_unbuffered(&self.loc).is_some();
+        if !eaten && web.grazes(me) && !grazed {
+            if let Some(grass) = state.grass_field.get_value(&self.loc) {
+                if grass.grown {
+                    state
+                        .grass_field
+                        .set_value_location(grass.eaten(), &self.loc);
+                    self.energy += species.gain_energy;
+                }
+            }
+        }
+
+        //UPDATE ENERGY
+        self.energy -= self.genome.metabolism;
+        if self.energy <= 0.0 {
+            self.die(Cause::Starvation, state);
+        } else {
+            //REPRODUCE
+            if self.energy >= self.genome.reproduction_threshold
+                && rng.random_bool(species.prob_reproduction)
+            {
+                self.energy /= 2.0;
+
+                let newborn = Animal::new(
+                    state.next_id,
+                    me,
+                    self.loc,
+                    self.energy,
+                    self.genome.inherit(&state.params, &mut rng),
+                );
+
+                state.next_id += 1;
+                state.lifecycles[me].birth(newborn, &state.layers[me]);
+            }
+        }
+    }
+
+    fn is_stopped(&mut self, _state: &mut dyn State) -> bool {
+        self.animal_state == LifeState::Dead
+    }
+}
+
+impl Individual for Animal {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn loc(&self) -> Int2D {
+        self.loc
+    }
+}
+
+impl Eq for Animal {}
+
+impl PartialEq for Animal {
+    fn eq(&self, other: &Animal) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Hash for Animal {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.id.hash(state);
+    }
+}
+
+impl fmt::Display for Animal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
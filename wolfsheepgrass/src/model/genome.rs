@@ -0,0 +1,123 @@
+use crate::model::params::Params;
+use examples_common::rng::ModelRng;
+use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Names of the genes, in the order of `Genome::genes`.
+pub const GENES: [&str; 4] = ["momentum", "reproduction_threshold", "metabolism", "vision"];
+
+/// Heritable traits of an animal. The first animals get the genome of their species, and each
+/// newborn inherits the genome of its parent with some mutations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    /// Probability of moving on in the same direction as the last step.
+    pub momentum: f64,
+    /// Energy an animal needs before it can reproduce.
+    pub reproduction_threshold: f64,
+    /// Energy spent at every step.
+    pub metabolism: f64,
+    /// Perception radius, in cells.
+    pub vision: f64,
+}
+
+impl Genome {
+    pub fn genes(&self) -> [f64; 4] {
+        [
+            self.momentum,
+            self.reproduction_threshold,
+            self.metabolism,
+            self.vision,
+        ]
+    }
+
+    /// Genome of a newborn. Each gene mutates with probability `mutation_rate`, shifting by a
+    /// uniform amount of up to `mutation_size` times its value (times 1 for values below 1).
+    pub fn inherit(&self, params: &Params, rng: &mut ModelRng) -> Genome {
+        if params.mutation_rate == 0. {
+            return *self;
+        }
+        let mut mutate = |gene: f64| {
+            if rng.random_bool(params.mutation_rate) {
+                let shift = params.mutation_size * gene.abs().max(1.);
+                (gene + rng.random_range(-1.0..=1.0) * shift).max(0.)
+            } else {
+                gene
+            }
+        };
+        Genome {
+            momentum: mutate(self.momentum).min(1.),
+            reproduction_threshold: mutate(self.reproduction_threshold),
+            metabolism: mutate(self.metabolism),
+            vision: mutate(self.vision),
+        }
+    }
+}
+
+/// Mean and standard deviation of the genes of a population, kept up to date one animal at a
+/// time as they are born and die. The sums are taken around the first genome, so a population
+/// that does not vary has exactly its genes as mean and 0 as deviation.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenePool {
+    count: usize,
+    first: [f64; 4],
+    sum: [f64; 4],
+    sum_sq: [f64; 4],
+}
+
+impl GenePool {
+    pub fn add(&mut self, genome: &Genome) {
+        if self.count == 0 {
+            self.first = genome.genes();
+        }
+        self.count += 1;
+        for (i, gene) in genome.genes().into_iter().enumerate() {
+            let d = gene - self.first[i];
+            self.sum[i] += d;
+            self.sum_sq[i] += d * d;
+        }
+    }
+
+    /// Take out the genome of an animal added before, once it died. An emptied pool starts
+    /// afresh from the next genome added.
+    pub fn remove(&mut self, genome: &Genome) {
+        self.count -= 1;
+        if self.count == 0 {
+            *self = GenePool::default();
+            return;
+        }
+        for (i, gene) in genome.genes().into_iter().enumerate() {
+            let d = gene - self.first[i];
+            self.sum[i] -= d;
+            self.sum_sq[i] -= d * d;
+        }
+    }
+
+    /// `(mean, standard deviation)` of each gene. Both are 0 for an empty population, which keeps
+    /// the exported metrics and the checkpoints free of NaN.
+    pub fn stats(&self) -> [(f64, f64); 4] {
+        let n = self.count as f64;
+        let mut stats = [(0., 0.); 4];
+        if self.count > 0 {
+            for (i, stat) in stats.iter_mut().enumerate() {
+                let shift = self.sum[i] / n;
+                let variance = (self.sum_sq[i] / n - shift * shift).max(0.);
+                *stat = (self.first[i] + shift, variance.sqrt());
+            }
+        }
+        stats
+    }
+
+    /// Metrics `<species>_<gene>_mean` and `<species>_<gene>_sd` of the population.
+    pub fn metrics(&self, species: &str) -> Vec<(String, f64)> {
+        GENES
+            .iter()
+            .zip(self.stats())
+            .flat_map(|(gene, (mean, sd))| {
+                [
+                    (format!("{}_{}_mean", species, gene), mean),
+                    (format!("{}_{}_sd", species, gene), sd),
+                ]
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,64 @@
+use super::raster;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Grass of a patch, as in the NetLogo Wolf Sheep Predation model: it is either grown, and can be
+/// eaten, or bare. Bare grass counts down one step at a time and grows back when the countdown is
+/// over, so it takes `regrowth` steps to come back after it is eaten.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grass {
+    pub grown: bool,
+    /// Steps left before bare grass grows back.
+    pub countdown: u16,
+    /// Regrowth time of the patch.
+    pub regrowth: u16,
+}
+
+impl Grass {
+    pub fn grown(regrowth: u16) -> Grass {
+        Grass {
+            grown: true,
+            countdown: regrowth,
+            regrowth,
+        }
+    }
+
+    pub fn bare(countdown: u16, regrowth: u16) -> Grass {
+        Grass {
+            grown: false,
+            countdown,
+            regrowth,
+        }
+    }
+
+    /// The grass one step later.
+    pub fn grow(self) -> Grass {
+        match self {
+            Grass { grown: true, .. } => self,
+            Grass { countdown: 0, .. } => Grass::grown(self.regrowth),
+            _ => Grass::bare(self.countdown - 1, self.regrowth),
+        }
+    }
+
+    /// The grass `ticks` steps of its countdown later, at once. Grown grass stays as it is.
+    pub fn grow_by(self, ticks: u16) -> Grass {
+        match self {
+            Grass { grown: true, .. } => self,
+            Grass { countdown, .. } if countdown < ticks => Grass::grown(self.regrowth),
+            _ => Grass::bare(self.countdown - ticks, self.regrowth),
+        }
+    }
+
+    /// The patch once a sheep has eaten its grass.
+    pub fn eaten(self) -> Grass {
+        Grass::bare(self.regrowth, self.regrowth)
+    }
+}
+
+/// Read the regrowth times of the patches from a raster (see `raster::read`) with one positive
+/// number per patch.
+pub fn read_raster(path: &Path, width: i32, height: i32) -> Result<Vec<u16>, String> {
+    raster::read(path, width, height, "grass raster", "regrowth time", |v| {
+        v.parse().ok().filter(|&t| t > 0)
+    })
+}
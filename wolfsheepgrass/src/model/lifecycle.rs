@@ -0,0 +1,110 @@
+use krabmaga::engine::agent::Agent;
+use krabmaga::engine::fields::dense_object_grid_2d::DenseGrid2D;
+use krabmaga::engine::location::Int2D;
+use krabmaga::engine::schedule::Schedule;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// Why an animal died.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cause {
+    /// Its energy ran out.
+    Starvation,
+    /// It was eaten.
+    Predation,
+    /// It outlived the maximum age of its species.
+    OldAge,
+}
+
+pub const CAUSES: [Cause; 3] = [Cause::Starvation, Cause::Predation, Cause::OldAge];
+
+impl Cause {
+    pub fn name(self) -> &'static str {
+        match self {
+            Cause::Starvation => "starvation",
+            Cause::Predation => "predation",
+            Cause::OldAge => "old_age",
+        }
+    }
+}
+
+/// An animal of the field, as seen by its `Lifecycle`.
+pub trait Individual: Agent + Copy + Eq + Hash {
+    /// Id of the animal, which is also the id the schedule knows it by.
+    fn id(&self) -> u32;
+    fn loc(&self) -> Int2D;
+}
+
+/// Births and deaths of a species during a step. Both go through here, so that the grid of the
+/// species is updated as soon as they happen and the schedule once the step is over.
+pub struct Lifecycle<A> {
+    /// Newborns, already on the grid and waiting to be scheduled.
+    pub born: Vec<A>,
+    /// Dead animals, already off the grid, with the cause of their death.
+    pub dead: Vec<(A, Cause)>,
+    /// Ids of the dead, so that `is_dead` does not go through them for every animal it is asked
+    /// about.
+    dead_ids: HashSet<u32>,
+}
+
+impl<A> Default for Lifecycle<A> {
+    fn default() -> Self {
+        Lifecycle {
+            born: Vec::new(),
+            dead: Vec::new(),
+            dead_ids: HashSet::new(),
+        }
+    }
+}
+
+impl<A: Individual> Lifecycle<A> {
+    pub fn birth(&mut self, animal: A, grid: &DenseGrid2D<A>) {
+        grid.set_object_location(animal, &animal.loc());
+        self.born.push(animal);
+    }
+
+    /// Record a death and take the animal off the cell it was written to during this step.
+    pub fn death(&mut self, animal: A, cause: Cause, grid: &DenseGrid2D<A>) {
+        grid.remove_object_location(animal, &animal.loc());
+        self.dead.push((animal, cause));
+        self.dead_ids.insert(animal.id());
+    }
+
+    pub fn is_dead(&self, animal: &A) -> bool {
+        self.dead_ids.contains(&animal.id())
+    }
+
+    pub fn deaths(&self, cause: Cause) -> usize {
+        self.dead.iter().filter(|(_, c)| *c == cause).count()
+    }
+
+    /// Take the dead out of the schedule. Animals that died in their own step were not queued
+    /// again, the others (e.g. eaten after they moved) are still waiting for the next step.
+    /// They are dequeued in id order: removals reshape the schedule queue, so any order that
+    /// changes from run to run would also change the stepping order of the next steps.
+    pub fn bury(&self, schedule: &mut Schedule) {
+        let mut dead: Vec<A> = self.dead.iter().map(|(animal, _)| *animal).collect();
+        dead.sort_by_key(|animal| animal.id());
+        for animal in dead {
+            schedule.dequeue(Box::new(animal), animal.id());
+        }
+    }
+
+    /// Metrics `born_<species>` and `dead_<species>_<cause>` of the step.
+    pub fn metrics(&self, species: &str) -> Vec<(String, f64)> {
+        let mut metrics = vec![(format!("born_{}", species), self.born.len() as f64)];
+        for cause in CAUSES {
+            metrics.push((
+                format!("dead_{}_{}", species, cause.name()),
+                self.deaths(cause) as f64,
+            ));
+        }
+        metrics
+    }
+
+    pub fn clear(&mut self) {
+        self.born.clear();
+        self.dead.clear();
+        self.dead_ids.clear();
+    }
+}
@@ -1,3 +1,12 @@
-pub mod sheep;
+pub mod animal;
+pub mod genome;
+pub mod grass;
+pub mod lifecycle;
+pub mod movement;
+pub mod params;
+pub mod raster;
+pub mod rng;
+pub mod season;
+pub mod species;
 pub mod state;
-pub mod wolf;
+pub mod terrain;
@@ -0,0 +1,273 @@
+use crate::model::params::Params;
+use crate::model::terrain::Terrain;
+use examples_common::rng::ModelRng;
+use krabmaga::engine::location::Int2D;
+use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// What happens at the edges of the field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topology {
+    /// Moves that would leave the field are not taken, as in the original example.
+    Bounded,
+    /// Opposite edges are joined, as in the NetLogo model.
+    Toroidal,
+    /// Moves bounce back off the edges.
+    Reflecting,
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Topology, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "bounded" => Ok(Topology::Bounded),
+            "toroidal" | "torus" => Ok(Topology::Toroidal),
+            "reflecting" => Ok(Topology::Reflecting),
+            _ => Err(format!(
+                "unknown topology `{}`, expected bounded, toroidal or reflecting",
+                s
+            )),
+        }
+    }
+}
+
+/// Cells an animal can reach in one step, and how far it sees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighbourhood {
+    /// The 8 surrounding cells, distances are counted in king moves.
+    Moore,
+    /// The 4 orthogonal cells, distances are counted in rook moves of one cell.
+    VonNeumann,
+}
+
+impl FromStr for Neighbourhood {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Neighbourhood, String> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "moore" => Ok(Neighbourhood::Moore),
+            "von_neumann" => Ok(Neighbourhood::VonNeumann),
+            _ => Err(format!(
+                "unknown neighbourhood `{}`, expected moore or von_neumann",
+                s
+            )),
+        }
+    }
+}
+
+const VON_NEUMANN: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Geometry of the field, shared by the movement of every animal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct World {
+    pub dim: (i32, i32),
+    pub topology: Topology,
+    pub neighbourhood: Neighbourhood,
+    /// Land of the cells: animals never step into water.
+    pub terrain: Terrain,
+}
+
+impl World {
+    pub fn new(params: &Params, terrain: Terrain) -> World {
+        World {
+            dim: params.dim(),
+            topology: params.topology,
+            neighbourhood: params.neighbourhood,
+            terrain,
+        }
+    }
+
+    /// Cell reached by moving to `(x, y)` from inside the field, or `None` when a bounded field
+    /// does not allow it or the cell is water.
+    pub fn place(&self, x: i32, y: i32) -> Option<Int2D> {
+        let (w, h) = self.dim;
+        let cell = match self.topology {
+            Topology::Bounded => (x >= 0 && x < w && y >= 0 && y < h).then_some(Int2D { x, y }),
+            Topology::Toroidal => Some(Int2D {
+                x: x.rem_euclid(w),
+                y: y.rem_euclid(h),
+            }),
+            Topology::Reflecting => Some(Int2D {
+                x: reflect(x, w),
+                y: reflect(y, h),
+            }),
+        };
+        cell.filter(|cell| self.terrain.passable(cell))
+    }
+
+    /// Offset from `from` to `to`, the shortest one across the edges of a toroidal field.
+    pub fn delta(&self, from: Int2D, to: Int2D) -> (i32, i32) {
+        let (mut dx, mut dy) = (to.x - from.x, to.y - from.y);
+        if self.topology == Topology::Toroidal {
+            dx = shortest(dx, self.dim.0);
+            dy = shortest(dy, self.dim.1);
+        }
+        (dx, dy)
+    }
+
+    /// Random walk of the original model: with probability `momentum` an animal goes on in the
+    /// direction of its last step, otherwise (or when that is not allowed) it steps to a random
+    /// neighbouring cell. An animal that draws a cell of water stays where it is.
+    pub fn wander(
+        &self,
+        loc: Int2D,
+        last: Option<Int2D>,
+        momentum: f64,
+        rng: &mut ModelRng,
+    ) -> Int2D {
+        if let Some(last) = last {
+            if rng.random_bool(momentum) {
+                let (dx, dy) = self.delta(last, loc);
+                if let Some(to) = self.place(loc.x + dx, loc.y + dy) {
+                    return to;
+                }
+            }
+        }
+
+        let Int2D { x, y } = loc;
+        match (self.neighbourhood, self.topology) {
+            (Neighbourhood::Moore, Topology::Bounded) => {
+                let xmin = if x > 0 { -1 } else { 0 };
+                let xmax = i32::from(x < self.dim.0 - 1);
+                let ymin = if y > 0 { -1 } else { 0 };
+                let ymax = i32::from(y < self.dim.1 - 1);
+                let nx = rng.random_range(xmin..=xmax);
+                let ny = rng.random_range(ymin..=ymax);
+                let to = Int2D {
+                    x: x + nx,
+                    y: y + ny,
+                };
+                if self.terrain.passable(&to) {
+                    to
+                } else {
+                    loc
+                }
+            }
+            (Neighbourhood::Moore, _) => {
+                let nx = rng.random_range(-1..=1);
+                let ny = rng.random_range(-1..=1);
+                self.place(x + nx, y + ny).unwrap_or(loc)
+            }
+            (Neighbourhood::VonNeumann, _) => {
+                let moves: Vec<Int2D> = VON_NEUMANN
+                    .iter()
+                    .filter_map(|(dx, dy)| self.place(x + dx, y + dy))
+                    .collect();
+                if moves.is_empty() {
+                    loc
+                } else {
+                    moves[rng.random_range(0..moves.len())]
+                }
+            }
+        }
+    }
+
+    /// Distance between two cells, in steps of the neighbourhood.
+    pub fn distance(&self, from: Int2D, to: Int2D) -> i32 {
+        let (dx, dy) = self.delta(from, to);
+        match self.neighbourhood {
+            Neighbourhood::Moore => dx.abs().max(dy.abs()),
+            Neighbourhood::VonNeumann => dx.abs() + dy.abs(),
+        }
+    }
+
+    /// Nearest cell within `radius` steps of `loc` where `found` holds, or `None`. Cells are
+    /// searched ring by ring, and each ring in a fixed order, so ties always resolve the same
+    /// way. Animals do not see across the edges of a field that is not toroidal.
+    pub fn nearest(
+        &self,
+        loc: Int2D,
+        radius: i32,
+        mut found: impl FnMut(&Int2D) -> bool,
+    ) -> Option<Int2D> {
+        // a ring larger than a toroidal field would only visit the same cells again
+        let radius = match self.topology {
+            Topology::Toroidal => radius.min(self.dim.0.max(self.dim.1)),
+            _ => radius,
+        };
+        for d in 0..=radius {
+            for dx in -d..=d {
+                for dy in -d..=d {
+                    let ring = match self.neighbourhood {
+                        Neighbourhood::Moore => dx.abs().max(dy.abs()),
+                        Neighbourhood::VonNeumann => dx.abs() + dy.abs(),
+                    };
+                    if ring != d {
+                        continue;
+                    }
+                    let (x, y) = (loc.x + dx, loc.y + dy);
+                    let cell = match self.topology {
+                        Topology::Toroidal => self.place(x, y),
+                        _ => (x >= 0 && x < self.dim.0 && y >= 0 && y < self.dim.1)
+                            .then_some(Int2D { x, y }),
+                    };
+                    if let Some(cell) = cell.filter(|cell| found(cell)) {
+                        return Some(cell);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Neighbouring cell one step closer to `target`. Water in the way stops the animal: it
+    /// does not look for a way around.
+    pub fn toward(&self, loc: Int2D, target: Int2D) -> Int2D {
+        let (dx, dy) = self.step(self.delta(loc, target));
+        self.place(loc.x + dx, loc.y + dy).unwrap_or(loc)
+    }
+
+    /// Neighbouring cell one step further from `threat`. `None` when there is no way to get
+    /// further, e.g. when the threat is on the same cell or the animal is cornered, also against
+    /// water.
+    pub fn away(&self, loc: Int2D, threat: Int2D) -> Option<Int2D> {
+        let (dx, dy) = self.delta(loc, threat);
+        let (sx, sy) = self.step((-dx, -dy));
+        let to = match self.topology {
+            Topology::Bounded => Int2D {
+                x: (loc.x + sx).clamp(0, self.dim.0 - 1),
+                y: (loc.y + sy).clamp(0, self.dim.1 - 1),
+            },
+            _ => self.place(loc.x + sx, loc.y + sy)?,
+        };
+        (to != loc
+            && self.terrain.passable(&to)
+            && self.distance(to, threat) > self.distance(loc, threat))
+        .then_some(to)
+    }
+
+    // Single step along an offset: diagonal in a Moore neighbourhood, along the longer axis in a
+    // von Neumann one.
+    fn step(&self, (dx, dy): (i32, i32)) -> (i32, i32) {
+        match self.neighbourhood {
+            Neighbourhood::Moore => (dx.signum(), dy.signum()),
+            Neighbourhood::VonNeumann if dx.abs() >= dy.abs() => (dx.signum(), 0),
+            Neighbourhood::VonNeumann => (0, dy.signum()),
+        }
+    }
+}
+
+fn reflect(x: i32, size: i32) -> i32 {
+    if size == 1 {
+        0
+    } else if x < 0 {
+        -x
+    } else if x >= size {
+        2 * (size - 1) - x
+    } else {
+        x
+    }
+}
+
+fn shortest(d: i32, size: i32) -> i32 {
+    let d = d.rem_euclid(size);
+    if d > size / 2 {
+        d - size
+    } else {
+        d
+    }
+}
@@ -0,0 +1,234 @@
+use examples_common::sweep::SweepParams;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::grass;
+use super::movement::{Neighbourhood, Topology};
+use super::season::Season;
+use super::species::{self, FoodWeb, Species};
+use super::terrain::Terrain;
+use crate::GRASS_REGROWTH_TIME;
+
+/// Run settings and model coefficients of a Wolf Sheep Grass simulation.
+/// Defaults match the values hard-coded in the original example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub steps: u64,
+    pub reps: u64,
+    /// Seed of the random number generators, the same seed replays the same run.
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    /// What happens at the edges of the field.
+    pub topology: Topology,
+    /// Cells an animal reaches in one step, also used to measure how far it sees.
+    #[serde(alias = "neighborhood")]
+    pub neighbourhood: Neighbourhood,
+    /// Sheep of the run, a partial `[sheep]` table keeping the values of the original model.
+    #[serde(deserialize_with = "species::sheep")]
+    pub sheep: Species,
+    /// Wolves of the run, see `sheep`.
+    #[serde(deserialize_with = "species::wolves")]
+    pub wolves: Species,
+    /// Steps eaten grass takes to grow back.
+    #[serde(alias = "full_grown")]
+    pub grass_regrowth_time: u16,
+    /// Raster with the regrowth time of every patch, replacing `grass_regrowth_time`.
+    pub grass_regrowth_raster: Option<PathBuf>,
+    /// Seasons of the year, changing how fast grass grows back and how much of it the field
+    /// holds. Without seasons, grass grows the same all year round.
+    pub seasons: Vec<Season>,
+    /// Raster or PNG image of the fertile, barren and water cells of the field. Without it, the
+    /// whole field is fertile.
+    pub terrain: Option<PathBuf>,
+    /// Probability that each gene of a newborn mutates; with 0 the traits never evolve.
+    pub mutation_rate: f64,
+    /// Largest shift of a mutation, relative to the value of the gene.
+    pub mutation_size: f64,
+    /// Species added to the sheep and the wolves, stepped after them in the order they are listed.
+    pub species: Vec<Species>,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 200,
+            reps: 10,
+            seed: 0,
+            width: 50,
+            height: 50,
+            topology: Topology::Bounded,
+            neighbourhood: Neighbourhood::Moore,
+            sheep: Species::sheep(),
+            wolves: Species::wolves(),
+            grass_regrowth_time: GRASS_REGROWTH_TIME,
+            grass_regrowth_raster: None,
+            seasons: Vec::new(),
+            terrain: None,
+            mutation_rate: 0.,
+            mutation_size: 0.1,
+            species: Vec::new(),
+        }
+    }
+}
+
+impl Params {
+    /// Load the parameters from a TOML or JSON scenario file, picked by extension. Keys left out
+    /// keep their default value, while unknown keys and out-of-range values are rejected.
+    pub fn from_file(path: &Path) -> Result<Params, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario `{}`: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => Err(String::from(
+                "unsupported format, expected a .toml or .json file",
+            )),
+        }
+        .and_then(|params: Params| params.validate().map(|_| params))
+        .map_err(|e| format!("invalid scenario `{}`: {}", path.display(), e))
+    }
+
+    pub fn dim(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Split a total number of animals as the original example does: 60% sheep, 40% wolves.
+    pub fn set_agents(&mut self, agents: u32) {
+        self.sheep.initial = (agents as f64 * 0.6) as u32;
+        self.wolves.initial = (agents as f64 * 0.4) as u32;
+    }
+
+    /// Every species of the run: the sheep, the wolves, then the species added by the scenario.
+    pub fn all_species(&self) -> impl Iterator<Item = &Species> {
+        [&self.sheep, &self.wolves].into_iter().chain(&self.species)
+    }
+
+    /// Override a parameter by name. Names are matched case-insensitively, so both
+    /// `mutation_rate` and `MUTATION_RATE` are accepted. Fields of a species are named
+    /// `<species>.<field>`, e.g. `sheep.vision` or `foxes.gain_energy`, and those of a season
+    /// `<season>.<field>`, e.g. `winter.growth`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if let Some((prefix, field)) = name.split_once('.') {
+            let field = field.to_ascii_lowercase();
+            if let Some(species) = [&mut self.sheep, &mut self.wolves]
+                .into_iter()
+                .chain(&mut self.species)
+                .find(|s| s.name.eq_ignore_ascii_case(prefix))
+            {
+                return species.set(&field, value);
+            }
+            return self
+                .seasons
+                .iter_mut()
+                .find(|s| s.name.eq_ignore_ascii_case(prefix))
+                .ok_or_else(|| format!("unknown species or season `{}`", prefix))?
+                .set(&field, value);
+        }
+        match name.to_ascii_lowercase().as_str() {
+            "steps" => self.steps = parse(name, value)?,
+            "reps" => self.reps = parse(name, value)?,
+            "seed" => self.seed = parse(name, value)?,
+            "width" => self.width = parse(name, value)?,
+            "height" => self.height = parse(name, value)?,
+            "topology" => self.topology = parse(name, value)?,
+            "neighbourhood" | "neighborhood" => self.neighbourhood = parse(name, value)?,
+            "agents" => self.set_agents(parse(name, value)?),
+            "grass_regrowth_time" | "full_grown" => self.grass_regrowth_time = parse(name, value)?,
+            "grass_regrowth_raster" => {
+                self.grass_regrowth_raster = Some(PathBuf::from(value.trim()))
+            }
+            "terrain" => self.terrain = Some(PathBuf::from(value.trim())),
+            "mutation_rate" => self.mutation_rate = parse(name, value)?,
+            "mutation_size" => self.mutation_size = parse(name, value)?,
+            _ => return Err(format!("unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
+    /// Check that every parameter is in its valid range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(format!(
+                "the field must have positive dimensions, found {}x{}",
+                self.width, self.height
+            ));
+        }
+        if self.reps == 0 {
+            return Err(String::from("`reps` must be at least 1"));
+        }
+        if self.grass_regrowth_time == 0 {
+            return Err(String::from("`grass_regrowth_time` must be at least 1"));
+        }
+        if let Some(path) = &self.grass_regrowth_raster {
+            grass::read_raster(path, self.width, self.height)?;
+        }
+        if let Some(path) = &self.terrain {
+            Terrain::read(path, self.width, self.height)?;
+        }
+        for (i, season) in self.seasons.iter().enumerate() {
+            if season.name.is_empty() {
+                return Err(String::from("every season must have a name"));
+            }
+            if self.seasons[..i].iter().any(|s| s.name == season.name) {
+                return Err(format!("season `{}` is defined twice", season.name));
+            }
+            if self.all_species().any(|s| s.name == season.name) {
+                return Err(format!(
+                    "`{}` names both a species and a season",
+                    season.name
+                ));
+            }
+            season.validate()?;
+        }
+        if self.mutation_size < 0. {
+            return Err(String::from("`mutation_size` must not be negative"));
+        }
+        probability("mutation_rate", self.mutation_rate)?;
+        FoodWeb::new(self).map(|_| ())
+    }
+}
+
+impl SweepParams for Params {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    fn reps(&self) -> u64 {
+        self.reps
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        Params::set(self, name, value)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        Params::validate(self)
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, name))
+}
+
+fn probability(name: &str, value: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{}` must be in [0, 1], found {}", name, value))
+    }
+}
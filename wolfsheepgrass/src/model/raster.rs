@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::Path;
+
+/// Read a raster of the field: a text file with one line per row of the field, the first line
+/// being the top row, and one value per cell separated by spaces or commas. `what` names the
+/// raster and `value` its values in the errors. Values are returned column by column, the order
+/// used to save the grids.
+pub fn read<T: Clone + Default>(
+    path: &Path,
+    width: i32,
+    height: i32,
+    what: &str,
+    value: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Vec<T>, String> {
+    let invalid = |e: String| format!("invalid {} `{}`: {}", what, path.display(), e);
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {} `{}`: {}", what, path.display(), e))?;
+    let rows: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    if rows.len() != height as usize {
+        return Err(invalid(format!(
+            "expected {} rows, found {}",
+            height,
+            rows.len()
+        )));
+    }
+    let mut cells = vec![T::default(); (width * height) as usize];
+    for (row, line) in rows.iter().enumerate() {
+        let y = height as usize - 1 - row;
+        let values: Vec<&str> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if values.len() != width as usize {
+            return Err(invalid(format!(
+                "expected {} values on row {}, found {}",
+                width,
+                row + 1,
+                values.len()
+            )));
+        }
+        for (x, v) in values.into_iter().enumerate() {
+            cells[x * height as usize + y] = parse(v)
+                .ok_or_else(|| invalid(format!("invalid {} `{}` on row {}", value, v, row + 1)))?;
+        }
+    }
+    Ok(cells)
+}
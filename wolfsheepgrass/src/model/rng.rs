@@ -0,0 +1,6 @@
+use examples_common::rng::{agent_rng, ModelRng};
+
+/// Stream of the grass at a given step, apart from the streams of the agents.
+pub fn grass_rng(seed: u64, step: u64) -> ModelRng {
+    agent_rng(seed, u64::MAX, step)
+}
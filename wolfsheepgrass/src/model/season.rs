@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// A season of the year: how fast grass grows back, and how much of it the land can hold.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Season {
+    pub name: String,
+    /// Steps the season lasts.
+    pub steps: u64,
+    /// Regrowth rate, relative to the regrowth time of the patches: at 2 bare grass grows back
+    /// twice as fast, at 0.5 it takes twice as long, and at 0 it does not grow back at all.
+    pub growth: f64,
+    /// Carrying capacity of the field, as the largest fraction of the fertile patches with grown
+    /// grass. Once it is reached, bare grass stops growing back.
+    pub capacity: f64,
+}
+
+impl Default for Season {
+    fn default() -> Self {
+        Season {
+            name: String::new(),
+            steps: 1,
+            growth: 1.,
+            capacity: 1.,
+        }
+    }
+}
+
+impl Season {
+    /// Set a field by name, for the `<season>.<field>` parameters.
+    pub fn set(&mut self, field: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value `{}` for `{}.{}`", value, self.name, field);
+        match field {
+            "steps" => self.steps = value.trim().parse().map_err(|_| invalid())?,
+            "growth" => self.growth = value.trim().parse().map_err(|_| invalid())?,
+            "capacity" => self.capacity = value.trim().parse().map_err(|_| invalid())?,
+            _ => {
+                return Err(format!(
+                    "unknown field `{}` of season `{}`",
+                    field, self.name
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.steps == 0 {
+            return Err(format!("season `{}` must last at least 1 step", self.name));
+        }
+        if self.growth < 0. {
+            return Err(format!(
+                "`growth` of season `{}` must not be negative",
+                self.name
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.capacity) {
+            return Err(format!(
+                "`capacity` of season `{}` must be in [0, 1], found {}",
+                self.name, self.capacity
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Season of a step: the seasons follow each other in the order they are listed, and the year
+/// starts over once the last one is over. `None` without seasons.
+pub fn at(seasons: &[Season], step: u64) -> Option<&Season> {
+    let year: u64 = seasons.iter().map(|s| s.steps).sum();
+    if year == 0 {
+        return None;
+    }
+    let mut day = step % year;
+    for season in seasons {
+        if day < season.steps {
+            return Some(season);
+        }
+        day -= season.steps;
+    }
+    None
+}
@@ -0,0 +1,267 @@
+use crate::model::genome::Genome;
+use crate::model::params::Params;
+use crate::{
+    ENERGY_CONSUME, GAIN_ENERGY_SHEEP, GAIN_ENERGY_WOLF, MOMENTUM_PROBABILITY, SHEEP_REPR,
+    WOLF_REPR,
+};
+use serde::de::{Deserializer, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Name of the grass in a diet.
+pub const GRASS: &str = "grass";
+
+/// An animal species of the food web, with what it eats and how it lives and moves.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Species {
+    pub name: String,
+    /// What the species eats: `grass` and the names of its prey.
+    pub diet: Vec<String>,
+    pub initial: u32,
+    /// Energy gained by eating.
+    pub gain_energy: f64,
+    /// Probability of reproducing at each step.
+    pub prob_reproduction: f64,
+    /// Steps an animal lives at most, 0 for no limit.
+    pub max_age: u32,
+    /// Moves toward the nearest food it sees: prey, or grown grass for grazers.
+    pub seeks_food: bool,
+    /// Moves away from the nearest predator it sees.
+    pub flees: bool,
+    /// Genome of the first animals, see `Genome`.
+    pub momentum: f64,
+    pub reproduction_threshold: f64,
+    pub metabolism: f64,
+    pub vision: f64,
+    /// Emoji drawn by the visualization, a PNG file of `assets/emojis`.
+    pub emoji: String,
+}
+
+impl Default for Species {
+    fn default() -> Self {
+        Species {
+            name: String::new(),
+            diet: Vec::new(),
+            initial: 0,
+            gain_energy: 0.,
+            prob_reproduction: 0.,
+            max_age: 0,
+            seeks_food: false,
+            flees: false,
+            momentum: MOMENTUM_PROBABILITY,
+            reproduction_threshold: 0.,
+            metabolism: ENERGY_CONSUME,
+            vision: 1.,
+            emoji: String::from("wolf"),
+        }
+    }
+}
+
+impl Species {
+    /// Sheep of the original model, 60% of its 200 animals.
+    pub fn sheep() -> Species {
+        Species {
+            name: String::from("sheep"),
+            diet: vec![String::from(GRASS)],
+            initial: 120,
+            gain_energy: GAIN_ENERGY_SHEEP,
+            prob_reproduction: SHEEP_REPR,
+            emoji: String::from("sheep"),
+            ..Species::default()
+        }
+    }
+
+    /// Wolves of the original model, 40% of its 200 animals.
+    pub fn wolves() -> Species {
+        Species {
+            name: String::from("wolves"),
+            diet: vec![String::from("sheep")],
+            initial: 80,
+            gain_energy: GAIN_ENERGY_WOLF,
+            prob_reproduction: WOLF_REPR,
+            emoji: String::from("wolf"),
+            ..Species::default()
+        }
+    }
+
+    /// Genome of the first animals of the species.
+    pub fn genome(&self) -> Genome {
+        Genome {
+            momentum: self.momentum,
+            reproduction_threshold: self.reproduction_threshold,
+            metabolism: self.metabolism,
+            vision: self.vision,
+        }
+    }
+
+    /// Set a field by name, for the `<species>.<field>` parameters.
+    pub fn set(&mut self, field: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value `{}` for `{}.{}`", value, self.name, field);
+        let number = || value.trim().parse::<f64>().map_err(|_| invalid());
+        let whole = || value.trim().parse::<u32>().map_err(|_| invalid());
+        let flag = || value.trim().parse::<bool>().map_err(|_| invalid());
+        match field {
+            "diet" => self.diet = parse_list(value),
+            "initial" => self.initial = whole()?,
+            "gain_energy" => self.gain_energy = number()?,
+            "prob_reproduction" => self.prob_reproduction = number()?,
+            "max_age" => self.max_age = whole()?,
+            "seeks_food" => self.seeks_food = flag()?,
+            "flees" => self.flees = flag()?,
+            "momentum" => self.momentum = number()?,
+            "reproduction_threshold" => self.reproduction_threshold = number()?,
+            "metabolism" => self.metabolism = number()?,
+            "vision" => self.vision = number()?,
+            "emoji" => self.emoji = value.trim().to_string(),
+            _ => {
+                return Err(format!(
+                    "unknown field `{}` of species `{}`",
+                    field, self.name
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.gain_energy < 0. || self.metabolism < 0. {
+            return Err(format!(
+                "`gain_energy` and `metabolism` of species `{}` must not be negative",
+                self.name
+            ));
+        }
+        if self.reproduction_threshold < 0. || self.vision < 0. {
+            return Err(format!(
+                "`reproduction_threshold` and `vision` of species `{}` must not be negative",
+                self.name
+            ));
+        }
+        for (field, value) in [
+            ("prob_reproduction", self.prob_reproduction),
+            ("momentum", self.momentum),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!(
+                    "`{}` of species `{}` must be in [0, 1], found {}",
+                    field, self.name, value
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the `[sheep]` table of a scenario, the keys left out keeping the values of
+/// `Species::sheep` rather than those of `Species::default`.
+pub fn sheep<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Species, D::Error> {
+    over(Species::sheep(), deserializer)
+}
+
+/// Read the `[wolves]` table of a scenario over `Species::wolves`, see `sheep`.
+pub fn wolves<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Species, D::Error> {
+    over(Species::wolves(), deserializer)
+}
+
+// The fields given by the deserializer replace those of `species`, unknown fields are still
+// rejected.
+fn over<'de, D: Deserializer<'de>>(species: Species, deserializer: D) -> Result<Species, D::Error> {
+    let mut fields = match serde_json::to_value(species).map_err(D::Error::custom)? {
+        Value::Object(fields) => fields,
+        _ => unreachable!("a species is serialized as a map"),
+    };
+    fields.extend(Map::deserialize(deserializer)?);
+    serde_json::from_value(Value::Object(fields)).map_err(D::Error::custom)
+}
+
+/// Comma separated list of a diet.
+pub fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Species of a run and who eats whom, by index in the species list. The index of a species is
+/// also its ordering in the schedule: species are stepped in the order they are listed.
+#[derive(Clone, Debug)]
+pub struct FoodWeb {
+    pub species: Vec<Species>,
+    prey: Vec<Vec<usize>>,
+    predators: Vec<Vec<usize>>,
+    grazers: Vec<bool>,
+}
+
+impl FoodWeb {
+    /// The food web of `params`: sheep and wolves, then the species added by the scenario.
+    pub fn new(params: &Params) -> Result<FoodWeb, String> {
+        FoodWeb::from_species(params.all_species().cloned().collect())
+    }
+
+    pub fn from_species(species: Vec<Species>) -> Result<FoodWeb, String> {
+        let n = species.len();
+        let mut web = FoodWeb {
+            prey: vec![Vec::new(); n],
+            predators: vec![Vec::new(); n],
+            grazers: vec![false; n],
+            species,
+        };
+        for (i, s) in web.species.iter().enumerate() {
+            if s.name.is_empty() || s.name == GRASS {
+                return Err(format!("invalid species name `{}`", s.name));
+            }
+            if web.species[..i].iter().any(|other| other.name == s.name) {
+                return Err(format!("species `{}` is defined twice", s.name));
+            }
+            s.validate()?;
+        }
+        for i in 0..n {
+            for food in web.species[i].diet.clone() {
+                if food == GRASS {
+                    web.grazers[i] = true;
+                    continue;
+                }
+                let prey = web.index(&food).ok_or_else(|| {
+                    format!(
+                        "unknown food `{}` in the diet of `{}`",
+                        food, web.species[i].name
+                    )
+                })?;
+                if prey == i {
+                    return Err(format!("species `{}` cannot eat itself", food));
+                }
+                if !web.prey[i].contains(&prey) {
+                    web.prey[i].push(prey);
+                    web.predators[prey].push(i);
+                }
+            }
+        }
+        Ok(web)
+    }
+
+    pub fn len(&self) -> usize {
+        self.species.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.species.is_empty()
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.species.iter().position(|s| s.name == name)
+    }
+
+    /// Species eaten by `species`, in the order of its diet.
+    pub fn prey(&self, species: usize) -> &[usize] {
+        &self.prey[species]
+    }
+
+    pub fn predators(&self, species: usize) -> &[usize] {
+        &self.predators[species]
+    }
+
+    pub fn grazes(&self, species: usize) -> bool {
+        self.grazers[species]
+    }
+}
@@ -5,19 +5,31 @@ use krabmaga::engine::{
 
 use krabmaga::*;
 
-use super::sheep::Sheep;
-use super::wolf::Wolf;
-use crate::{FULL_GROWN, GAIN_ENERGY_SHEEP, GAIN_ENERGY_WOLF, SHEEP_REPR, WOLF_REPR};
+use super::animal::Animal;
+use super::genome::GenePool;
+use super::grass::{self, Grass};
+use super::lifecycle::Lifecycle;
+use super::movement::World;
+use super::params::Params;
+use super::rng::grass_rng;
+use super::season;
+use super::species::FoodWeb;
+use super::terrain::{Land, Terrain};
+use crate::analysis::Analysis;
+#[cfg(feature = "snapshot")]
+use crate::frames::{self, Frames};
 use core::fmt;
-use hashbrown::HashSet;
-use krabmaga::engine::fields::grid_option::GridOption;
-use krabmaga::rand;
+use examples_common::checkpoint::{self, Cell, Resumable};
+use examples_common::observer::{Observer, TimeSeries};
+use examples_common::rng::{self, ModelRng};
+use examples_common::sweep::Sweepable;
+use krabmaga::engine::agent::Agent;
 use krabmaga::rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
-pub use std::time::Duration;
-pub use std::time::Instant;
+use std::sync::Arc;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum LifeState {
     Alive,
     Dead,
@@ -34,44 +46,131 @@ impl fmt::Display for LifeState {
 
 pub struct WsgState {
     pub dim: (i32, i32),
-    pub wolves_grid: DenseGrid2D<Wolf>,
-    pub sheep_grid: DenseGrid2D<Sheep>,
-    pub grass_field: DenseNumberGrid2D<u16>,
+    pub world: World,
+    pub food_web: Arc<FoodWeb>,
+    /// Grid of each species of the food web, by index.
+    pub layers: Vec<DenseGrid2D<Animal>>,
+    /// Grass of the fertile patches, the others have none.
+    pub grass_field: DenseNumberGrid2D<Grass>,
+    /// Regrowth time of each patch read from `Params::grass_regrowth_raster`, column by column.
+    pub regrowth: Option<Vec<u16>>,
+    /// Regrowth built up toward the next step of the grass countdowns, see `Season::growth`.
+    pub growth: f64,
     pub step: u64,
+    /// Id of the next newborn. Animals are scheduled in id order, so it is also the next id
+    /// the schedule hands out.
     pub next_id: u32,
-    pub new_sheep: Vec<Sheep>,
-    pub new_wolves: Vec<Wolf>,
-    pub killed_sheep: HashSet<Sheep>,
-    pub initial_animals: (u32, u32),
+    /// Births and deaths of each species during the step.
+    pub lifecycles: Vec<Lifecycle<Animal>>,
+    /// Live animals of each species, kept up to date with the births and deaths of every step.
+    pub population: Vec<u32>,
+    /// Genes of the live animals of each species, kept up to date like the population.
+    pub genes: Vec<GenePool>,
+    pub params: Params,
+    pub observer: Observer,
+    #[cfg(feature = "snapshot")]
+    pub frames: Option<Frames>,
 }
 
 impl WsgState {
-    pub fn new(dim: (i32, i32), initial_animals: (u32, u32)) -> WsgState {
-        WsgState {
+    /// State of a run of `params`. Fails on the errors `Params::validate` reports about the
+    /// food web, or when the terrain or the grass raster cannot be read.
+    pub fn new(params: Params) -> Result<WsgState, String> {
+        let dim = params.dim();
+        let food_web = FoodWeb::new(&params)?;
+        let terrain = match &params.terrain {
+            Some(path) => Terrain::read(path, dim.0, dim.1)?,
+            None => Terrain::fertile(dim),
+        };
+        let regrowth = match &params.grass_regrowth_raster {
+            Some(path) => Some(grass::read_raster(path, dim.0, dim.1)?),
+            None => None,
+        };
+        Ok(WsgState {
             dim,
-            wolves_grid: DenseGrid2D::new(dim.0, dim.1),
-            sheep_grid: DenseGrid2D::new(dim.0, dim.1),
+            world: World::new(&params, terrain),
+            regrowth,
+            layers: food_web
+                .species
+                .iter()
+                .map(|_| DenseGrid2D::new(dim.0, dim.1))
+                .collect(),
             grass_field: DenseNumberGrid2D::new(dim.0, dim.1),
+            growth: 0.,
             step: 0,
-            next_id: initial_animals.1 + initial_animals.0,
-            new_sheep: Vec::new(),
-            new_wolves: Vec::new(),
-            initial_animals,
-            killed_sheep: HashSet::new(),
+            next_id: food_web.species.iter().map(|s| s.initial).sum(),
+            lifecycles: food_web
+                .species
+                .iter()
+                .map(|_| Lifecycle::default())
+                .collect(),
+            population: vec![0; food_web.len()],
+            genes: vec![GenePool::default(); food_web.len()],
+            food_web: Arc::new(food_web),
+            params,
+            observer: Observer::new(),
+            #[cfg(feature = "snapshot")]
+            frames: None,
+        })
+    }
+
+    /// Seed of the repetition being run, the seed of the parameters plus the repetition, see
+    /// `Observer::seed`. Every random draw of the run derives from it.
+    pub fn seed(&self) -> u64 {
+        self.observer.seed(self.params.seed)
+    }
+
+    /// End-of-run analysis of the populations recorded so far.
+    pub fn analysis(&self) -> Analysis {
+        Analysis::new(self.observer.time_series(), &self.food_web)
+    }
+
+    // Written next to the metrics, when they are exported, once the run has ended.
+    fn write_analysis(&self) {
+        if let Some(output) = self.observer.output() {
+            let path = Analysis::path(output, self.observer.rep());
+            if let Err(e) = self.analysis().write(&path) {
+                eprintln!("cannot write analysis `{}`: {}", path.display(), e);
+            }
         }
     }
+
+    /// Index of a species of the food web.
+    pub fn species(&self, name: &str) -> Option<usize> {
+        self.food_web.index(name)
+    }
+
+    /// Live animals of `species` on `cell`, as an animal of species `by` sees them: species
+    /// stepped before `by` are where they have just moved, the others where they were at the end
+    /// of the last step.
+    pub fn animals_at(&self, species: usize, cell: &Int2D, by: usize) -> Vec<Animal> {
+        let grid = &self.layers[species];
+        let animals = if species < by {
+            grid.get_objects_unbuffered(cell)
+        } else {
+            grid.get_objects(cell)
+        };
+        let mut animals = animals.unwrap_or_default();
+        animals.retain(|animal| !self.lifecycles[species].is_dead(animal));
+        animals
+    }
 }
 
 impl State for WsgState {
     fn reset(&mut self) {
         self.step = 0;
-        self.wolves_grid = DenseGrid2D::new(self.dim.0, self.dim.1);
-        self.sheep_grid = DenseGrid2D::new(self.dim.0, self.dim.1);
+        for layer in self.layers.iter_mut() {
+            *layer = DenseGrid2D::new(self.dim.0, self.dim.1);
+        }
         self.grass_field = DenseNumberGrid2D::new(self.dim.0, self.dim.1);
-        self.next_id = self.initial_animals.0 + self.initial_animals.1;
-        self.new_sheep = Vec::new();
-        self.new_wolves = Vec::new();
-        self.initial_animals = (self.initial_animals.0, self.initial_animals.1);
+        self.growth = 0.;
+        self.next_id = self.food_web.species.iter().map(|s| s.initial).sum();
+        for lifecycle in self.lifecycles.iter_mut() {
+            lifecycle.clear();
+        }
+        self.population.fill(0);
+        self.genes.fill(GenePool::default());
+        self.observer.reset();
     }
 
     fn init(&mut self, schedule: &mut Schedule) {
@@ -81,9 +180,9 @@ impl State for WsgState {
                         there is an ecosystem that involves animals into their life-cycle.".to_string();
         description!(s);
 
-        generate_grass(self);
-        generate_wolves(self, schedule);
-        generate_sheep(self, schedule);
+        let mut rng = rng::state_rng(self.seed());
+        generate_grass(self, &mut rng);
+        generate_animals(self, schedule, &mut rng);
 
         addplot!(
             String::from("Agents"),
@@ -101,25 +200,14 @@ impl State for WsgState {
     }
 
     fn update(&mut self, step: u64) {
-        if step != 0 {
-            self.grass_field.apply_to_all_values(
-                |grass| {
-                    let growth = *grass;
-                    if growth < FULL_GROWN {
-                        growth + 1
-                    } else {
-                        growth
-                    }
-                },
-                GridOption::READWRITE,
-            );
-        }
-
         self.grass_field.lazy_update();
-        self.sheep_grid.lazy_update();
-        self.wolves_grid.lazy_update();
+        for layer in self.layers.iter_mut() {
+            layer.lazy_update();
+        }
 
         self.step = step;
+        #[cfg(feature = "snapshot")]
+        frames::record(self, step);
     }
 
     fn as_any_mut(&mut self) -> &mut dyn Any {
@@ -139,127 +227,279 @@ impl State for WsgState {
     }
 
     fn before_step(&mut self, _schedule: &mut Schedule) {
-        self.new_sheep.clear();
-        self.new_wolves.clear();
+        for lifecycle in self.lifecycles.iter_mut() {
+            lifecycle.clear();
+        }
     }
 
     fn after_step(&mut self, schedule: &mut Schedule) {
-        for sheep in self.new_sheep.iter() {
-            schedule.schedule_repeating(Box::new(*sheep), schedule.time + 1.0, 0);
+        // Newborns of every species are scheduled in the order they were born, so that the
+        // schedule gives each one its own id and the dead can be dequeued by it. Animals born and
+        // eaten in the same step are scheduled before being buried.
+        let mut born: Vec<Animal> = self
+            .lifecycles
+            .iter()
+            .flat_map(|lifecycle| lifecycle.born.iter().copied())
+            .collect();
+        born.sort_by_key(|animal| animal.id);
+        for animal in born {
+            schedule.schedule_repeating(
+                Box::new(animal),
+                schedule.time + 1.0,
+                animal.species as i32,
+            );
         }
+        for ((population, genes), lifecycle) in self
+            .population
+            .iter_mut()
+            .zip(self.genes.iter_mut())
+            .zip(&self.lifecycles)
+        {
+            lifecycle.bury(schedule);
+            *population += lifecycle.born.len() as u32;
+            *population -= lifecycle.dead.len() as u32;
+            for animal in &lifecycle.born {
+                genes.add(&animal.genome);
+            }
+            for (animal, _) in &lifecycle.dead {
+                genes.remove(&animal.genome);
+            }
+        }
+
+        // Grass grows back once the sheep have grazed, as in NetLogo, and is counted afterwards.
+        let grown_grass = grow_grass(self, schedule.step);
+
+        let species = &self.food_web.species;
+        let counts = &self.population;
 
-        for wolf in self.new_wolves.iter() {
-            schedule.schedule_repeating(Box::new(*wolf), schedule.time + 1.0, 1);
+        for (i, s) in species.iter().enumerate() {
+            plot!(
+                String::from("Agents"),
+                s.name.clone(),
+                schedule.step as f64,
+                counts[i] as f64
+            );
+            plot!(
+                String::from("Dead/Born"),
+                format!("Dead {}", s.name),
+                schedule.step as f64,
+                self.lifecycles[i].dead.len() as f64
+            );
+            plot!(
+                String::from("Dead/Born"),
+                format!("Born {}", s.name),
+                schedule.step as f64,
+                self.lifecycles[i].born.len() as f64
+            );
         }
 
-        for sheep in self.killed_sheep.iter() {
-            schedule.dequeue(Box::new(*sheep), sheep.id);
+        let mut metrics: Vec<(String, f64)> = species
+            .iter()
+            .zip(counts)
+            .map(|(s, &count)| (s.name.clone(), count as f64))
+            .collect();
+        metrics.push((String::from("grass"), grown_grass as f64));
+        for (s, lifecycle) in species.iter().zip(&self.lifecycles) {
+            metrics.extend(lifecycle.metrics(&s.name));
         }
+        for (s, genes) in species.iter().zip(&self.genes) {
+            metrics.extend(genes.metrics(&s.name));
+        }
+        let metrics: Vec<(&str, f64)> = metrics
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+        self.observer.record(schedule.step + 1, &metrics);
+    }
 
-        let agents = schedule.get_all_events();
-        let mut num_sheep: f32 = 0.;
-        let mut num_wolves: f32 = 0.;
+    /// The run ends after its last step, or once every animal is dead. Either way, the analysis
+    /// of the run is written.
+    fn end_condition(&mut self, schedule: &mut Schedule) -> bool {
+        let end = schedule.step >= self.params.steps || self.population.iter().all(|&n| n == 0);
+        if end {
+            self.write_analysis();
+        }
+        end
+    }
+}
 
-        for n in agents {
-            if let Some(_s) = n.downcast_ref::<Sheep>() {
-                num_sheep += 1.;
-            }
-            if let Some(_w) = n.downcast_ref::<Wolf>() {
-                num_wolves += 1.;
-            }
+/// Everything a `WsgState` needs to go on with a run, see `Resumable`.
+#[derive(Serialize, Deserialize)]
+pub struct WsgSnapshot {
+    pub params: Params,
+    pub step: u64,
+    pub next_id: u32,
+    pub growth: f64,
+    pub population: Vec<u32>,
+    pub genes: Vec<GenePool>,
+    /// Animals of each species, in the order of the food web.
+    pub layers: Vec<Vec<Cell<Animal>>>,
+    pub grass: Vec<Option<Grass>>,
+}
+
+// Snapshots are taken between two steps, once the births and deaths of the last one have been
+// applied to the schedule, so only the grids and the ids are left to save.
+impl Resumable for WsgState {
+    type Snapshot = WsgSnapshot;
+    type Agent = Animal;
+
+    fn snapshot(&self) -> WsgSnapshot {
+        WsgSnapshot {
+            params: self.params.clone(),
+            step: self.step,
+            next_id: self.next_id,
+            growth: self.growth,
+            population: self.population.clone(),
+            genes: self.genes.clone(),
+            layers: self.layers.iter().map(checkpoint::save_grid).collect(),
+            grass: checkpoint::save_values(&self.grass_field),
         }
+    }
 
-        plot!(
-            String::from("Agents"),
-            String::from("Wolfs"),
-            schedule.step as f64,
-            num_wolves as f64
-        );
-        plot!(
-            String::from("Agents"),
-            String::from("Sheep"),
-            schedule.step as f64,
-            num_sheep as f64
-        );
+    fn restore(snapshot: WsgSnapshot) -> Result<WsgState, String> {
+        let mut state = WsgState::new(snapshot.params)?;
+        state.step = snapshot.step;
+        state.next_id = snapshot.next_id;
+        state.growth = snapshot.growth;
+        state.population = snapshot.population;
+        state.genes = snapshot.genes;
+        for (layer, cells) in state.layers.iter_mut().zip(snapshot.layers) {
+            checkpoint::restore_grid(layer, cells);
+            layer.lazy_update();
+        }
+        checkpoint::restore_values(&state.grass_field, snapshot.grass);
+        state.grass_field.lazy_update();
+        Ok(state)
+    }
 
-        plot!(
-            String::from("Dead/Born"),
-            String::from("Dead Sheep"),
-            schedule.step as f64,
-            self.killed_sheep.len() as f64
-        );
-        plot!(
-            String::from("Dead/Born"),
-            String::from("Born Wolfs"),
-            schedule.step as f64,
-            self.new_wolves.len() as f64
-        );
-        plot!(
-            String::from("Dead/Born"),
-            String::from("Born Sheep"),
-            schedule.step as f64,
-            self.new_sheep.len() as f64
-        );
+    fn save_agent(agent: &dyn Agent) -> Animal {
+        *agent.downcast_ref::<Animal>().unwrap()
+    }
 
-        self.killed_sheep.clear();
+    fn restore_agent(agent: Animal) -> Box<dyn Agent> {
+        Box::new(agent)
+    }
+
+    fn observer(&mut self) -> &mut Observer {
+        &mut self.observer
+    }
+
+    #[cfg(feature = "snapshot")]
+    fn take_outputs(&mut self, state: &mut WsgState) {
+        std::mem::swap(&mut self.observer, &mut state.observer);
+        self.frames = state.frames.take();
     }
 }
 
-fn generate_grass(state: &mut WsgState) {
-    (0..state.dim.1).into_iter().for_each(|x| {
-        (0..state.dim.0).into_iter().for_each(|y| {
-            let mut rng = rand::thread_rng();
-            let fully_growth = rng.gen_bool(0.5);
-            if fully_growth {
-                state
-                    .grass_field
-                    .set_value_location(FULL_GROWN, &Int2D { x, y });
-            } else {
-                let grass_init_value = rng.gen_range(0..FULL_GROWN + 1);
-                state
-                    .grass_field
-                    .set_value_location(grass_init_value, &Int2D { x, y });
+// As in NetLogo, half of the patches start with grown grass and the others somewhere in their
+// countdown. Grass only grows on fertile land.
+fn generate_grass(state: &mut WsgState, rng: &mut ModelRng) {
+    for x in 0..state.dim.0 {
+        for y in 0..state.dim.1 {
+            if state.world.terrain.land(&Int2D { x, y }) != Land::Fertile {
+                continue;
             }
-        })
-    });
+            let regrowth = match &state.regrowth {
+                Some(times) => times[(x * state.dim.1 + y) as usize],
+                None => state.params.grass_regrowth_time,
+            };
+            let grass = if rng.random_bool(0.5) {
+                Grass::grown(regrowth)
+            } else {
+                Grass::bare(rng.random_range(0..regrowth), regrowth)
+            };
+            state.grass_field.set_value_location(grass, &Int2D { x, y });
+        }
+    }
 }
 
-fn generate_sheep(state: &mut WsgState, schedule: &mut Schedule) {
-    let mut rng = rand::thread_rng();
+// Bare grass counts down by as many steps as the season has built up, a whole step a day
+// without seasons. Once the grown grass reaches the carrying capacity of the season, the
+// patches that are ready stay bare; when only some of them fit, each grows back with the same
+// probability. Returns the patches of grown grass.
+fn grow_grass(state: &mut WsgState, step: u64) -> usize {
+    let season = season::at(&state.params.seasons, step);
+    let (growth, capacity) = season.map_or((1., 1.), |s| (s.growth, s.capacity));
+    state.growth += growth;
+    let ticks = state.growth.floor();
+    state.growth -= ticks;
+    let ticks = ticks.min(u16::MAX as f64) as u16;
+
+    let mut grown = 0;
+    let mut ready = Vec::new();
+    for x in 0..state.dim.0 {
+        for y in 0..state.dim.1 {
+            let loc = Int2D { x, y };
+            let current = state.grass_field.get_value_unbuffered(&loc);
+            let Some(grass) = current.or_else(|| state.grass_field.get_value(&loc)) else {
+                continue;
+            };
+            let next = grass.grow_by(ticks);
+            if grass.grown {
+                grown += 1;
+            } else if next.grown {
+                ready.push((loc, next));
+                continue;
+            }
+            state.grass_field.set_value_location(next, &loc);
+        }
+    }
 
-    for id in 0..state.initial_animals.0 {
-        let loc = Int2D {
-            x: rng.gen_range(0..state.dim.0),
-            y: rng.gen_range(0..state.dim.1),
+    let fertile = state.world.terrain.count(Land::Fertile);
+    let mut room = ((capacity * fertile as f64).floor() as usize).saturating_sub(grown);
+    let share = room as f64 / ready.len().max(1) as f64;
+    let mut rng = (share < 1.).then(|| grass_rng(state.seed(), step));
+    for (loc, grass) in ready {
+        let fits = room > 0 && rng.as_mut().is_none_or(|rng| rng.random_bool(share));
+        let grass = if fits {
+            room -= 1;
+            grown += 1;
+            grass
+        } else {
+            Grass::bare(0, grass.regrowth)
         };
-        let init_energy = rng.gen_range(0..(2 * GAIN_ENERGY_SHEEP as usize));
-        let sheep = Sheep::new(
-            id + state.initial_animals.1,
-            loc,
-            init_energy as f64,
-            GAIN_ENERGY_SHEEP,
-            SHEEP_REPR,
-        );
-        state.sheep_grid.set_object_location(sheep, &loc);
+        state.grass_field.set_value_location(grass, &loc);
+    }
+    grown
+}
 
-        schedule.schedule_repeating(Box::new(sheep), 0., 0);
+// Species are generated, and scheduled, in the order of the food web, so that the ids of the
+// animals are also the ids the schedule knows them by. The ordering of a species is its index:
+// prey listed before their predators are stepped first, and are eaten where they have just moved.
+fn generate_animals(state: &mut WsgState, schedule: &mut Schedule, rng: &mut ModelRng) {
+    let web = Arc::clone(&state.food_web);
+    let mut id = 0;
+    for (index, species) in web.species.iter().enumerate() {
+        for _ in 0..species.initial {
+            let loc = loop {
+                let loc = Int2D {
+                    x: rng.random_range(0..state.dim.0),
+                    y: rng.random_range(0..state.dim.1),
+                };
+                if state.world.terrain.passable(&loc) {
+                    break loc;
+                }
+            };
+            let init_energy = rng.random_range(0..((2. * species.gain_energy) as usize).max(1));
+            let animal = Animal::new(id, index, loc, init_energy as f64, species.genome());
+            state.layers[index].set_object_location(animal, &loc);
+            state.population[index] += 1;
+            state.genes[index].add(&animal.genome);
+
+            schedule.schedule_repeating(Box::new(animal), 0., index as i32);
+            id += 1;
+        }
     }
 }
 
-fn generate_wolves(state: &mut WsgState, schedule: &mut Schedule) {
-    let mut rng = rand::thread_rng();
-    for id in 0..state.initial_animals.1 {
-        let loc = Int2D {
-            x: rng.gen_range(0..state.dim.0),
-            y: rng.gen_range(0..state.dim.1),
-        };
-        let init_energy = rng.gen_range(0..(2 * GAIN_ENERGY_WOLF as usize));
+impl Sweepable for WsgState {
+    type Params = Params;
 
-        let wolf = Wolf::new(id, loc, init_energy as f64, GAIN_ENERGY_WOLF, WOLF_REPR);
-        state.wolves_grid.set_object_location(wolf, &loc);
+    fn build(params: Params) -> Result<Self, String> {
+        WsgState::new(params)
+    }
 
-        // Sheep have an higher ordering than wolves. This is so that if a wolf kills one, in the next step
-        // the attacked sheep will immediately notice and die, instead of noticing after two steps.
-        schedule.schedule_repeating(Box::new(wolf), 0., 1);
+    fn metrics(&self) -> &TimeSeries {
+        self.observer.time_series()
     }
 }
@@ -0,0 +1,135 @@
+use super::raster;
+use krabmaga::engine::location::Int2D;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Ground of a cell of the field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Land {
+    /// Grass grows, as everywhere in the original model.
+    #[default]
+    Fertile,
+    /// Nothing grows, but animals walk through.
+    Barren,
+    /// Nothing grows, and animals cannot step in.
+    Water,
+}
+
+impl FromStr for Land {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Land, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "f" | "fertile" => Ok(Land::Fertile),
+            "b" | "barren" => Ok(Land::Barren),
+            "w" | "water" => Ok(Land::Water),
+            _ => Err(format!(
+                "unknown land `{}`, expected fertile, barren or water",
+                s
+            )),
+        }
+    }
+}
+
+impl Land {
+    /// Land of a pixel of a terrain image: mostly blue is water, mostly green is fertile, any
+    /// other colour (e.g. brown, grey or black) is barren.
+    pub fn of_color([r, g, b]: [u8; 3]) -> Land {
+        if b > r && b > g {
+            Land::Water
+        } else if g > r && g > b {
+            Land::Fertile
+        } else {
+            Land::Barren
+        }
+    }
+}
+
+/// Land of every cell of the field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Terrain {
+    pub dim: (i32, i32),
+    /// Cells column by column, as the grids are saved.
+    land: Vec<Land>,
+}
+
+impl Terrain {
+    /// A field that is fertile everywhere, as in the original model.
+    pub fn fertile(dim: (i32, i32)) -> Terrain {
+        Terrain {
+            dim,
+            land: vec![Land::Fertile; (dim.0 * dim.1) as usize],
+        }
+    }
+
+    /// Read the terrain of a `width` x `height` field from a PNG image, one pixel per cell, or
+    /// otherwise from a raster (see `raster::read`) of `fertile`, `barren` and `water` cells,
+    /// or of their initials `f`, `b` and `w`.
+    pub fn read(path: &Path, width: i32, height: i32) -> Result<Terrain, String> {
+        let land = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("png") => read_png(path, width, height)?,
+            _ => raster::read(path, width, height, "terrain", "land", |v| v.parse().ok())?,
+        };
+        if land.iter().all(|&land| land == Land::Water) {
+            return Err(format!(
+                "invalid terrain `{}`: every cell is water",
+                path.display()
+            ));
+        }
+        Ok(Terrain {
+            dim: (width, height),
+            land,
+        })
+    }
+
+    pub fn land(&self, loc: &Int2D) -> Land {
+        self.land[(loc.x * self.dim.1 + loc.y) as usize]
+    }
+
+    /// Whether animals can step in `loc`.
+    pub fn passable(&self, loc: &Int2D) -> bool {
+        self.land(loc) != Land::Water
+    }
+
+    /// Number of cells of the given land.
+    pub fn count(&self, land: Land) -> usize {
+        self.land.iter().filter(|&&l| l == land).count()
+    }
+}
+
+fn read_png(path: &Path, width: i32, height: i32) -> Result<Vec<Land>, String> {
+    let invalid = |e: String| format!("invalid terrain `{}`: {}", path.display(), e);
+    let file =
+        File::open(path).map_err(|e| format!("cannot read terrain `{}`: {}", path.display(), e))?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| invalid(e.to_string()))?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut pixels)
+        .map_err(|e| invalid(e.to_string()))?;
+    if (info.width, info.height) != (width as u32, height as u32) {
+        return Err(invalid(format!(
+            "expected {}x{} pixels, found {}x{}",
+            width, height, info.width, info.height
+        )));
+    }
+    // grey pixels are barren, whatever their shade, and the alpha channel is left out
+    let samples = info.color_type.samples();
+    let mut land = vec![Land::Fertile; (width * height) as usize];
+    for row in 0..height as usize {
+        let y = height as usize - 1 - row;
+        for x in 0..width as usize {
+            let i = row * info.line_size + x * samples;
+            let color = if samples >= 3 {
+                [pixels[i], pixels[i + 1], pixels[i + 2]]
+            } else {
+                [pixels[i]; 3]
+            };
+            land[x * height as usize + y] = Land::of_color(color);
+        }
+    }
+    Ok(land)
+}
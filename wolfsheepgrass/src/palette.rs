@@ -0,0 +1,49 @@
+// Colours of the field, shared by the Bevy visualization and the PNG frames of the `snapshot`
+// feature.
+
+use crate::model::grass::Grass;
+use crate::model::terrain::Land;
+
+pub const BACKGROUND: [u8; 3] = [255, 255, 255];
+pub const SHEEP: [u8; 4] = [255, 255, 255, 255];
+pub const WOLF: [u8; 4] = [40, 40, 40, 255];
+/// Colours of the species added to the sheep and the wolves, in turn.
+pub const OTHER_SPECIES: [[u8; 4]; 4] = [
+    [230, 120, 20, 255],
+    [150, 60, 200, 255],
+    [30, 110, 220, 255],
+    [220, 40, 40, 255],
+];
+
+/// Colour of the animals of a species, by its index in the food web.
+pub fn animal(species: usize) -> [u8; 4] {
+    match species {
+        0 => SHEEP,
+        1 => WOLF,
+        _ => OTHER_SPECIES[(species - 2) % OTHER_SPECIES.len()],
+    }
+}
+
+/// Colour of a patch: grown grass is dark green, bare soil turns green as its countdown runs out.
+pub fn grass(grass: Grass) -> [u8; 4] {
+    let regrown = 1. - grass.countdown as f32 / grass.regrowth as f32;
+    if grass.grown {
+        [0u8, 128u8, 0u8, 255u8]
+    } else if regrown < 0.5 {
+        [139u8, 69u8, 19u8, 180u8]
+    } else if regrown < 0.7 {
+        [128u8, 128u8, 0u8, 150u8]
+    } else {
+        [0u8, 255u8, 0u8, 255u8]
+    }
+}
+
+/// Colour of the land under the grass: fertile land always has grass on it, barren land is sand
+/// and water is blue.
+pub fn land(land: Land) -> [u8; 4] {
+    match land {
+        Land::Fertile => [0u8, 0u8, 0u8, 0u8],
+        Land::Barren => [210u8, 190u8, 140u8, 255u8],
+        Land::Water => [40u8, 100u8, 200u8, 255u8],
+    }
+}
@@ -1,4 +1,4 @@
-use crate::model::sheep::Sheep;
+use crate::model::animal::Animal;
 use crate::model::state::WsgState;
 use krabmaga::bevy::ecs as bevy_ecs;
 use krabmaga::bevy::prelude::{Component, Quat, Transform, Visibility};
@@ -8,19 +8,21 @@ use krabmaga::engine::state::State;
 use krabmaga::visualization::agent_render::{AgentRender, SpriteType};
 
 #[derive(Component)]
-pub struct SheepVis {
+pub struct AnimalVis {
     pub id: u32,
 }
 
-impl AgentRender for SheepVis {
-    fn sprite(&self, _agent: &Box<dyn Agent>, _state: &Box<&dyn State>) -> SpriteType {
-        SpriteType::Emoji(String::from("sheep"))
+impl AgentRender for AnimalVis {
+    fn sprite(&self, agent: &Box<dyn Agent>, state: &Box<&dyn State>) -> SpriteType {
+        let state = state.as_any().downcast_ref::<WsgState>().unwrap();
+        let agent = agent.downcast_ref::<Animal>().unwrap();
+        SpriteType::Emoji(state.food_web.species[agent.species].emoji.clone())
     }
 
     fn location(&self, agent: &Box<dyn Agent>, state: &Box<&dyn State>) -> (f32, f32, f32) {
         let state = state.as_any().downcast_ref::<WsgState>().unwrap();
-        let agent = agent.downcast_ref::<Sheep>().unwrap();
-        let loc = state.sheep_grid.get_location(*agent);
+        let agent = agent.downcast_ref::<Animal>().unwrap();
+        let loc = state.layers[agent.species].get_location(*agent);
         match loc {
             Some(loc) => (loc.x as f32, loc.y as f32, 1.),
             None => (agent.loc.x as f32, agent.loc.y as f32, 1.),
@@ -32,7 +34,7 @@ impl AgentRender for SheepVis {
     }
 
     fn rotation(&self, agent: &Box<dyn Agent>, _state: &Box<&dyn State>) -> f32 {
-        let agent = agent.downcast_ref::<Sheep>().unwrap();
+        let agent = agent.downcast_ref::<Animal>().unwrap();
         if let Some(Int2D { x, y }) = agent.last {
             ((y - agent.loc.y) as f32).atan2((x - agent.loc.x) as f32)
         } else {
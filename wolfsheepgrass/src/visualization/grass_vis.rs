@@ -1,24 +1,14 @@
+use crate::model::grass::Grass;
 use crate::model::state::WsgState;
-use crate::FULL_GROWN;
+use crate::palette;
 use krabmaga::engine::fields::dense_number_grid_2d::DenseNumberGrid2D;
 use krabmaga::engine::location::Int2D;
 use krabmaga::{bevy::prelude::Image, visualization::fields::number_grid_2d::BatchRender};
 
-impl BatchRender<WsgState> for DenseNumberGrid2D<u16> {
+impl BatchRender<WsgState> for DenseNumberGrid2D<Grass> {
     fn get_pixel(&self, loc: &Int2D) -> [u8; 4] {
         match self.get_value(loc) {
-            Some(val) => {
-                let growth = val;
-                if (growth as f32 / FULL_GROWN as f32) < 0.5 {
-                    [139u8, 69u8, 19u8, 180u8]
-                } else if (growth as f32 / FULL_GROWN as f32) < 0.7 {
-                    [128u8, 128u8, 0u8, 150u8]
-                } else if growth == FULL_GROWN {
-                    [0u8, 128u8, 0u8, 255u8]
-                } else {
-                    [0u8, 255u8, 0u8, 255u8]
-                }
-            }
+            Some(grass) => palette::grass(grass),
             None => [0u8, 255u8, 0u8, 0u8],
         }
     }
@@ -1,4 +1,4 @@
+pub mod animal_vis;
 pub mod grass_vis;
-pub mod sheep_vis;
+pub mod terrain_vis;
 pub mod vis_state;
-pub mod wolf_vis;
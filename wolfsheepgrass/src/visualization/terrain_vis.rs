@@ -0,0 +1,23 @@
+use crate::model::state::WsgState;
+use crate::model::terrain::Terrain;
+use crate::palette;
+use krabmaga::engine::location::Int2D;
+use krabmaga::{bevy::prelude::Image, visualization::fields::number_grid_2d::BatchRender};
+
+impl BatchRender<WsgState> for Terrain {
+    fn get_pixel(&self, loc: &Int2D) -> [u8; 4] {
+        palette::land(self.land(loc))
+    }
+
+    fn get_dimensions(&self) -> (u32, u32) {
+        (self.dim.0 as u32, self.dim.1 as u32)
+    }
+
+    fn get_layer(&self) -> f32 {
+        -1.
+    }
+
+    fn get_texture_from_state(state: &WsgState) -> Image {
+        state.world.terrain.texture()
+    }
+}
@@ -1,8 +1,7 @@
-use crate::model::sheep::Sheep;
+use crate::model::animal::Animal;
+use crate::model::genome::Genome;
 use crate::model::state::WsgState;
-use crate::model::wolf::Wolf;
-use crate::visualization::sheep_vis::SheepVis;
-use crate::visualization::wolf_vis::WolfVis;
+use crate::visualization::animal_vis::AnimalVis;
 use krabmaga::bevy::ecs as bevy_ecs;
 use krabmaga::bevy::ecs::system::Resource;
 use krabmaga::bevy::prelude::Commands;
@@ -36,12 +35,8 @@ impl VisualizationState<WsgState> for VisState {
         agent: &Box<dyn Agent>,
         _state: &WsgState,
     ) -> Option<Box<dyn AgentRender>> {
-        if let Some(wolf) = agent.downcast_ref::<Wolf>() {
-            Some(Box::new(WolfVis { id: wolf.id }))
-        } else {
-            let sheep = agent.downcast_ref::<Sheep>().unwrap();
-            Some(Box::new(SheepVis { id: sheep.id }))
-        }
+        let animal = agent.downcast_ref::<Animal>().unwrap();
+        Some(Box::new(AnimalVis { id: animal.id }))
     }
 
     fn get_agent(
@@ -50,29 +45,18 @@ impl VisualizationState<WsgState> for VisState {
         state: &Box<&dyn State>,
     ) -> Option<Box<dyn Agent>> {
         let state = state.as_any().downcast_ref::<WsgState>().unwrap();
-        if let Some(_wolf_vis) = agent_render.downcast_ref::<WolfVis>() {
-            match state.wolves_grid.get(&Wolf::new(
-                agent_render.get_id(),
-                Int2D { x: 0, y: 0 },
-                0.,
-                0.,
-                0.,
-            )) {
-                Some(matching_agent) => Some(Box::new(matching_agent)),
-                None => None,
-            }
-        } else {
-            match state.sheep_grid.get(&Sheep::new(
-                agent_render.get_id(),
-                Int2D { x: 0, y: 0 },
-                0.,
-                0.,
-                0.,
-            )) {
-                Some(matching_agent) => Some(Box::new(matching_agent)),
-                None => None,
-            }
-        }
+        let probe = Animal::new(
+            agent_render.get_id(),
+            0,
+            Int2D { x: 0, y: 0 },
+            0.,
+            Genome::default(),
+        );
+        state
+            .layers
+            .iter()
+            .find_map(|layer| layer.get(&probe))
+            .map(|matching_agent| Box::new(matching_agent) as Box<dyn Agent>)
     }
 
     fn before_render(
@@ -82,15 +66,17 @@ impl VisualizationState<WsgState> for VisState {
         commands: &mut Commands,
         asset_factory: &mut AssetHandleFactoryResource,
     ) {
-        // let new_sheep = state.new_sheep.lock().unwrap();
-        // for sheep in &*new_sheep {
-        for sheep in &state.new_sheep {
-            //let boxed_agent = &(*sheep).as_agent();
-            let boxed_agent = &sheep.as_agent();
+        let born: Vec<Animal> = state
+            .lifecycles
+            .iter()
+            .flat_map(|lifecycle| lifecycle.born.iter().copied())
+            .collect();
+        for animal in born {
+            let boxed_agent = &animal.as_agent();
             let boxed_state = Box::new(state.as_state());
-            let sheep_vis = self.get_agent_render(boxed_agent, state);
+            let animal_vis = self.get_agent_render(boxed_agent, state);
             let SpriteType::Emoji(emoji_code) =
-                sheep_vis.unwrap().sprite(boxed_agent, &boxed_state);
+                animal_vis.unwrap().sprite(boxed_agent, &boxed_state);
             let sprite_render = asset_factory.get_emoji_loader(emoji_code);
             self.setup_agent_graphics(
                 boxed_agent,
@@ -100,23 +86,6 @@ impl VisualizationState<WsgState> for VisState {
                 &boxed_state,
             );
         }
-
-        // let new_wolves = state.new_wolves.lock().unwrap();
-        // for wolf in &*new_wolves {
-        for wolf in &state.new_wolves {
-            let boxed_wolf = &wolf.as_agent();
-            let boxed_state = Box::new(state.as_state());
-            let wolf_vis = self.get_agent_render(boxed_wolf, state);
-            let SpriteType::Emoji(emoji_code) = wolf_vis.unwrap().sprite(boxed_wolf, &boxed_state);
-            let sprite_render = asset_factory.get_emoji_loader(emoji_code);
-            self.setup_agent_graphics(
-                boxed_wolf,
-                self.get_agent_render(boxed_wolf, state).unwrap(),
-                sprite_render,
-                commands,
-                &boxed_state,
-            );
-        }
     }
 }
 
@@ -127,6 +96,11 @@ impl VisState {
         commands: &mut Commands,
         sim: &mut SimulationDescriptor,
     ) {
+        // the terrain does not change, it is drawn once under the grass
+        state
+            .world
+            .terrain
+            .render(&mut *sprite_render_factory, commands, sim);
         state
             .grass_field
             .render(&mut *sprite_render_factory, commands, sim);
@@ -0,0 +1,1156 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use examples_common::checkpoint::{Checkpoints, Resumable, ScheduleSnapshot};
+use examples_common::observer::{Format, Observer, Output, TimeSeries};
+use examples_common::sweep::{Design, Sweep};
+use krabmaga::engine::fields::dense_object_grid_2d::DenseGrid2D;
+use krabmaga::engine::location::Int2D;
+use krabmaga::engine::schedule::Schedule;
+use krabmaga::engine::state::State;
+use wolfsheepgrass::analysis::{self, Analysis};
+use wolfsheepgrass::model::animal::Animal;
+use wolfsheepgrass::model::genome::{GenePool, Genome};
+use wolfsheepgrass::model::grass::Grass;
+use wolfsheepgrass::model::movement::{Neighbourhood, Topology, World};
+use wolfsheepgrass::model::params::Params;
+use wolfsheepgrass::model::season::{self, Season};
+use wolfsheepgrass::model::species::{FoodWeb, Species};
+use wolfsheepgrass::model::state::{LifeState, WsgSnapshot, WsgState};
+use wolfsheepgrass::model::terrain::{Land, Terrain};
+
+fn small_params(seed: u64) -> Params {
+    Params {
+        width: 30,
+        height: 30,
+        seed,
+        ..Default::default()
+    }
+}
+
+fn init(params: Params) -> (WsgState, Schedule) {
+    let mut state = WsgState::new(params).unwrap();
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    (state, schedule)
+}
+
+// (id, x, y, energy bits) of every scheduled animal, sorted by id
+fn animals(schedule: &Schedule) -> Vec<(u32, i32, i32, u64)> {
+    let mut animals: Vec<_> = schedule
+        .get_all_events()
+        .iter()
+        .map(|agent| {
+            let animal = agent.downcast_ref::<Animal>().unwrap();
+            (
+                animal.id,
+                animal.loc.x,
+                animal.loc.y,
+                animal.energy.to_bits(),
+            )
+        })
+        .collect();
+    animals.sort();
+    animals
+}
+
+#[test]
+fn init_schedules_every_animal() {
+    let params = small_params(0);
+    let expected = (params.sheep.initial + params.wolves.initial) as usize;
+    let (_state, schedule) = init(params);
+    assert_eq!(schedule.get_all_events().len(), expected);
+}
+
+#[test]
+fn energy_stays_positive_while_alive() {
+    let (mut state, mut schedule) = init(small_params(1));
+    for _ in 0..100 {
+        schedule.step(&mut state);
+        for agent in schedule.get_all_events() {
+            let animal = agent.downcast_ref::<Animal>().unwrap();
+            if animal.animal_state == LifeState::Alive {
+                assert!(
+                    animal.energy > 0.,
+                    "animal {} has {}",
+                    animal.id,
+                    animal.energy
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn grass_stays_within_growth_range() {
+    let (mut state, mut schedule) = init(small_params(2));
+    for _ in 0..50 {
+        schedule.step(&mut state);
+        for x in 0..state.dim.0 {
+            for y in 0..state.dim.1 {
+                let grass = state.grass_field.get_value(&Int2D { x, y }).unwrap();
+                assert_eq!(grass.regrowth, state.params.grass_regrowth_time);
+                assert!(grass.countdown <= grass.regrowth);
+                if grass.grown {
+                    assert_eq!(grass.countdown, grass.regrowth);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn eaten_grass_grows_back_after_the_regrowth_time() {
+    let mut grass = Grass::grown(3).eaten();
+    for _ in 0..3 {
+        assert!(!grass.grown);
+        grass = grass.grow();
+    }
+    assert!(!grass.grown);
+    assert_eq!(grass.countdown, 0);
+    grass = grass.grow();
+    assert_eq!(grass, Grass::grown(3));
+    assert_eq!(grass.grow(), grass);
+}
+
+#[test]
+fn regrowth_times_are_read_from_a_raster() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("regrowth.txt");
+    // three columns, two rows: the first line is the top of the field
+    fs::write(&path, "1 2 3\n4,5,6\n").unwrap();
+    let params = Params {
+        width: 3,
+        height: 2,
+        sheep: Species {
+            initial: 0,
+            ..Species::sheep()
+        },
+        wolves: Species {
+            initial: 0,
+            ..Species::wolves()
+        },
+        grass_regrowth_raster: Some(path.clone()),
+        ..Default::default()
+    };
+    params.validate().unwrap();
+    let (mut state, mut schedule) = init(params.clone());
+    schedule.step(&mut state);
+    let regrowth = |x, y| {
+        state
+            .grass_field
+            .get_value(&Int2D { x, y })
+            .unwrap()
+            .regrowth
+    };
+    assert_eq!((regrowth(0, 1), regrowth(2, 1)), (1, 3));
+    assert_eq!((regrowth(0, 0), regrowth(2, 0)), (4, 6));
+
+    fs::write(&path, "1 2 3\n4 0 6\n").unwrap();
+    assert!(params.validate().unwrap_err().contains("`0` on row 2"));
+    let params = Params { width: 4, ..params };
+    assert!(params.validate().unwrap_err().contains("expected 4 values"));
+}
+
+fn season(name: &str, steps: u64, growth: f64, capacity: f64) -> Season {
+    Season {
+        name: String::from(name),
+        steps,
+        growth,
+        capacity,
+    }
+}
+
+#[test]
+fn seasons_follow_each_other_every_year() {
+    let seasons = [season("summer", 3, 1., 1.), season("winter", 2, 0., 1.)];
+    let names: Vec<&str> = (0..7)
+        .map(|step| season::at(&seasons, step).unwrap().name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        ["summer", "summer", "summer", "winter", "winter", "summer", "summer"]
+    );
+    assert!(season::at(&[], 3).is_none());
+
+    let grass = Grass::grown(5).eaten();
+    assert_eq!(grass.grow_by(0), grass);
+    assert_eq!(grass.grow_by(2), Grass::bare(3, 5));
+    assert_eq!(grass.grow_by(5), Grass::bare(0, 5));
+    assert_eq!(grass.grow_by(6), Grass::grown(5));
+
+    let mut params = Params {
+        seasons: seasons.to_vec(),
+        ..Default::default()
+    };
+    params.set("WINTER.growth", "0.5").unwrap();
+    assert_eq!(params.seasons[1].growth, 0.5);
+    assert!(params.set("autumn.growth", "1").is_err());
+    params.seasons[1].capacity = 2.;
+    assert!(params.validate().unwrap_err().contains("`capacity`"));
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/seasons.toml");
+    let params = Params::from_file(&path).unwrap();
+    let year: Vec<&str> = params.seasons.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(year, ["spring", "summer", "autumn", "winter"]);
+    assert!(params.terrain.is_some());
+}
+
+// Patches of grown grass after each step of a field without animals
+fn grown_grass(seasons: Vec<Season>, steps: usize) -> Vec<f64> {
+    let params = Params {
+        width: 10,
+        height: 10,
+        sheep: Species {
+            initial: 0,
+            ..Species::sheep()
+        },
+        wolves: Species {
+            initial: 0,
+            ..Species::wolves()
+        },
+        seasons,
+        ..Default::default()
+    };
+    let (mut state, mut schedule) = init(params);
+    for _ in 0..steps {
+        schedule.step(&mut state);
+    }
+    state.observer.series("grass").unwrap().to_vec()
+}
+
+#[test]
+fn grass_grows_with_the_seasons_up_to_their_capacity() {
+    // nothing grows back in winter
+    let grass = grown_grass(vec![season("winter", 10, 0., 1.)], 10);
+    assert!(grass.iter().all(|&g| g == grass[0]), "{:?}", grass);
+
+    // at half speed, grass grows back on every other step
+    let grass = grown_grass(vec![season("spring", 10, 0.5, 1.)], 45);
+    assert!(grass[..40].iter().any(|&g| g < 100.));
+    assert_eq!(grass[44], 100.);
+    for step in (2..45).step_by(2) {
+        assert_eq!(grass[step], grass[step - 1], "step {}", step);
+    }
+
+    // the field holds no more grass than the capacity of the season
+    let grass = grown_grass(vec![season("summer", 10, 1., 0.8)], 40);
+    assert!(grass.iter().all(|&g| g <= 80.), "{:?}", grass);
+    assert_eq!(grass[39], 80.);
+
+    // the capacity changes with the season, and the grass follows
+    let year = vec![season("summer", 30, 1., 0.9), season("winter", 30, 1., 0.6)];
+    let grass = grown_grass(year, 60);
+    assert_eq!(grass[29], 90.);
+    assert_eq!(grass[59], 90., "grass above the capacity is not killed");
+}
+
+#[test]
+fn terrain_is_read_from_a_raster_or_an_image() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join("terrain.csv");
+    // three columns, two rows: the first line is the top of the field
+    fs::write(&path, "f,b,w\nwater fertile BARREN\n").unwrap();
+    let terrain = Terrain::read(&path, 3, 2).unwrap();
+    let land = |terrain: &Terrain, x, y| terrain.land(&Int2D { x, y });
+    assert_eq!(
+        [0, 1, 2].map(|x| land(&terrain, x, 1)),
+        [Land::Fertile, Land::Barren, Land::Water]
+    );
+    assert_eq!(
+        [0, 1, 2].map(|x| land(&terrain, x, 0)),
+        [Land::Water, Land::Fertile, Land::Barren]
+    );
+    assert_eq!(terrain.count(Land::Water), 2);
+
+    fs::write(&path, "f b w\nf x f\n").unwrap();
+    let error = Terrain::read(&path, 3, 2).unwrap_err();
+    assert!(error.contains("invalid land `x` on row 2"), "{}", error);
+    fs::write(&path, "w w\n").unwrap();
+    assert!(Terrain::read(&path, 2, 1)
+        .unwrap_err()
+        .contains("every cell is water"));
+
+    // green is fertile, blue water, and anything else barren
+    let path = dir.join("terrain.png");
+    let mut encoder = png::Encoder::new(fs::File::create(&path).unwrap(), 2, 2);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    let top = [0, 200, 0, 20, 40, 220];
+    let bottom = [120, 80, 40, 0, 0, 0];
+    writer.write_image_data(&[top, bottom].concat()).unwrap();
+    writer.finish().unwrap();
+    let terrain = Terrain::read(&path, 2, 2).unwrap();
+    assert_eq!(
+        [(0, 1), (1, 1), (0, 0), (1, 0)].map(|(x, y)| land(&terrain, x, y)),
+        [Land::Fertile, Land::Water, Land::Barren, Land::Barren]
+    );
+    assert!(Terrain::read(&path, 3, 2)
+        .unwrap_err()
+        .contains("expected 3x2 pixels"));
+}
+
+#[test]
+fn unreadable_files_and_food_webs_are_errors() {
+    let missing = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("missing.csv");
+    let error = |params: Params| WsgState::new(params).err().unwrap();
+    assert!(error(Params {
+        terrain: Some(missing.clone()),
+        ..small_params(0)
+    })
+    .contains("missing.csv"));
+    assert!(error(Params {
+        grass_regrowth_raster: Some(missing),
+        ..small_params(0)
+    })
+    .contains("missing.csv"));
+    assert!(error(Params {
+        wolves: Species {
+            diet: vec![String::from("bears")],
+            ..Species::wolves()
+        },
+        ..small_params(0)
+    })
+    .contains("unknown food `bears`"));
+}
+
+#[test]
+fn animals_never_cross_water() {
+    // a river down the middle of the field, and a barren strip along its left edge
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("river.txt");
+    let row = |_| "b f f f f w f f f f\n";
+    fs::write(&path, (0..10).map(row).collect::<String>()).unwrap();
+    let params = Params {
+        width: 10,
+        height: 10,
+        sheep: Species {
+            initial: 30,
+            seeks_food: true,
+            ..Species::sheep()
+        },
+        wolves: Species {
+            initial: 10,
+            ..Species::wolves()
+        },
+        terrain: Some(path),
+        ..small_params(3)
+    };
+    params.validate().unwrap();
+    let (mut state, mut schedule) = init(params);
+
+    let world = &state.world;
+    let (river, shore) = (Int2D { x: 5, y: 5 }, Int2D { x: 4, y: 5 });
+    assert!(world.place(5, 5).is_none());
+    assert!(world.toward(shore, Int2D { x: 7, y: 5 }) == shore);
+    assert!(world
+        .away(Int2D { x: 6, y: 5 }, Int2D { x: 7, y: 5 })
+        .is_none());
+    assert!(world.nearest(shore, 1, |c| *c == river) == Some(river));
+    for y in 0..10 {
+        assert!(state
+            .grass_field
+            .get_value_unbuffered(&Int2D { x: 0, y })
+            .is_none());
+        assert!(state
+            .grass_field
+            .get_value_unbuffered(&Int2D { x: 5, y })
+            .is_none());
+        assert!(state
+            .grass_field
+            .get_value_unbuffered(&Int2D { x: 3, y })
+            .is_some());
+    }
+
+    // every animal stays on the bank it started on
+    let mut banks = std::collections::HashMap::new();
+    for _ in 0..50 {
+        for (id, x, _, _) in animals(&schedule) {
+            assert_ne!(x, 5);
+            assert_eq!(*banks.entry(id).or_insert(x < 5), x < 5, "animal {}", id);
+        }
+        schedule.step(&mut state);
+    }
+    assert!(banks.values().any(|&left| left) && banks.values().any(|&left| !left));
+}
+
+// Genomes of the scheduled sheep and wolves
+fn genomes(schedule: &Schedule) -> (Vec<Genome>, Vec<Genome>) {
+    let (mut sheep, mut wolves) = (Vec::new(), Vec::new());
+    for agent in schedule.get_all_events() {
+        let animal = agent.downcast_ref::<Animal>().unwrap();
+        match animal.species {
+            0 => sheep.push(animal.genome),
+            _ => wolves.push(animal.genome),
+        }
+    }
+    (sheep, wolves)
+}
+
+#[test]
+fn genomes_are_inherited_unchanged_without_mutations() {
+    let params = small_params(8);
+    let initial = [params.sheep.genome(), params.wolves.genome()];
+    let (mut state, mut schedule) = init(params);
+    for _ in 0..30 {
+        schedule.step(&mut state);
+    }
+    assert!(state.next_id > state.params.sheep.initial + state.params.wolves.initial);
+    let (sheep, wolves) = genomes(&schedule);
+    assert!(sheep.iter().all(|genome| *genome == initial[0]));
+    assert!(wolves.iter().all(|genome| *genome == initial[1]));
+    assert_eq!(
+        state.observer.series("sheep_metabolism_sd").unwrap()[29],
+        0.
+    );
+}
+
+#[test]
+fn mutations_spread_the_traits_of_newborns() {
+    let params = Params {
+        mutation_rate: 1.,
+        mutation_size: 0.2,
+        ..small_params(9)
+    };
+    let initial = params.sheep.genome();
+    let (mut state, mut schedule) = init(params);
+    for _ in 0..30 {
+        schedule.step(&mut state);
+    }
+    let (sheep, _) = genomes(&schedule);
+    let mutated = sheep.iter().filter(|genome| **genome != initial).count();
+    assert!(mutated > 0);
+    for genome in &sheep {
+        assert!((0.0..=1.0).contains(&genome.momentum));
+        assert!(genome.metabolism >= 0. && genome.vision >= 0.);
+    }
+    let sd = state.observer.series("sheep_momentum_sd").unwrap();
+    assert!(*sd.last().unwrap() > 0.);
+}
+
+#[test]
+fn gene_pools_follow_births_and_deaths() {
+    let params = Params {
+        mutation_rate: 1.,
+        mutation_size: 0.2,
+        ..small_params(9)
+    };
+    let (mut state, mut schedule) = init(params);
+    for _ in 0..50 {
+        schedule.step(&mut state);
+        let (sheep, wolves) = genomes(&schedule);
+        for (kept, genomes) in state.genes.iter().zip([sheep, wolves]) {
+            let mut counted = GenePool::default();
+            for genome in &genomes {
+                counted.add(genome);
+            }
+            for ((mean, sd), (counted_mean, counted_sd)) in
+                kept.stats().into_iter().zip(counted.stats())
+            {
+                assert!((mean - counted_mean).abs() < 1e-9);
+                assert!((sd - counted_sd).abs() < 1e-6);
+            }
+        }
+    }
+}
+
+#[test]
+fn animals_below_the_reproduction_threshold_do_not_reproduce() {
+    let mut params = small_params(10);
+    params.sheep.reproduction_threshold = 1000.;
+    params.wolves.reproduction_threshold = 1000.;
+    let (mut state, mut schedule) = init(params);
+    for _ in 0..20 {
+        schedule.step(&mut state);
+    }
+    assert_eq!(
+        state.next_id,
+        state.params.sheep.initial + state.params.wolves.initial
+    );
+    let born = state.observer.series("born_sheep").unwrap();
+    assert!(born.iter().all(|&b| b == 0.));
+}
+
+fn world(topology: Topology, neighbourhood: Neighbourhood) -> World {
+    World {
+        dim: (10, 10),
+        topology,
+        neighbourhood,
+        terrain: Terrain::fertile((10, 10)),
+    }
+}
+
+fn xy(loc: Option<Int2D>) -> Option<(i32, i32)> {
+    loc.map(|Int2D { x, y }| (x, y))
+}
+
+#[test]
+fn animals_sense_the_nearest_cell_within_their_vision() {
+    let world = world(Topology::Bounded, Neighbourhood::Moore);
+    let loc = Int2D { x: 5, y: 5 };
+    let targets = [Int2D { x: 8, y: 5 }, Int2D { x: 3, y: 7 }];
+    let nearest = |radius| world.nearest(loc, radius, |c| targets.contains(c));
+    assert_eq!(xy(nearest(1)), None);
+    assert_eq!(xy(nearest(2)), Some((3, 7)));
+    assert_eq!(xy(Some(world.toward(loc, targets[1]))), Some((4, 6)));
+    assert_eq!(xy(world.away(loc, targets[0])), Some((4, 5)));
+    // cornered: there is no cell further away
+    let corner = Int2D { x: 0, y: 0 };
+    assert_eq!(xy(world.away(corner, Int2D { x: 1, y: 1 })), None);
+}
+
+#[test]
+fn topologies_handle_the_edges() {
+    let edge = Int2D { x: 9, y: 0 };
+    let bounded = world(Topology::Bounded, Neighbourhood::Moore);
+    let torus = world(Topology::Toroidal, Neighbourhood::Moore);
+    let reflecting = world(Topology::Reflecting, Neighbourhood::Moore);
+    assert_eq!(xy(bounded.place(10, -1)), None);
+    assert_eq!(xy(torus.place(10, -1)), Some((0, 9)));
+    assert_eq!(xy(reflecting.place(10, -1)), Some((8, 1)));
+
+    // a toroidal world sees, and moves, across its edges
+    let across = Int2D { x: 0, y: 9 };
+    assert_eq!(torus.distance(edge, across), 1);
+    assert_eq!(xy(torus.nearest(edge, 1, |c| *c == across)), Some((0, 9)));
+    assert_eq!(xy(bounded.nearest(edge, 1, |c| *c == across)), None);
+    assert_eq!(xy(Some(torus.toward(edge, across))), Some((0, 9)));
+    assert_eq!(xy(torus.away(across, edge)), Some((1, 8)));
+
+    // von Neumann moves are orthogonal, and distances count both axes
+    let rook = world(Topology::Bounded, Neighbourhood::VonNeumann);
+    let loc = Int2D { x: 5, y: 5 };
+    assert_eq!(rook.distance(loc, Int2D { x: 7, y: 6 }), 3);
+    assert_eq!(
+        xy(Some(rook.toward(loc, Int2D { x: 7, y: 6 }))),
+        Some((6, 5))
+    );
+    assert_eq!(xy(rook.nearest(loc, 1, |c| c.x == 6 && c.y == 6)), None);
+}
+
+#[test]
+fn animals_move_within_their_neighbourhood_in_every_topology() {
+    for topology in [Topology::Bounded, Topology::Toroidal, Topology::Reflecting] {
+        for neighbourhood in [Neighbourhood::Moore, Neighbourhood::VonNeumann] {
+            let params = Params {
+                width: 15,
+                height: 10,
+                topology,
+                neighbourhood,
+                ..small_params(12)
+            };
+            let (mut state, mut schedule) = init(params);
+            let world = state.world.clone();
+            let mut before = animals(&schedule);
+            for _ in 0..20 {
+                schedule.step(&mut state);
+                let after = animals(&schedule);
+                for (id, x, y, _) in &after {
+                    assert!((0..15).contains(x) && (0..10).contains(y));
+                    if let Some((_, bx, by, _)) = before.iter().find(|a| a.0 == *id) {
+                        let d = world.distance(Int2D { x: *bx, y: *by }, Int2D { x: *x, y: *y });
+                        assert!(d <= 1, "{:?} {:?}: moved {}", topology, neighbourhood, d);
+                    }
+                }
+                before = after;
+            }
+        }
+    }
+}
+
+#[test]
+fn hunting_wolves_catch_the_sheep_they_see() {
+    let kills = |wolves_hunt| {
+        let params = Params {
+            width: 12,
+            height: 12,
+            sheep: Species {
+                initial: 1,
+                prob_reproduction: 0.,
+                gain_energy: 100.,
+                ..Species::sheep()
+            },
+            wolves: Species {
+                initial: 1,
+                prob_reproduction: 0.,
+                gain_energy: 100.,
+                seeks_food: wolves_hunt,
+                vision: 12.,
+                ..Species::wolves()
+            },
+            ..small_params(11)
+        };
+        let (mut state, mut schedule) = init(params);
+        for _ in 0..40 {
+            schedule.step(&mut state);
+        }
+        state
+            .observer
+            .series("dead_sheep_predation")
+            .unwrap()
+            .iter()
+            .sum::<f64>()
+    };
+    assert_eq!(kills(true), 1.);
+    assert_eq!(kills(false), 0.);
+}
+
+// (id, x, y) of every animal on a grid, sorted by id
+fn on_grid(grid: &DenseGrid2D<Animal>, dim: (i32, i32)) -> Vec<(u32, i32, i32)> {
+    let mut animals = Vec::new();
+    for x in 0..dim.0 {
+        for y in 0..dim.1 {
+            for animal in grid.get_objects(&Int2D { x, y }).unwrap_or_default() {
+                animals.push((animal.id, x, y));
+            }
+        }
+    }
+    animals.sort();
+    animals
+}
+
+#[test]
+fn populations_match_the_grids_and_the_schedule_every_step() {
+    let params = Params {
+        sheep: Species {
+            max_age: 10,
+            ..Species::sheep()
+        },
+        wolves: Species {
+            max_age: 20,
+            seeks_food: true,
+            ..Species::wolves()
+        },
+        ..small_params(12)
+    };
+    let (mut state, mut schedule) = init(params);
+    let mut population: Vec<f64> = state
+        .food_web
+        .species
+        .iter()
+        .map(|s| s.initial as f64)
+        .collect();
+    let causes = ["starvation", "predation", "old_age"];
+    let mut deaths = vec![[0.; 3]; population.len()];
+    for _ in 0..100 {
+        schedule.step(&mut state);
+
+        let mut scheduled = vec![Vec::new(); population.len()];
+        for (agent, _) in schedule.events.iter() {
+            let animal = agent.agent.downcast_ref::<Animal>().unwrap();
+            assert_eq!(agent.id, animal.id);
+            scheduled[animal.species].push((animal.id, animal.loc.x, animal.loc.y));
+        }
+
+        let last = |name: &str| *state.observer.series(name).unwrap().last().unwrap();
+        for (i, species) in state.food_web.species.iter().enumerate() {
+            let animals = &mut scheduled[i];
+            animals.sort();
+            assert_eq!(&on_grid(&state.layers[i], state.dim), animals);
+            assert_eq!(last(&species.name), animals.len() as f64);
+            assert_eq!(state.population[i] as usize, animals.len());
+
+            let dead = causes.map(|cause| last(&format!("dead_{}_{}", species.name, cause)));
+            population[i] += last(&format!("born_{}", species.name)) - dead.iter().sum::<f64>();
+            assert_eq!(population[i], animals.len() as f64);
+            for (total, dead) in deaths[i].iter_mut().zip(dead) {
+                *total += dead;
+            }
+        }
+    }
+    // every cause of death came up, except wolves being eaten
+    assert!(deaths[0].iter().all(|&dead| dead > 0.), "{:?}", deaths);
+    assert!(deaths[1][0] > 0. && deaths[1][2] > 0., "{:?}", deaths);
+    assert_eq!(deaths[1][1], 0.);
+}
+
+#[test]
+fn species_of_a_scenario_join_the_food_web() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/food_web.toml");
+    let params = Params {
+        width: 30,
+        height: 30,
+        ..Params::from_file(&path).unwrap()
+    };
+    let (mut state, mut schedule) = init(params);
+    let web = &state.food_web;
+    let [sheep, wolves, rabbits, foxes] =
+        ["sheep", "wolves", "rabbits", "foxes"].map(|name| web.index(name).unwrap());
+    assert_eq!(web.prey(wolves), [sheep, rabbits]);
+    assert_eq!(web.predators(wolves), [foxes]);
+    assert!(web.grazes(rabbits) && !web.grazes(foxes));
+
+    for _ in 0..50 {
+        schedule.step(&mut state);
+        for (i, layer) in state.layers.iter().enumerate() {
+            let scheduled = schedule
+                .get_all_events()
+                .iter()
+                .filter(|agent| agent.downcast_ref::<Animal>().unwrap().species == i)
+                .count();
+            assert_eq!(on_grid(layer, state.dim).len(), scheduled);
+        }
+    }
+    let eaten = |species: &str| {
+        state
+            .observer
+            .series(&format!("dead_{}_predation", species))
+            .unwrap()
+            .iter()
+            .sum::<f64>()
+    };
+    assert!(eaten("rabbits") > 0. && eaten("wolves") > 0.);
+    assert_eq!(eaten("foxes"), 0.);
+    assert!(state.observer.series("foxes_vision_mean").is_some());
+}
+
+#[test]
+fn food_webs_are_validated() {
+    let with_species = |diet: &str| {
+        let mut params = Params::default();
+        params.species.push(Species {
+            name: String::from("foxes"),
+            diet: diet.split(',').map(String::from).collect(),
+            ..Species::default()
+        });
+        params
+    };
+    assert!(with_species("wolves,grass").validate().is_ok());
+    let error = with_species("badgers").validate().unwrap_err();
+    assert!(error.contains("unknown food `badgers`"), "{}", error);
+    let error = with_species("foxes").validate().unwrap_err();
+    assert!(error.contains("cannot eat itself"), "{}", error);
+    let mut params = with_species("sheep");
+    params.species.push(params.species[0].clone());
+    assert!(params.validate().unwrap_err().contains("defined twice"));
+
+    let mut params = with_species("sheep");
+    params.set("FOXES.gain_energy", "7").unwrap();
+    params.set("wolves.diet", "sheep, foxes").unwrap();
+    assert_eq!(params.species[0].gain_energy, 7.);
+    assert_eq!(params.wolves.diet, ["sheep", "foxes"]);
+    assert!(params.set("foxes.speed", "1").is_err());
+    assert!(params.set("badgers.initial", "1").is_err());
+}
+
+#[test]
+fn sheep_and_wolves_are_set_like_any_species() {
+    let mut params = small_params(13);
+    params.set("sheep.vision", "3").unwrap();
+    params.set("WOLVES.metabolism", "2").unwrap();
+    assert!(params.set("sheep.speed", "1").is_err());
+    let (_state, schedule) = init(params);
+    let (sheep, wolves) = genomes(&schedule);
+    assert!(sheep
+        .iter()
+        .all(|genome| genome.vision == 3. && genome.metabolism == 1.));
+    assert!(wolves
+        .iter()
+        .all(|genome| genome.vision == 1. && genome.metabolism == 2.));
+
+    // the keys left out of a table keep the values of the original model
+    let params: Params = toml::from_str("[wolves]\nvision = 2.0").unwrap();
+    let defaults = Params::default();
+    assert_eq!(
+        params.wolves,
+        Species {
+            vision: 2.,
+            ..defaults.wolves
+        }
+    );
+    assert_eq!(params.sheep, defaults.sheep);
+    assert!(toml::from_str::<Params>("[sheep]\nspeed = 1.0").is_err());
+}
+
+#[test]
+fn same_seed_replays_same_trajectory() {
+    let run = |seed| {
+        let (mut state, mut schedule) = init(small_params(seed));
+        for _ in 0..50 {
+            schedule.step(&mut state);
+        }
+        animals(&schedule)
+    };
+    assert!(run(7) == run(7));
+    assert!(run(7) != run(8));
+}
+
+#[test]
+fn observer_exports_one_row_per_step() {
+    let output = Output {
+        dir: PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("metrics"),
+        run_id: String::from("observer_exports_one_row_per_step"),
+        seed: 4,
+        format: Format::Csv,
+    };
+    let path = output.path(0);
+    let (mut state, mut schedule) = init(small_params(4));
+    state.observer = Observer::with_output(output);
+    for _ in 0..10 {
+        schedule.step(&mut state);
+        let animals = schedule.get_all_events().len() as f64;
+        let observer = &state.observer;
+        let sheep = observer.series("sheep").unwrap();
+        let wolves = observer.series("wolves").unwrap();
+        assert_eq!(sheep.last().unwrap() + wolves.last().unwrap(), animals);
+    }
+    state.observer.finish();
+
+    let csv = fs::read_to_string(path).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "step,sheep,wolves,grass,\
+         born_sheep,dead_sheep_starvation,dead_sheep_predation,dead_sheep_old_age,\
+         born_wolves,dead_wolves_starvation,dead_wolves_predation,dead_wolves_old_age,\
+         sheep_momentum_mean,sheep_momentum_sd,\
+         sheep_reproduction_threshold_mean,sheep_reproduction_threshold_sd,\
+         sheep_metabolism_mean,sheep_metabolism_sd,sheep_vision_mean,sheep_vision_sd,\
+         wolves_momentum_mean,wolves_momentum_sd,\
+         wolves_reproduction_threshold_mean,wolves_reproduction_threshold_sd,\
+         wolves_metabolism_mean,wolves_metabolism_sd,wolves_vision_mean,wolves_vision_sd"
+    );
+    assert_eq!(lines.len(), 11);
+    assert!(lines[10].starts_with("10,"));
+}
+
+#[test]
+fn repetitions_are_seeded_and_exported_apart() {
+    let output = Output {
+        dir: PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("metrics"),
+        run_id: String::from("repetitions_are_seeded_and_exported_apart"),
+        seed: 4,
+        format: Format::Csv,
+    };
+    let mut state = WsgState::new(small_params(4)).unwrap();
+    state.observer = Observer::with_output(output.clone());
+    let mut reps = Vec::new();
+    for rep in 0..2 {
+        let mut schedule = Schedule::new();
+        state.init(&mut schedule);
+        assert_eq!((state.observer.rep(), state.seed()), (rep, 4 + rep));
+        for _ in 0..20 {
+            schedule.step(&mut state);
+        }
+        reps.push(animals(&schedule));
+    }
+    state.observer.finish();
+
+    // the second repetition replays a run of the next seed
+    let (mut next, mut schedule) = init(small_params(5));
+    for _ in 0..20 {
+        schedule.step(&mut next);
+    }
+    assert!(reps[0] != reps[1]);
+    assert!(reps[1] == animals(&schedule));
+    for rep in 0..2 {
+        let csv = fs::read_to_string(output.path(rep)).unwrap();
+        assert_eq!(csv.lines().count(), 21);
+    }
+    assert!(output.path(1).ends_with("rep_1_seed_5.csv"));
+}
+
+// Populations of a predator following its prey a quarter of a period behind
+fn cycles(period: f64, steps: usize) -> (Vec<f64>, Vec<f64>) {
+    let wave = |t: usize, lag: f64| (std::f64::consts::TAU * (t as f64 - lag) / period).sin();
+    let prey = (0..steps).map(|t| 100. + 50. * wave(t, 0.)).collect();
+    let predator = (0..steps)
+        .map(|t| 20. + 10. * wave(t, period / 4.))
+        .collect();
+    (prey, predator)
+}
+
+#[test]
+fn oscillations_have_a_period_and_a_phase_lag() {
+    assert_eq!(analysis::mean_variance(&[1., 2., 3., 4.]), (2.5, 1.25));
+    assert_eq!(analysis::mean_variance(&[7.; 5]), (7., 0.));
+    assert_eq!(analysis::dominant_period(&[7.; 50]), None);
+
+    let (prey, predator) = cycles(40., 400);
+    let period = analysis::dominant_period(&prey).unwrap();
+    assert!((period - 40.).abs() < 0.5, "{}", period);
+    assert_eq!(
+        analysis::phase_lag(&prey, &predator, Some(period)),
+        Some(10)
+    );
+
+    // the wolves die out after 300 steps: the cycles before still count
+    let (sheep, mut wolves) = cycles(40., 400);
+    wolves[300..].fill(0.);
+    let series = TimeSeries {
+        names: vec![String::from("sheep"), String::from("wolves")],
+        steps: (1..=400).collect(),
+        values: vec![sheep, wolves],
+    };
+    let params = Params::default();
+    let web = FoodWeb::new(&params).unwrap();
+    let analysis = Analysis::new(&series, &web);
+    assert_eq!(analysis.steps, 400);
+    let [sheep, wolves] = [&analysis.populations[0], &analysis.populations[1]];
+    assert_eq!((sheep.extinction, wolves.extinction), (None, Some(301)));
+    assert!((sheep.mean - 100.).abs() < 1e-9, "{}", sheep.mean);
+    assert!((sheep.variance - 1250.).abs() < 1e-6, "{}", sheep.variance);
+    assert!((wolves.period.unwrap() - 40.).abs() < 0.5);
+    let lag = &analysis.lags[0];
+    assert_eq!(
+        (lag.predator.as_str(), lag.prey.as_str()),
+        ("wolves", "sheep")
+    );
+    assert_eq!(lag.lag, Some(10));
+    assert!((lag.phase.unwrap() - 0.25).abs() < 0.01);
+}
+
+#[test]
+fn analysis_is_written_when_the_run_ends() {
+    let output = Output {
+        dir: PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("metrics"),
+        run_id: String::from("analysis_is_written_when_the_run_ends"),
+        seed: 5,
+        format: Format::Csv,
+    };
+    let _ = fs::remove_dir_all(output.dir.join(&output.run_id));
+    let params = Params {
+        steps: 20,
+        ..small_params(5)
+    };
+    let mut state = WsgState::new(params).unwrap();
+    state.observer = Observer::with_output(output.clone());
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    for _ in 0..19 {
+        schedule.step(&mut state);
+        assert!(!state.end_condition(&mut schedule));
+    }
+    assert!(!Analysis::path(&output, 0).exists());
+    schedule.step(&mut state);
+    assert!(state.end_condition(&mut schedule));
+
+    let read = |output: &Output, rep| -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(Analysis::path(output, rep)).unwrap()).unwrap()
+    };
+    let json = read(&output, 0);
+    assert_eq!(json["steps"], 20);
+    assert_eq!(json["populations"][0]["species"], "sheep");
+    assert_eq!(json["populations"][1]["species"], "wolves");
+    assert_eq!(json["lags"][0]["predator"], "wolves");
+    let mean = state.observer.series("sheep").unwrap().iter().sum::<f64>() / 20.;
+    assert!((json["populations"][0]["mean"].as_f64().unwrap() - mean).abs() < 1e-9);
+
+    // the next repetition gets its own analysis
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    while !state.end_condition(&mut schedule) {
+        schedule.step(&mut state);
+    }
+    assert_eq!(
+        Analysis::path(&output, 1).file_name().unwrap(),
+        "analysis_rep_1_seed_6.json"
+    );
+    assert_eq!(read(&output, 1)["steps"], 20);
+    assert_eq!(read(&output, 0), json);
+
+    // a run ends early once every animal is dead
+    let mut params = Params {
+        steps: 20,
+        ..small_params(5)
+    };
+    params.set_agents(0);
+    let output = Output {
+        run_id: String::from("analysis_is_written_on_extinction"),
+        ..output
+    };
+    let mut state = WsgState::new(params).unwrap();
+    state.observer = Observer::with_output(output.clone());
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    schedule.step(&mut state);
+    assert!(state.end_condition(&mut schedule));
+    assert_eq!(read(&output, 0)["steps"], 1);
+}
+
+// Grass of every cell, column by column
+fn grass(state: &WsgState) -> Vec<Option<Grass>> {
+    let mut grass = Vec::new();
+    for x in 0..state.dim.0 {
+        for y in 0..state.dim.1 {
+            grass.push(state.grass_field.get_value(&Int2D { x, y }));
+        }
+    }
+    grass
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let (mut state, mut schedule) = init(small_params(5));
+    for _ in 0..20 {
+        schedule.step(&mut state);
+    }
+    let saved = serde_json::to_string(&(
+        state.snapshot(),
+        ScheduleSnapshot::take(&mut schedule, WsgState::save_agent),
+    ))
+    .unwrap();
+    let (snapshot, events): (WsgSnapshot, ScheduleSnapshot<Animal>) =
+        serde_json::from_str(&saved).unwrap();
+    let mut resumed = WsgState::restore(snapshot).unwrap();
+    let mut resumed_schedule = events.restore(WsgState::restore_agent);
+    assert!(animals(&resumed_schedule) == animals(&schedule));
+
+    for _ in 0..30 {
+        schedule.step(&mut state);
+        resumed_schedule.step(&mut resumed);
+    }
+    assert_eq!(resumed_schedule.step, schedule.step);
+    assert!(animals(&resumed_schedule) == animals(&schedule));
+    assert!(grass(&resumed) == grass(&state));
+}
+
+#[test]
+fn resume_completes_the_exported_metrics() {
+    let dir =
+        PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("resume_completes_the_exported_metrics");
+    let _ = fs::remove_dir_all(&dir);
+    let run = |run_id: &str, steps, resume| {
+        let params = small_params(6);
+        let mut state = WsgState::new(params.clone()).unwrap();
+        state.observer = Observer::with_output(Output {
+            dir: dir.clone(),
+            run_id: run_id.to_string(),
+            seed: params.seed,
+            format: Format::Csv,
+        });
+        let checkpoints = Checkpoints {
+            path: dir.join(format!("{}.json", run_id)),
+            every: 10,
+            resume,
+        };
+        checkpoints.run(state, steps, 1).unwrap();
+        fs::read_to_string(dir.join(run_id).join("rep_0_seed_6.csv")).unwrap()
+    };
+
+    let uninterrupted = run("uninterrupted", 40, false);
+    // stop after 25 steps, the last snapshot is the one of step 20
+    let interrupted = run("interrupted", 25, false);
+    assert_eq!(interrupted.lines().count(), 26);
+    let resumed = run("interrupted", 40, true);
+    assert_eq!(resumed.lines().count(), 41);
+    assert_eq!(resumed, uninterrupted);
+}
+
+#[cfg(feature = "snapshot")]
+#[test]
+fn frames_are_written_every_n_steps() {
+    use wolfsheepgrass::frames::{self, Frames};
+    use wolfsheepgrass::palette;
+
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("frames_are_written_every_n_steps");
+    let _ = fs::remove_dir_all(&dir);
+    let mut state = WsgState::new(small_params(7)).unwrap();
+    state.frames = Some(Frames::new(dir.clone(), 5, 4));
+    let mut schedule = Schedule::new();
+    state.init(&mut schedule);
+    for _ in 0..12 {
+        schedule.step(&mut state);
+    }
+    let mut written: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    written.sort();
+    assert_eq!(
+        written,
+        ["step_000000.png", "step_000005.png", "step_000010.png"]
+    );
+
+    let canvas = frames::render(&state, 4);
+    assert_eq!((canvas.width(), canvas.height()), (120, 120));
+    let events = schedule.get_all_events();
+    let Int2D { x, y } = events
+        .iter()
+        .filter_map(|agent| agent.downcast_ref::<Animal>())
+        .find(|animal| animal.species == 1)
+        .unwrap()
+        .loc;
+    let wolf = palette::WOLF;
+    assert_eq!(
+        canvas.pixel(x as u32 * 4 + 2, (29 - y) as u32 * 4 + 2),
+        [wolf[0], wolf[1], wolf[2]]
+    );
+}
+
+fn sweep(design: Design, points: usize, threads: usize, factors: &[&str]) -> Sweep {
+    Sweep {
+        factors: factors.iter().map(|f| f.parse().unwrap()).collect(),
+        design,
+        points,
+        threads,
+    }
+}
+
+#[test]
+fn grid_sweep_runs_every_replicate_of_every_point() {
+    let factors = [
+        "sheep.prob_reproduction=0.1:0.3",
+        "wolves.prob_reproduction=0.05,0.1",
+    ];
+    let points = sweep(Design::Grid, 3, 1, &factors).points(0);
+    assert_eq!(
+        points,
+        [
+            ["0.1", "0.05"],
+            ["0.1", "0.1"],
+            ["0.2", "0.05"],
+            ["0.2", "0.1"],
+            ["0.3", "0.05"],
+            ["0.3", "0.1"],
+        ]
+    );
+
+    let params = Params {
+        steps: 20,
+        reps: 2,
+        ..small_params(3)
+    };
+    let sequential = sweep(Design::Grid, 3, 1, &factors)
+        .run::<WsgState>(&params)
+        .unwrap();
+    let keys: Vec<_> = sequential
+        .iter()
+        .map(|s| (s.point, s.replicate, s.seed))
+        .collect();
+    assert_eq!(keys.len(), 12);
+    assert_eq!(keys[..3], [(0, 0, 3), (0, 1, 4), (1, 0, 3)]);
+    assert_eq!(sequential[11].values, ["0.3", "0.1"]);
+    assert_eq!(sequential[0].names[0], "sheep");
+    // the replicates do not depend on the thread they run on
+    let parallel = sweep(Design::Grid, 3, 4, &factors)
+        .run::<WsgState>(&params)
+        .unwrap();
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn latin_hypercube_draws_every_stratum_once() {
+    let design = sweep(
+        Design::LatinHypercube,
+        8,
+        1,
+        &["sheep.prob_reproduction=0:0.8", "agents=0:80"],
+    );
+    let points = design.points(9);
+    assert_eq!(points.len(), 8);
+    for (factor, width) in [(0, 0.1), (1, 10.)] {
+        let mut strata: Vec<u32> = points
+            .iter()
+            .map(|point| (point[factor].parse::<f64>().unwrap() / width) as u32)
+            .collect();
+        strata.sort();
+        assert_eq!(strata, (0..8).collect::<Vec<_>>());
+    }
+    assert_eq!(design.points(9), points);
+}
+
+#[test]
+fn default_scenario_matches_defaults() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/default.toml");
+    assert_eq!(Params::from_file(&path).unwrap(), Params::default());
+}